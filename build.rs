@@ -1,19 +1,52 @@
+// 内嵌的预构建索引只在启用 tantivy 功能（默认启用）时生成，以下条目都只用于生成索引
+#[cfg(feature = "tantivy")]
+use std::collections::HashMap;
+#[cfg(feature = "tantivy")]
 use std::env;
+#[cfg(feature = "tantivy")]
 use std::fs;
+#[cfg(feature = "tantivy")]
 use std::path::Path;
+#[cfg(feature = "tantivy")]
 use tantivy::schema::*;
+#[cfg(feature = "tantivy")]
 use tantivy::{doc, Index};
+#[cfg(feature = "tantivy")]
 use tantivy_jieba::JiebaTokenizer;
 
+// Include the error module used by csv_loader
+#[cfg(feature = "tantivy")]
+#[allow(dead_code)]
+#[path = "src/error.rs"]
+mod error;
+
+// Include the constants shared with the library
+#[cfg(feature = "tantivy")]
+#[path = "src/constants.rs"]
+mod constants;
+#[cfg(feature = "tantivy")]
+use constants::{DEFAULT_WRITER_HEAP_SIZE, SCHEMA_VERSION};
+
 // Include the csv_loader module
+// The build script only uses part of it, the rest is library API
+#[cfg(feature = "tantivy")]
+#[allow(dead_code)]
 #[path = "src/csv_loader.rs"]
 mod csv_loader;
-use csv_loader::{build_region_map, load_regions, Region};
-use std::collections::HashMap;
+#[cfg(feature = "tantivy")]
+use csv_loader::{build_region_map, load_regions, select_regions, IndexingPolicy, Region};
+
+fn main() -> anyhow::Result<()> {
+    #[cfg(feature = "tantivy")]
+    build_index()?;
+    Ok(())
+}
 
+#[cfg(feature = "tantivy")]
 fn resolve_address(
     region: &Region,
-    map: &HashMap<u64, Region>,
+    map: &HashMap<u64, &Region>,
+    name: fn(&Region) -> &String,
 ) -> (String, String, String, String) {
     let mut province = String::new();
     let mut city = String::new();
@@ -23,23 +56,24 @@ fn resolve_address(
     let mut current = Some(region);
     while let Some(r) = current {
         match r.deep {
-            0 => province = r.ext_name.clone(),
-            1 => city = r.ext_name.clone(),
-            2 => district = r.ext_name.clone(),
-            3 => county = r.ext_name.clone(),
+            0 => province = name(r).clone(),
+            1 => city = name(r).clone(),
+            2 => district = name(r).clone(),
+            3 => county = name(r).clone(),
             _ => {} // Ignore deeper levels if any
         }
 
         if r.pid == 0 {
             break;
         }
-        current = map.get(&r.pid);
+        current = map.get(&r.pid).copied();
     }
 
     (province, city, district, county)
 }
 
-fn main() -> anyhow::Result<()> {
+#[cfg(feature = "tantivy")]
+fn build_index() -> anyhow::Result<()> {
     // 1. Setup paths
     let out_dir = env::var("OUT_DIR")?;
     let dest_path = Path::new(&out_dir).join("index_loader.rs");
@@ -53,6 +87,16 @@ fn main() -> anyhow::Result<()> {
 
     println!("cargo:rerun-if-changed=src/areas.csv");
     println!("cargo:rerun-if-changed=src/csv_loader.rs");
+    println!("cargo:rerun-if-changed=src/constants.rs");
+    println!("cargo:rerun-if-env-changed=TEXT2LOCATION_INDEXING_POLICY");
+    println!("cargo:rerun-if-env-changed=TEXT2LOCATION_WRITER_HEAP_SIZE");
+    println!("cargo:rerun-if-env-changed=TEXT2LOCATION_WRITER_THREADS");
+
+    // 索引策略：默认所有层级都生成文档，设置为 "leaf-only" 时只索引叶子节点
+    let policy = match env::var("TEXT2LOCATION_INDEXING_POLICY") {
+        Ok(value) => value.parse::<IndexingPolicy>()?,
+        Err(_) => IndexingPolicy::default(),
+    };
 
     // 2. Define Schema (must match src/address_index.rs)
     let mut schema_builder = Schema::builder();
@@ -63,15 +107,34 @@ fn main() -> anyhow::Result<()> {
         .set_fieldnorms(true);
 
     let text_options = TextOptions::default()
-        .set_indexing_options(text_indexing)
+        .set_indexing_options(text_indexing.clone())
         .set_stored();
 
     let province_field = schema_builder.add_text_field("province", text_options.clone());
     let city_field = schema_builder.add_text_field("city", text_options.clone());
     let district_field = schema_builder.add_text_field("district", text_options.clone());
     let county_field = schema_builder.add_text_field("county", text_options.clone());
-    let full_address_field = schema_builder.add_text_field("full_address", text_options.clone());
-    let address_code_field = schema_builder.add_text_field("address_code", STRING | STORED);
+    // full_address 只索引不存储，检索时由层级字段重新拼接
+    let full_address_field = schema_builder.add_text_field(
+        "full_address",
+        TextOptions::default().set_indexing_options(text_indexing),
+    );
+    let province_short_field = schema_builder.add_text_field("province_short", STRING | STORED);
+    let city_short_field = schema_builder.add_text_field("city_short", STRING | STORED);
+    let district_short_field = schema_builder.add_text_field("district_short", STRING | STORED);
+    let county_short_field = schema_builder.add_text_field("county_short", STRING | STORED);
+    let province_pinyin_field = schema_builder.add_text_field("province_pinyin", STORED);
+    let city_pinyin_field = schema_builder.add_text_field("city_pinyin", STORED);
+    let district_pinyin_field = schema_builder.add_text_field("district_pinyin", STORED);
+    let county_pinyin_field = schema_builder.add_text_field("county_pinyin", STORED);
+    let address_code_field = schema_builder.add_text_field("address_code", STRING | STORED | FAST);
+    let deep_field = schema_builder.add_u64_field("deep", INDEXED | STORED | FAST);
+    let id_field = schema_builder.add_u64_field("id", STORED);
+    let pid_field = schema_builder.add_u64_field("pid", STORED);
+    let population_field = schema_builder.add_u64_field("population", FAST);
+    let lat_field = schema_builder.add_f64_field("lat", FAST);
+    let lng_field = schema_builder.add_f64_field("lng", FAST);
+    let name_field = schema_builder.add_text_field("name", STRING);
 
     let schema = schema_builder.build();
 
@@ -85,33 +148,75 @@ fn main() -> anyhow::Result<()> {
     let regions = load_regions(csv_path)?;
     let region_map = build_region_map(&regions);
 
-    let mut index_writer = index.writer(50_000_000)?;
-
-    for region in &regions {
-        let (province, city, district, county) = resolve_address(region, &region_map);
+    // 写入器内存预算（字节）和线程数，默认 DEFAULT_WRITER_HEAP_SIZE、按 CPU 数量
+    let heap_size = match env::var("TEXT2LOCATION_WRITER_HEAP_SIZE") {
+        Ok(value) => value.trim().parse()?,
+        Err(_) => DEFAULT_WRITER_HEAP_SIZE,
+    };
+    let mut index_writer = match env::var("TEXT2LOCATION_WRITER_THREADS") {
+        Ok(value) => index.writer_with_num_threads(value.trim().parse()?, heap_size)?,
+        Err(_) => index.writer(heap_size)?,
+    };
+
+    for region in select_regions(&regions, policy) {
+        let (province, city, district, county) =
+            resolve_address(region, &region_map, |r| &r.ext_name);
+        let (short_province, short_city, short_district, short_county) =
+            resolve_address(region, &region_map, |r| &r.name);
+        let (pinyin_province, pinyin_city, pinyin_district, pinyin_county) =
+            resolve_address(region, &region_map, |r| &r.pinyin);
         let full = format!("{} {} {} {}", province, city, district, county);
 
-        index_writer.add_document(doc!(
+        let mut document = doc!(
             province_field => province,
             city_field => city,
             district_field => district,
             county_field => county,
             full_address_field => full,
-            address_code_field => region.ext_id.clone()
-        ))?;
+            province_short_field => short_province,
+            city_short_field => short_city,
+            district_short_field => short_district,
+            county_short_field => short_county,
+            province_pinyin_field => pinyin_province,
+            city_pinyin_field => pinyin_city,
+            district_pinyin_field => pinyin_district,
+            county_pinyin_field => pinyin_county,
+            address_code_field => region.ext_id.clone(),
+            name_field => region.ext_name.clone(),
+            deep_field => u64::from(region.deep),
+            id_field => region.id,
+            pid_field => region.pid,
+            population_field => region.population.unwrap_or(0)
+        );
+        if let Some((lat, lng)) = region.location() {
+            document.add_f64(lat_field, lat);
+            document.add_f64(lng_field, lng);
+        }
+
+        index_writer.add_document(document)?;
     }
 
-    index_writer.commit()?;
+    // 在元数据中记录 schema 版本，供 AddressIndexBuilder::open_in_dir 检查
+    let mut prepared = index_writer.prepare_commit()?;
+    prepared.set_payload(&format!("text2location-schema:{}", SCHEMA_VERSION));
+    prepared.commit()?;
+
+    // 合并为一个段再嵌入，减小嵌入的索引体积并降低查询延迟
+    let segment_ids = index.searchable_segment_ids()?;
+    if segment_ids.len() > 1 {
+        index_writer.merge(&segment_ids).wait()?;
+    }
+    index_writer.wait_merging_threads()?;
 
-    // Explicitly drop writer and index to ensure locks are released and files flushed
-    drop(index_writer);
+    // Explicitly drop index to ensure files are flushed (the writer was consumed above)
     drop(index);
 
     // 5. Generate Rust code to embed the index
     let mut code = String::new();
-    code.push_str("use tantivy::directory::RamDirectory;\n");
-    code.push_str("use std::io::Write;\n\n");
-    code.push_str("pub fn load_index_directory() -> anyhow::Result<RamDirectory> {\n");
+    code.push_str("use std::io::Write;\n");
+    code.push_str("use std::path::Path;\n");
+    code.push_str("use tantivy::directory::{Directory, RamDirectory, TerminatingWrite};\n\n");
+    code.push_str("pub fn load_index_directory() -> tantivy::Result<RamDirectory> {\n");
     code.push_str("    let dir = RamDirectory::create();\n");
 
     for entry in fs::read_dir(&index_dir)? {
@@ -0,0 +1,413 @@
+use crate::error::{Result, Text2LocationError};
+use crate::locale::{Locale, Localized};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// 地址查询结果
+///
+/// 可以通过 serde（如 JSON）完整地序列化和反序列化；单行文本格式（Display）
+/// 只包含编码和各级名称，`FromStr` 解析时其余字段取默认值。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressResult {
+    pub address_code: String,
+    pub province: String,
+    pub city: String,
+    pub district: String,
+    pub county: String,
+    /// 各级全称拼接成的完整地址，如“广东省梅州市兴宁市”，分隔符见
+    /// `AddressIndexBuilder::full_address_separator`
+    pub full_address: String,
+    /// 各级简称（如“广东”“梅州”），调用方可按字段选择全称或简称
+    #[serde(default, skip_serializing_if = "LevelNames::is_empty")]
+    pub short_names: LevelNames,
+    /// 各级简称的拼音（如“guang dong”），只在 `AddressIndexBuilder::pinyin` 开启时返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinyin: Option<LevelNames>,
+    /// 源数据中的区划 id，可用于与源数据关联
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    /// 源数据中的上级区划 id（省级为 0），可用于沿区划树向上查找
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u64>,
+    /// 层级（0 省 / 1 市 / 2 区县 / 3 乡镇街道）
+    pub deep: u8,
+    /// 解析到的最深层级，与 `deep` 对应；只解析到省或市的结果可转人工复核
+    pub granularity: AdminLevel,
+    /// 排序得分（已包含人口、位置等加权）
+    pub score: f32,
+    /// 构建索引时挂载的额外字段（字段名 -> 值）
+    #[serde(default)]
+    pub extra: BTreeMap<String, String>,
+    /// 命中的区划已撤销时，结果替换为继任区划，这里记录原区划编码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirected_from: Option<String>,
+    /// 按区划变更表并入或更名为该区划的历史编码，便于与按旧编码存储的数据关联
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub former_codes: Vec<String>,
+    /// 该解释在本次返回的候选中所占的概率，只在 `SearchOptions::interpretations` 开启时计算
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub probability: Option<f32>,
+    /// 各级全称中与查询词匹配的片段，只在 `SearchOptions::highlight` 开启时计算
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<Highlight>,
+}
+
+/// 结果中某一级全称里与查询词匹配的片段，供界面加粗显示
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Highlight {
+    /// 片段所在的层级
+    pub level: AdminLevel,
+    /// 在该级全称中的起始字节位置
+    pub start: usize,
+    /// 在该级全称中的结束字节位置（不含）
+    pub end: usize,
+}
+
+/// 省市区县各级的一组名称（简称、拼音等），与全称一一对应
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelNames {
+    pub province: String,
+    pub city: String,
+    pub district: String,
+    pub county: String,
+}
+
+impl LevelNames {
+    /// 各级名称是否都为空（数据中没有该列）
+    pub fn is_empty(&self) -> bool {
+        self.province.is_empty()
+            && self.city.is_empty()
+            && self.district.is_empty()
+            && self.county.is_empty()
+    }
+
+    /// 某一级的名称
+    pub fn get(&self, level: AdminLevel) -> &str {
+        match level {
+            AdminLevel::Province => &self.province,
+            AdminLevel::City => &self.city,
+            AdminLevel::District => &self.district,
+            AdminLevel::County => &self.county,
+        }
+    }
+}
+
+impl AddressResult {
+    /// 省市区县层级链，用于判断两个结果是否指向同一地址
+    #[cfg(feature = "tantivy")]
+    pub(crate) fn chain(&self) -> (String, String, String, String) {
+        (
+            self.province.clone(),
+            self.city.clone(),
+            self.district.clone(),
+            self.county.clone(),
+        )
+    }
+
+    /// 某一级的全称
+    pub fn level_name(&self, level: AdminLevel) -> &str {
+        match level {
+            AdminLevel::Province => &self.province,
+            AdminLevel::City => &self.city,
+            AdminLevel::District => &self.district,
+            AdminLevel::County => &self.county,
+        }
+    }
+
+    /// 用 `open` 和 `close` 包围某一级全称中与查询词匹配的片段，
+    /// 如 `highlighted(AdminLevel::District, "<b>", "</b>")` 得到“<b>兴宁</b>市”
+    pub fn highlighted(&self, level: AdminLevel, open: &str, close: &str) -> String {
+        let name = self.level_name(level);
+        let mut out = String::with_capacity(name.len());
+        let mut last = 0;
+        for h in self.highlights.iter().filter(|h| h.level == level) {
+            // 反序列化得到的片段可能越界、重叠或不在字符边界上，这类片段直接跳过
+            let start = h.start.max(last);
+            let (Some(before), Some(matched)) = (name.get(last..start), name.get(start..h.end))
+            else {
+                continue;
+            };
+            if matched.is_empty() {
+                continue;
+            }
+            out.push_str(before);
+            out.push_str(open);
+            out.push_str(matched);
+            out.push_str(close);
+            last = h.end;
+        }
+        out.push_str(&name[last..]);
+        out
+    }
+
+    /// 按指定语言的标签格式化为单行文本，如 `result.localized(Locale::En).to_string()`
+    pub fn localized(&self, locale: Locale) -> Localized<'_> {
+        Localized::new(self, locale)
+    }
+}
+
+impl fmt::Display for AddressResult {
+    /// 格式化为带简体中文标签的单行文本，其他语言见 `localized`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.localized(Locale::ZhCn).fmt(f)
+    }
+}
+
+impl FromStr for AddressResult {
+    type Err = Text2LocationError;
+
+    /// 解析 Display（或任一语言的 `localized`）输出的单行文本
+    ///
+    /// 只恢复编码和各级名称：`full_address` 按无分隔符拼接，`deep` 取最深的非空层级，
+    /// 得分为 0，其余字段为空。
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Text2LocationError::InvalidInput(format!("无法解析的地址结果: {}", s));
+        let parts: Vec<(&str, &str)> = s
+            .split('|')
+            .map(|part| part.split_once(':').map(|(k, v)| (k.trim(), v.trim())))
+            .collect::<Option<_>>()
+            .ok_or_else(invalid)?;
+        let labels: Vec<&str> = parts.iter().map(|(label, _)| *label).collect();
+        if !Locale::ALL
+            .iter()
+            .any(|locale| locale.labels() == labels[..])
+        {
+            return Err(invalid());
+        }
+        let [address_code, province, city, district, county] =
+            [0, 1, 2, 3, 4].map(|i| parts[i].1.to_string());
+        let levels = [
+            province.as_str(),
+            city.as_str(),
+            district.as_str(),
+            county.as_str(),
+        ];
+        let deep = levels
+            .iter()
+            .rposition(|name| !name.is_empty())
+            .unwrap_or(0) as u8;
+        Ok(AddressResult {
+            full_address: join_address(levels, ""),
+            address_code,
+            province,
+            city,
+            district,
+            county,
+            short_names: LevelNames::default(),
+            pinyin: None,
+            id: None,
+            pid: None,
+            deep,
+            granularity: AdminLevel::from_deep(deep).unwrap_or(AdminLevel::Province),
+            score: 0.0,
+            extra: BTreeMap::new(),
+            redirected_from: None,
+            former_codes: Vec::new(),
+            probability: None,
+            highlights: Vec::new(),
+        })
+    }
+}
+
+/// 行政区划层级，对应数据中的 deep 列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminLevel {
+    /// 省级
+    Province,
+    /// 地级市
+    City,
+    /// 区县
+    District,
+    /// 乡镇街道
+    County,
+}
+
+impl AdminLevel {
+    /// 从省到乡镇街道的所有层级
+    pub const ALL: [AdminLevel; 4] = [Self::Province, Self::City, Self::District, Self::County];
+
+    /// 由 deep 值转换，超出范围的层级返回 None
+    pub fn from_deep(deep: u8) -> Option<Self> {
+        match deep {
+            0 => Some(Self::Province),
+            1 => Some(Self::City),
+            2 => Some(Self::District),
+            3 => Some(Self::County),
+            _ => None,
+        }
+    }
+
+    /// 对应的 deep 值
+    pub fn deep(self) -> u8 {
+        match self {
+            Self::Province => 0,
+            Self::City => 1,
+            Self::District => 2,
+            Self::County => 3,
+        }
+    }
+}
+
+/// 待索引的地址记录
+#[derive(Debug, Clone)]
+pub struct AddressRecord {
+    pub province: String,
+    pub city: String,
+    pub district: String,
+    pub county: String,
+    pub address_code: String,
+    pub level: AdminLevel,
+    /// 源数据中的区划 id 和上级区划 id，会原样返回在查询结果中
+    pub id: Option<u64>,
+    pub pid: Option<u64>,
+    /// 人口（或订单量等重要度指标），用于排序加权
+    pub population: Option<u64>,
+    /// 区划中心点坐标（纬度、经度），用于按位置偏好排序
+    pub location: Option<(f64, f64)>,
+    /// 额外字段值，字段名必须已通过 `AddressIndexBuilder::extra_field` 注册
+    pub extra: BTreeMap<String, String>,
+    /// 各级简称，对应数据中的 name 列，存入索引并在查询结果中返回，也用于区划名称扫描和精确匹配
+    pub short_names: LevelNames,
+    /// 各级简称的拼音，对应数据中的 pinyin 列
+    pub pinyin: LevelNames,
+    /// 本级区划除全称和简称以外的其他名称（如旧称、俗称），用于区划名称扫描和精确匹配
+    pub aliases: Vec<String>,
+}
+
+impl AddressRecord {
+    /// 本级区划名称，即层级链中与 `level` 对应的一级
+    pub fn name(&self) -> &str {
+        match self.level {
+            AdminLevel::Province => &self.province,
+            AdminLevel::City => &self.city,
+            AdminLevel::District => &self.district,
+            AdminLevel::County => &self.county,
+        }
+    }
+
+    /// 本级区划简称，数据中没有简称时为空
+    pub fn short_name(&self) -> &str {
+        match self.level {
+            AdminLevel::Province => &self.short_names.province,
+            AdminLevel::City => &self.short_names.city,
+            AdminLevel::District => &self.short_names.district,
+            AdminLevel::County => &self.short_names.county,
+        }
+    }
+}
+
+impl From<&AddressRecord> for AddressResult {
+    /// 不经过索引直接由地址记录得到结果（得分为 `EXACT_MATCH_SCORE`），
+    /// 各级名称无分隔符拼接，不含拼音和区划变更信息
+    fn from(record: &AddressRecord) -> Self {
+        AddressResult {
+            address_code: record.address_code.clone(),
+            province: record.province.clone(),
+            city: record.city.clone(),
+            district: record.district.clone(),
+            county: record.county.clone(),
+            full_address: join_address(
+                [
+                    record.province.as_str(),
+                    record.city.as_str(),
+                    record.district.as_str(),
+                    record.county.as_str(),
+                ],
+                "",
+            ),
+            short_names: record.short_names.clone(),
+            pinyin: None,
+            id: record.id,
+            pid: record.pid,
+            deep: record.level.deep(),
+            granularity: record.level,
+            score: EXACT_MATCH_SCORE,
+            extra: record.extra.clone(),
+            redirected_from: None,
+            former_codes: Vec::new(),
+            probability: None,
+            highlights: Vec::new(),
+        }
+    }
+}
+
+/// 精确匹配命中的结果使用的得分，高于模糊搜索的常见得分
+pub const EXACT_MATCH_SCORE: f32 = 100.0;
+
+/// 拼接各级名称，跳过空的层级，直辖市重复的一级（北京市北京市）只保留一次
+pub(crate) fn join_address(names: [&str; 4], separator: &str) -> String {
+    let mut parts: Vec<&str> = names.into_iter().filter(|n| !n.is_empty()).collect();
+    parts.dedup();
+    parts.join(separator)
+}
+
+/// 文本中的名称是否写全了层级链：链上每一级（相邻重名的算一级）都被提到，
+/// 且文本中的名称个数不少于层级数（一个简称不能同时顶替“朝阳市”和“朝阳县”两级）
+#[cfg(any(feature = "tantivy", feature = "fst"))]
+pub(crate) fn complete(chain: &[String], matched: usize, mentions: impl Fn(&str) -> usize) -> bool {
+    let mut levels: Vec<&str> = chain.iter().map(String::as_str).collect();
+    levels.dedup();
+    matched >= levels.len() && levels.iter().all(|name| mentions(name) > 0)
+}
+
+/// 两点之间的球面距离（公里）
+#[cfg(any(feature = "tantivy", feature = "fst"))]
+pub(crate) fn haversine_km((lat1, lng1): (f64, f64), (lat2, lng2): (f64, f64)) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lng = (lng2 - lng1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xingning(highlights: &[(usize, usize)]) -> AddressResult {
+        let mut result = AddressResult::from(&AddressRecord {
+            province: "广东省".to_string(),
+            city: "梅州市".to_string(),
+            district: "兴宁市".to_string(),
+            county: String::new(),
+            address_code: "441481000000".to_string(),
+            level: AdminLevel::District,
+            id: None,
+            pid: None,
+            population: None,
+            location: None,
+            extra: BTreeMap::new(),
+            short_names: LevelNames::default(),
+            pinyin: LevelNames::default(),
+            aliases: Vec::new(),
+        });
+        result.highlights = highlights
+            .iter()
+            .map(|&(start, end)| Highlight {
+                level: AdminLevel::District,
+                start,
+                end,
+            })
+            .collect();
+        result
+    }
+
+    #[test]
+    fn highlighted_skips_invalid_ranges_instead_of_panicking() {
+        let highlighted = |ranges: &[(usize, usize)]| {
+            xingning(ranges).highlighted(AdminLevel::District, "<", ">")
+        };
+        assert_eq!(highlighted(&[(0, 6)]), "<兴宁>市");
+        // 越界、不在字符边界上、起止颠倒的片段跳过
+        assert_eq!(highlighted(&[(6, 100)]), "兴宁市");
+        assert_eq!(highlighted(&[(1, 4)]), "兴宁市");
+        assert_eq!(highlighted(&[(6, 3)]), "兴宁市");
+        // 与前一个片段重叠的部分不再重复输出
+        assert_eq!(highlighted(&[(0, 6), (3, 9)]), "<兴宁><市>");
+        assert_eq!(highlighted(&[(0, 6), (0, 3)]), "<兴宁>市");
+    }
+}
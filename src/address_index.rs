@@ -1,45 +1,584 @@
-use tantivy::collector::TopDocs;
-use tantivy::query::QueryParser;
+pub(crate) use crate::address::{haversine_km, join_address};
+pub use crate::address::{
+    AddressRecord, AddressResult, AdminLevel, Highlight, LevelNames, EXACT_MATCH_SCORE,
+};
+use crate::archive::{Archive, ArchiveHeader};
+use crate::clean::strip_noise;
+use crate::consignee::ADDRESS_CHARS;
+pub use crate::constants::{DEFAULT_WRITER_HEAP_SIZE, SCHEMA_VERSION};
+use crate::dictionary::{AmbiguousName, NameDictionary, NameMatch};
+use crate::error::{Result, Text2LocationError};
+use crate::locale::Locale;
+use crate::memory::{HeapSize, MemoryReport};
+use crate::query::{escape_regex, Q};
+use crate::query_log::{QueryLog, QueryLogEntry};
+use crate::synonyms::Synonyms;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tantivy::collector::{Collector, Count, SegmentCollector, TopDocs};
+use tantivy::directory::{Directory, RamDirectory};
+use tantivy::indexer::PreparedCommit;
+use tantivy::query::{
+    AllQuery, BooleanQuery, ConstScoreQuery, Occur, Query, QueryParser, QueryParserError,
+    RangeQuery, RegexQuery, Scorer, TermQuery, Weight,
+};
 use tantivy::schema::*;
-use tantivy::{doc, Index, IndexReader, ReloadPolicy, TantivyDocument};
+use tantivy::tokenizer::TextAnalyzer;
+use tantivy::{
+    doc, DocAddress, DocId, DocSet, Index, IndexReader, IndexWriter, ReloadPolicy, Score, Searcher,
+    SegmentOrdinal, SegmentReader, TantivyDocument, TantivyError, TERMINATED,
+};
 use tantivy_jieba::JiebaTokenizer;
+use tracing::{debug, info, instrument};
 
-/// 地址查询结果
+/// 结构化的地址查询请求
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AddressQuery {
+    /// 待解析的地址文本
+    pub query: String,
+    /// 只返回指定层级的结果
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<AdminLevel>,
+    /// 只在指定层级的名称字段中搜索，见 `SearchOptions::field`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<AdminLevel>,
+    /// 优先返回指定层级的结果，见 `SearchOptions::prefer_level`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefer_level: Option<AdminLevel>,
+    /// 只返回至少解析到指定层级的结果，见 `SearchOptions::require_at_least`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_at_least: Option<AdminLevel>,
+    /// 返回结果数量上限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// 查询的数据集名称，由 `Datasets` 选择索引，单个索引上查询时忽略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dataset: Option<String>,
+    /// 返回去重后的不同解释及其概率，见 `SearchOptions::interpretations`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub interpretations: bool,
+    /// 返回各级全称中与查询匹配的片段，见 `SearchOptions::highlight`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub highlight: bool,
+    /// 查询的时间上限（毫秒），见 `SearchOptions::timeout`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// 查询中没有已知的区划名称时报错而不搜索，见 `SearchOptions::require_location_signal`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub require_location_signal: bool,
+}
+
+impl AddressQuery {
+    /// 创建只包含地址文本的查询
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            level: None,
+            field: None,
+            prefer_level: None,
+            require_at_least: None,
+            limit: None,
+            dataset: None,
+            interpretations: false,
+            highlight: false,
+            timeout_ms: None,
+            require_location_signal: false,
+        }
+    }
+
+    /// 转换为搜索选项
+    pub fn options(&self) -> SearchOptions {
+        let mut options = SearchOptions::new();
+        if let Some(limit) = self.limit {
+            options = options.limit(limit);
+        }
+        if let Some(level) = self.level {
+            options = options.level(level);
+        }
+        if let Some(field) = self.field {
+            options = options.field(field);
+        }
+        if let Some(level) = self.prefer_level {
+            options = options.prefer_level(level);
+        }
+        if let Some(level) = self.require_at_least {
+            options = options.require_at_least(level);
+        }
+        if self.interpretations {
+            options = options.interpretations(true);
+        }
+        if self.highlight {
+            options = options.highlight(true);
+        }
+        if let Some(timeout_ms) = self.timeout_ms {
+            options = options.timeout(Duration::from_millis(timeout_ms));
+        }
+        if self.require_location_signal {
+            options = options.require_location_signal(true);
+        }
+        options
+    }
+}
+
+/// 搜索选项
 #[derive(Debug, Clone)]
-pub struct AddressResult {
-    pub address_code: String,
-    pub province: String,
-    pub city: String,
-    pub district: String,
-    pub county: String,
-}
-
-impl AddressResult {
-    /// 格式化为字符串
-    pub fn to_string(&self) -> String {
-        format!(
-            "编码: {} | 省: {} | 市: {} | 区: {} | 县: {}",
-            self.address_code, self.province, self.city, self.district, self.county
-        )
+pub struct SearchOptions {
+    limit: usize,
+    dedup: bool,
+    interpretations: bool,
+    highlight: bool,
+    level: Option<AdminLevel>,
+    field: Option<AdminLevel>,
+    extra_filters: Vec<(String, String)>,
+    bias: Option<(f64, f64)>,
+    prefer_level: Option<AdminLevel>,
+    require_at_least: Option<AdminLevel>,
+    timeout: Option<Duration>,
+    require_location_signal: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            limit: 10,
+            dedup: false,
+            interpretations: false,
+            highlight: false,
+            level: None,
+            field: None,
+            extra_filters: Vec::new(),
+            bias: None,
+            prefer_level: None,
+            require_at_least: None,
+            timeout: None,
+            require_location_signal: false,
+        }
     }
 }
 
-/// 地址索引结构体，封装索引和查询功能
-pub struct AddressIndex {
-    index: Index,
-    reader: IndexReader,
-    province: Field,
-    city: Field,
-    district: Field,
-    county: Field,
-    full_address: Field,
-    address_code: Field,
+impl SearchOptions {
+    /// 创建默认搜索选项（返回前 10 个结果，不去重）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置返回结果数量上限
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// 合并解析出相同省市区县链的结果，只保留得分最高的一条
+    pub fn dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// 把结果作为有歧义输入（如“朝阳”）的不同解释返回：与查询相关的层级链相容的结果
+    /// （如辽宁朝阳市下的各区县）只保留得分最高的一条，
+    /// 并按得分在返回的候选中所占比例计算每条解释的 `probability`（总和为 1）
+    ///
+    /// 概率只在本次返回的候选之间分配，调用方可据此判断是否需要向用户确认。
+    pub fn interpretations(mut self, interpretations: bool) -> Self {
+        self.interpretations = interpretations;
+        self
+    }
+
+    /// 为每个结果计算 `highlights`：各级全称中与查询分词匹配的片段
+    pub fn highlight(mut self, highlight: bool) -> Self {
+        self.highlight = highlight;
+        self
+    }
+
+    /// 只返回指定层级的结果
+    pub fn level(mut self, level: AdminLevel) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// 只在指定层级的名称字段中搜索，而不是在拼接的完整地址中搜索
+    ///
+    /// 查询词只与该层级的名称比较（如只比较区县名），下级区划因层级链中包含该名称同样会命中，
+    /// 需要只返回该层级本身时再配合 `level` 过滤，见 `AddressIndex::search_field`。
+    pub fn field(mut self, level: AdminLevel) -> Self {
+        self.field = Some(level);
+        self
+    }
+
+    /// 只返回额外字段等于指定值的结果，字段需以 indexed 方式注册
+    pub fn extra_filter(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra_filters.push((name.into(), value.into()));
+        self
+    }
+
+    /// 优先返回靠近给定位置（纬度、经度）的结果
+    ///
+    /// 适用于已知用户大致位置（如 IP 定位）时解析有歧义的地名，
+    /// 没有坐标的区划不受影响。
+    pub fn bias(mut self, lat: f64, lng: f64) -> Self {
+        self.bias = Some((lat, lng));
+        self
+    }
+
+    /// 同样匹配查询文本时，优先返回指定层级的区划
+    ///
+    /// 例如做城市级统计时用 `prefer_level(AdminLevel::City)`，“朝阳”会先返回辽宁朝阳市
+    /// 而不是其下的区县或北京朝阳区。与 `level` 不同，其他层级的结果仍会返回，只是排在后面。
+    pub fn prefer_level(mut self, level: AdminLevel) -> Self {
+        self.prefer_level = Some(level);
+        self
+    }
+
+    /// 只返回至少解析到指定层级的结果，如 `require_at_least(AdminLevel::District)`
+    /// 去掉只解析到省或市的结果，适合需要区县以下地址的末端派送场景
+    pub fn require_at_least(mut self, level: AdminLevel) -> Self {
+        self.require_at_least = Some(level);
+        self
+    }
+
+    /// 设置查询的时间上限，超时后中止搜索并返回 `Text2LocationError::Timeout`
+    ///
+    /// 用于防止病态的模糊查询在大数据集上长时间占用线程，默认不限制。
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 查询中没有任何已知的区划名称（见 `AddressIndex::has_location_signal`）时不搜索，
+    /// 返回 `Text2LocationError::NoLocationSignal`
+    ///
+    /// 分词后的 OR 查询只要有一个词命中就会返回结果，“人民医院”这类不含地名的输入
+    /// 会得到“人民路街道”等偶然命中的低分结果；开启后调用方可以区分“没有地名”和“未找到”。
+    ///
+    /// 对接受搜索选项的文本查询生效（`search`、`search_codes` 及 `AddressQuery`）；
+    /// `resolve`、`search_first`、`search_iter` 使用默认选项，不做此检查，
+    /// 需要时先调用 `AddressIndex::has_location_signal`。
+    pub fn require_location_signal(mut self, require: bool) -> Self {
+        self.require_location_signal = require;
+        self
+    }
 }
 
-impl AddressIndex {
-    /// 创建新的地址索引
-    pub fn new() -> anyhow::Result<Self> {
-        println!("正在初始化中文地址索引系统...");
+/// 内置字段名，额外字段不能与之重名
+const BUILTIN_FIELDS: [&str; 21] = [
+    "province",
+    "city",
+    "district",
+    "county",
+    "full_address",
+    "province_short",
+    "city_short",
+    "district_short",
+    "county_short",
+    "province_pinyin",
+    "city_pinyin",
+    "district_pinyin",
+    "county_pinyin",
+    "address_code",
+    "deep",
+    "id",
+    "pid",
+    "population",
+    "lat",
+    "lng",
+    "name",
+];
+
+/// 建索引的进度，通过 `AddressIndexBuilder::progress` 设置的回调报告
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexProgress {
+    /// 已从数据文件读取的行数（由加载数据的调用方报告）
+    RowsLoaded(usize),
+    /// 已写入的文档数和本次要写入的总数，每写入 `PROGRESS_INTERVAL` 条及全部写完时报告
+    DocsWritten { written: usize, total: usize },
+    /// 开始提交（合并内存中的段并写入存储）
+    Committing,
+    /// 提交完成
+    Committed,
+}
+
+/// 元数据中 schema 版本的前缀
+const SCHEMA_VERSION_PREFIX: &str = "text2location-schema:";
+
+/// 提交并在元数据中记录 schema 版本
+fn commit_with_version(mut prepared: PreparedCommit<'_>) -> Result<()> {
+    prepared.set_payload(&format!("{}{}", SCHEMA_VERSION_PREFIX, SCHEMA_VERSION));
+    prepared.commit()?;
+    Ok(())
+}
+
+/// 检查已有索引的 schema 版本和字段，不兼容时给出明确的错误
+///
+/// 没有任何段的空索引不检查版本；版本一致但字段不同（如额外字段不同）同样拒绝。
+fn check_schema(index: &Index, expected: &Schema) -> Result<()> {
+    let metas = index.load_metas()?;
+    let version = metas
+        .payload
+        .as_deref()
+        .and_then(|payload| payload.strip_prefix(SCHEMA_VERSION_PREFIX))
+        .and_then(|version| version.parse::<u32>().ok());
+    match version {
+        Some(SCHEMA_VERSION) => {}
+        None if metas.segments.is_empty() => {}
+        Some(version) => {
+            return Err(Text2LocationError::SchemaMismatch(format!(
+                "索引的 schema 版本为 {}，当前版本为 {}，请用当前版本重新构建索引",
+                version, SCHEMA_VERSION
+            )))
+        }
+        None => {
+            return Err(Text2LocationError::SchemaMismatch(
+                "索引缺少 schema 版本（可能由早期版本或其他程序生成），请用当前版本重新构建索引"
+                    .to_string(),
+            ))
+        }
+    }
+    if index.schema() != *expected {
+        return Err(Text2LocationError::SchemaMismatch(
+            "索引的字段与当前 schema 不一致（额外字段是否相同？），请用当前版本重新构建索引"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 报告写入进度的间隔（文档数）
+pub const PROGRESS_INTERVAL: usize = 10_000;
+
+/// 进度回调
+pub type ProgressCallback = Arc<dyn Fn(IndexProgress) + Send + Sync>;
+
+/// 地址索引构建器，用于在创建索引前注册额外字段
+#[derive(Clone, Default)]
+pub struct AddressIndexBuilder {
+    extra_fields: Vec<(String, bool)>,
+    query_log: Option<QueryLog>,
+    redirects: HashMap<String, String>,
+    synonyms: HashMap<String, String>,
+    hints: HashMap<String, String>,
+    weights: RankingWeights,
+    stopwords: Option<HashSet<String>>,
+    full_address_separator: String,
+    pinyin: bool,
+    #[cfg(feature = "traditional")]
+    traditional: bool,
+    locale: Locale,
+    progress: Option<ProgressCallback>,
+    writer_heap_size: Option<usize>,
+    writer_threads: Option<usize>,
+    token_cache_size: Option<usize>,
+}
+
+impl AddressIndexBuilder {
+    /// 注册额外字段（如 warehouse_id、sales_region）
+    ///
+    /// 额外字段总是被存储并随结果返回；`indexed` 为 true 时按原值整体索引，
+    /// 可通过 `SearchOptions::extra_filter` 精确过滤。
+    pub fn extra_field(mut self, name: impl Into<String>, indexed: bool) -> Self {
+        self.extra_fields.push((name.into(), indexed));
+        self
+    }
+
+    /// 记录每次查询的查询日志（JSONL 文件或回调）
+    pub fn query_log(mut self, query_log: QueryLog) -> Self {
+        self.query_log = Some(query_log);
+        self
+    }
+
+    /// 区划变更表（旧编码 -> 继任编码），命中已撤销的区划时返回继任区划
+    pub fn redirects(mut self, redirects: HashMap<String, String>) -> Self {
+        self.redirects = redirects;
+        self
+    }
+
+    /// 缓存最近多少个查询的分词结果，默认 `DEFAULT_TOKEN_CACHE_SIZE`，设为 0 关闭缓存
+    ///
+    /// 批量解析的输入中常有大量重复地址，命中缓存时不再清理噪声和重新分词。
+    pub fn token_cache_size(mut self, size: usize) -> Self {
+        self.token_cache_size = Some(size);
+        self
+    }
+
+    /// 同义词表（词条 -> 区划名称），如“虹桥”->“上海”、“浦东机场”->“浦东新区”
+    ///
+    /// 查询预处理时，文本中出现的词条会被扩展：对应的区划名称追加到查询中再分词，
+    /// 使地标、俗称等非行政区划名称的查询也能解析到区划。
+    pub fn synonyms(mut self, synonyms: HashMap<String, String>) -> Self {
+        self.synonyms = synonyms;
+        self
+    }
+
+    /// 提示词表（关键词 -> 区划名称），如“深南大道”->“深圳市”，用于区分同名区划
+    ///
+    /// 与同义词不同，提示词不扩展查询、不增加召回：原文中出现提示词时，
+    /// 层级链中含有对应区划的结果只额外获得 `RankingWeights::hint`（默认 `HINT_SCORE`）的得分，
+    /// 在“南山区”“鼓楼区”等同名区划得分接近时决定排序。
+    pub fn hints(mut self, hints: HashMap<String, String>) -> Self {
+        self.hints = hints;
+        self
+    }
+
+    /// 排序权重，默认为 `RankingWeights::default()`，可用 `tuning::tune` 从标注的查询中拟合
+    pub fn weights(mut self, weights: RankingWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// 分词后丢弃的停用词，替换默认的 `DEFAULT_STOPWORDS`；传入空列表可关闭停用词
+    ///
+    /// 粘贴的文本中常带有“中国”“收货地址”等与区划无关的词，它们进入 OR 查询后会稀释得分。
+    pub fn stopwords<I, S>(mut self, stopwords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.stopwords = Some(stopwords.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// 拼接结果中 `full_address` 时各级之间的分隔符，默认不加分隔符
+    pub fn full_address_separator(mut self, separator: impl Into<String>) -> Self {
+        self.full_address_separator = separator.into();
+        self
+    }
+
+    /// 在结果中返回各级名称的拼音，供只能显示 ASCII 的系统使用（如国际运单）
+    pub fn pinyin(mut self, pinyin: bool) -> Self {
+        self.pinyin = pinyin;
+        self
+    }
+
+    /// 写入文档时报告进度（已写入文档数、提交阶段），供命令行或服务显示进度条
+    pub fn progress(mut self, progress: impl Fn(IndexProgress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// 写入文档时索引写入器的内存预算（字节，所有线程共享），默认 `DEFAULT_WRITER_HEAP_SIZE`
+    ///
+    /// 预算越大，写入时生成的段越少；内存受限的容器可以调小，
+    /// 但每个写入线程至少需要约 15MB，不足时写入会报错。
+    pub fn writer_heap_size(mut self, bytes: usize) -> Self {
+        self.writer_heap_size = Some(bytes);
+        self
+    }
+
+    /// 索引写入线程数，默认由 tantivy 按 CPU 数量和内存预算决定
+    pub fn writer_threads(mut self, threads: usize) -> Self {
+        self.writer_threads = Some(threads);
+        self
+    }
+
+    /// `search_address` 等文本输出使用的标签语言，默认简体中文
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// 以繁体中文返回结果中的地名（各级名称、简称和 `full_address`），
+    /// 供面向港澳台用户的界面使用；索引和查询仍使用简体，繁体输入同样不会被识别
+    #[cfg(feature = "traditional")]
+    pub fn traditional(mut self, traditional: bool) -> Self {
+        self.traditional = traditional;
+        self
+    }
+
+    /// 创建地址索引
+    pub fn build(self) -> Result<AddressIndex> {
+        // 2. 创建索引 (在内存中)
+        // 实际生产环境可以使用 Index::create_in_dir 在磁盘创建索引
+        self.build_with(|schema| Ok(Index::create_in_ram(schema)))
+    }
+
+    /// 从 `AddressIndex::export` 导出的索引文件加载，不需要重新读取数据文件建索引
+    ///
+    /// 额外字段、区划变更表和区划名称词典取自索引文件（构建器上设置的额外字段被忽略，
+    /// 构建器上设置了区划变更表时以构建器为准）；分隔符、拼音等输出选项仍按构建器设置。
+    pub fn import(mut self, path: impl AsRef<Path>) -> Result<AddressIndex> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| {
+            Text2LocationError::Archive(format!("无法打开 {}: {}", path.display(), e))
+        })?;
+        let Archive { header, files } = Archive::read(BufReader::new(file))?;
+        self.extra_fields = header.extra_fields;
+        if self.redirects.is_empty() {
+            self.redirects = header.redirects;
+        }
+
+        let address_index = self.build_with(|schema| {
+            let directory = RamDirectory::create();
+            for (name, data) in &files {
+                directory
+                    .atomic_write(Path::new(name), data)
+                    .map_err(TantivyError::from)?;
+            }
+            let index = Index::open(directory)?;
+            check_schema(&index, &schema)?;
+            Ok(index)
+        })?;
+        *address_index.dictionary.write().unwrap() =
+            NameDictionary::from_entries(header.dictionary);
+        info!(
+            num_docs = address_index.reader.searcher().num_docs(),
+            "已从索引文件加载"
+        );
+        Ok(address_index)
+    }
+
+    /// 打开磁盘上已有的 tantivy 索引目录（如 build.rs 生成的索引）
+    ///
+    /// 索引的 schema 版本和字段必须与当前版本一致，否则返回错误而不是在查询时失败。
+    /// 区划名称词典由存储的文档重建，目录中不保存其他名称（`AddressRecord::aliases`），
+    /// 需要时请使用 `import` 加载导出的索引文件。
+    pub fn open_in_dir(self, dir: impl AsRef<Path>) -> Result<AddressIndex> {
+        let dir = dir.as_ref();
+        let address_index = self.open_with(|| Ok(Index::open_in_dir(dir)?))?;
+        info!(
+            num_docs = address_index.reader.searcher().num_docs(),
+            path = %dir.display(),
+            "已打开索引目录"
+        );
+        Ok(address_index)
+    }
+
+    /// 加载编译时由 build.rs 生成并嵌入的索引（默认数据集 `src/areas.csv`）
+    ///
+    /// 与 `open_in_dir` 一样由存储的文档重建区划名称词典，不需要在运行时读取数据文件。
+    pub fn prebuilt(self) -> Result<AddressIndex> {
+        let address_index =
+            self.open_with(|| Ok(Index::open(crate::prebuilt::load_index_directory()?)?))?;
+        info!(
+            num_docs = address_index.reader.searcher().num_docs(),
+            "已加载内置索引"
+        );
+        Ok(address_index)
+    }
+
+    /// 打开已有的索引并检查 schema，再由存储的文档重建区划名称词典
+    fn open_with(self, open: impl FnOnce() -> Result<Index>) -> Result<AddressIndex> {
+        let address_index = self.build_with(|schema| {
+            let index = open()?;
+            check_schema(&index, &schema)?;
+            Ok(index)
+        })?;
+        let records = address_index.stored_records()?;
+        address_index.dictionary.write().unwrap().replace(&records);
+        Ok(address_index)
+    }
+
+    /// 定义 Schema 并用 `create` 创建（或打开）索引
+    fn build_with(self, create: impl FnOnce(Schema) -> Result<Index>) -> Result<AddressIndex> {
+        info!("正在初始化中文地址索引系统...");
 
         // 1. 定义 Schema
         // Schema 描述了文档的结构：省市区县字段和地址编码
@@ -55,7 +594,7 @@ impl AddressIndex {
 
         // 设置字段选项：使用上面定义的索引配置，并存储原始文本以便检索时返回
         let text_options = TextOptions::default()
-            .set_indexing_options(text_indexing)
+            .set_indexing_options(text_indexing.clone())
             .set_stored();
 
         // 添加省市区县字段（可搜索、可存储）
@@ -65,21 +604,72 @@ impl AddressIndex {
         let county = schema_builder.add_text_field("county", text_options.clone());
 
         // 关键修改：增加完整地址合并列
-        let full_address = schema_builder.add_text_field("full_address", text_options.clone());
+        // 只索引不存储：它与四个层级字段重复，结果中的 full_address 由层级字段重新拼接
+        let full_address = schema_builder.add_text_field(
+            "full_address",
+            TextOptions::default().set_indexing_options(text_indexing),
+        );
 
-        // 地址编码字段（仅存储，不索引，用于唯一标识）
-        let address_code = schema_builder.add_text_field("address_code", STRING | STORED);
+        // 各级简称（整体作为一个词索引，并存储），与全称一起返回
+        let province_short = schema_builder.add_text_field("province_short", STRING | STORED);
+        let city_short = schema_builder.add_text_field("city_short", STRING | STORED);
+        let district_short = schema_builder.add_text_field("district_short", STRING | STORED);
+        let county_short = schema_builder.add_text_field("county_short", STRING | STORED);
 
-        let schema = schema_builder.build();
+        // 各级简称的拼音（仅存储），按需在结果中返回
+        let province_pinyin = schema_builder.add_text_field("province_pinyin", STORED);
+        let city_pinyin = schema_builder.add_text_field("city_pinyin", STORED);
+        let district_pinyin = schema_builder.add_text_field("district_pinyin", STORED);
+        let county_pinyin = schema_builder.add_text_field("county_pinyin", STORED);
 
-        // 2. 创建索引 (在内存中)
-        // 实际生产环境可以使用 Index::create_in_dir 在磁盘创建索引
-        let index = Index::create_in_ram(schema.clone());
+        // 地址编码字段，整体索引为一个词（不分词），用于唯一标识和按编码查找文档
+        // 同时作为 fast field，用于同分结果的稳定排序和只返回编码的搜索
+        let address_code = schema_builder.add_text_field("address_code", STRING | STORED | FAST);
+
+        // 层级字段（0 省 / 1 市 / 2 区县 / 3 乡镇街道），索引后用于按层级过滤
+        let deep = schema_builder.add_u64_field("deep", INDEXED | STORED | FAST);
+
+        // 源数据中的区划 id 和上级 id（仅存储），原样返回给调用方
+        let id = schema_builder.add_u64_field("id", STORED);
+        let pid = schema_builder.add_u64_field("pid", STORED);
+
+        // 人口字段（仅 fast field），用于按重要度加权排序
+        let population = schema_builder.add_u64_field("population", FAST);
+
+        // 中心点坐标（仅 fast field），用于按位置偏好排序
+        let lat = schema_builder.add_f64_field("lat", FAST);
+        let lng = schema_builder.add_f64_field("lng", FAST);
+
+        // 本级区划全称（整体作为一个词索引，不存储），用于正则匹配
+        let name = schema_builder.add_text_field("name", STRING);
+
+        // 调用方注册的额外字段
+        let mut extra_fields = Vec::with_capacity(self.extra_fields.len());
+        for (name, indexed) in self.extra_fields {
+            if BUILTIN_FIELDS.contains(&name.as_str())
+                || extra_fields.iter().any(|(n, _)| n == &name)
+            {
+                return Err(Text2LocationError::InvalidInput(format!(
+                    "额外字段名重复或与内置字段冲突: {}",
+                    name
+                )));
+            }
+            let options = if indexed {
+                STRING | STORED
+            } else {
+                STORED.into()
+            };
+            let field = schema_builder.add_text_field(&name, options);
+            extra_fields.push((name, field));
+        }
+
+        let schema = schema_builder.build();
+        let index = create(schema)?;
 
         // 3. 注册 Jieba 分词器
         // 这是关键步骤，让 tantivy 知道如何处理中文
-        let tokenizer = JiebaTokenizer {};
-        index.tokenizers().register("jieba", tokenizer);
+        let tokenizer = TextAnalyzer::from(JiebaTokenizer {});
+        index.tokenizers().register("jieba", tokenizer.clone());
 
         // 4. 创建 Reader
         let reader = index
@@ -87,7 +677,7 @@ impl AddressIndex {
             .reload_policy(ReloadPolicy::Manual)
             .try_into()?;
 
-        Ok(Self {
+        Ok(AddressIndex {
             index,
             reader,
             province,
@@ -95,51 +685,478 @@ impl AddressIndex {
             district,
             county,
             full_address,
+            province_short,
+            city_short,
+            district_short,
+            county_short,
+            province_pinyin,
+            city_pinyin,
+            district_pinyin,
+            county_pinyin,
             address_code,
+            deep,
+            id,
+            pid,
+            population,
+            lat,
+            lng,
+            name,
+            extra_fields,
+            query_log: self.query_log,
+            former_codes: former_codes(&self.redirects),
+            redirects: self.redirects,
+            synonyms: RwLock::new(Synonyms::new(&self.synonyms)),
+            hints: RwLock::new(Synonyms::new(&self.hints)),
+            weights: self.weights,
+            stopwords: self
+                .stopwords
+                .unwrap_or_else(|| DEFAULT_STOPWORDS.iter().map(|w| w.to_string()).collect()),
+            full_address_separator: self.full_address_separator,
+            include_pinyin: self.pinyin,
+            #[cfg(feature = "traditional")]
+            traditional: self.traditional,
+            locale: self.locale,
+            progress: self.progress,
+            writer_heap_size: self.writer_heap_size.unwrap_or(DEFAULT_WRITER_HEAP_SIZE),
+            writer_threads: self.writer_threads,
+            dictionary: RwLock::default(),
+            tokenizer,
+            token_cache: TokenCache::new(self.token_cache_size.unwrap_or(DEFAULT_TOKEN_CACHE_SIZE)),
         })
     }
+}
+
+impl fmt::Debug for AddressIndexBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AddressIndexBuilder")
+            .field("extra_fields", &self.extra_fields)
+            .field("query_log", &self.query_log)
+            .field("redirects", &self.redirects)
+            .field("synonyms", &self.synonyms)
+            .field("hints", &self.hints)
+            .field("weights", &self.weights)
+            .field("stopwords", &self.stopwords)
+            .field("full_address_separator", &self.full_address_separator)
+            .field("pinyin", &self.pinyin)
+            .field("locale", &self.locale)
+            .field("progress", &self.progress.is_some())
+            .field("writer_heap_size", &self.writer_heap_size)
+            .field("writer_threads", &self.writer_threads)
+            .field("token_cache_size", &self.token_cache_size)
+            .finish_non_exhaustive()
+    }
+}
+
+/// 地址索引结构体，封装索引和查询功能
+pub struct AddressIndex {
+    index: Index,
+    reader: IndexReader,
+    province: Field,
+    city: Field,
+    district: Field,
+    county: Field,
+    full_address: Field,
+    province_short: Field,
+    city_short: Field,
+    district_short: Field,
+    county_short: Field,
+    province_pinyin: Field,
+    city_pinyin: Field,
+    district_pinyin: Field,
+    county_pinyin: Field,
+    address_code: Field,
+    deep: Field,
+    id: Field,
+    pid: Field,
+    population: Field,
+    lat: Field,
+    lng: Field,
+    name: Field,
+    extra_fields: Vec<(String, Field)>,
+    query_log: Option<QueryLog>,
+    redirects: HashMap<String, String>,
+    /// 现行编码 -> 历史编码，由 `redirects` 反向得到
+    former_codes: HashMap<String, Vec<String>>,
+    /// 查询预处理时扩展的同义词，可用 `replace_synonyms` 整体替换
+    synonyms: RwLock<Synonyms>,
+    /// 提示词 -> 区划名称，用于区分同名区划，可用 `replace_hints` 整体替换
+    hints: RwLock<Synonyms>,
+    /// 文本得分以外的排序权重
+    weights: RankingWeights,
+    /// 分词后丢弃的停用词
+    stopwords: HashSet<String>,
+    /// 结果中 `full_address` 的分隔符
+    full_address_separator: String,
+    /// 是否在结果中返回拼音
+    include_pinyin: bool,
+    /// 是否以繁体中文返回地名
+    #[cfg(feature = "traditional")]
+    traditional: bool,
+    /// 文本输出的标签语言
+    locale: Locale,
+    /// 写入文档时的进度回调
+    progress: Option<ProgressCallback>,
+    /// 索引写入器的内存预算（字节）
+    writer_heap_size: usize,
+    /// 索引写入线程数，None 时由 tantivy 决定
+    writer_threads: Option<usize>,
+    /// 区划名称词典，随文档一起写入，用于 `resolve` 的精确匹配
+    dictionary: RwLock<NameDictionary>,
+    /// 查询分词使用的 jieba 分词器，避免每次查询都从索引的分词器表中查找
+    tokenizer: TextAnalyzer,
+    /// 最近查询的分词结果
+    token_cache: TokenCache,
+}
+
+impl AddressIndex {
+    /// 创建新的地址索引
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
+
+    /// 创建地址索引构建器
+    pub fn builder() -> AddressIndexBuilder {
+        AddressIndexBuilder::default()
+    }
 
     /// 批量添加地址文档
-    pub fn add_documents(
-        &self,
-        docs: &[(String, String, String, String, String)],
-    ) -> anyhow::Result<()> {
-        let mut index_writer = self.index.writer(50_000_000)?;
-        for (province_val, city_val, district_val, county_val, address_code_val) in docs {
-            // 构建完整地址字符串
-            // 简单的拼接其实也行，因为我们已经禁用了 fieldnorm
-            // 为了更好的搜索体验，我们保留层级结构
-            // 使用空格分隔，以便更好地支持分词
-            let full = format!(
-                "{} {} {} {}",
-                province_val, city_val, district_val, county_val
-            );
+    #[instrument(name = "index_build", skip_all, fields(docs = docs.len()))]
+    pub fn add_documents(&self, docs: &[AddressRecord]) -> Result<()> {
+        let started = Instant::now();
+        let mut index_writer = self.writer()?;
+        self.write_documents(&index_writer, docs)?;
+        self.report(IndexProgress::Committing);
+        commit_with_version(index_writer.prepare_commit()?)?;
+        self.report(IndexProgress::Committed);
+        self.dictionary.write().unwrap().extend(docs);
+        info!(
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "文档写入完成"
+        );
+        Ok(())
+    }
 
-            index_writer.add_document(doc!(
-                self.province => province_val.as_str(),
-                self.city => city_val.as_str(),
-                self.district => district_val.as_str(),
-                self.county => county_val.as_str(),
-                self.full_address => full,
-                self.address_code => address_code_val.as_str()
-            ))?;
-        }
-        index_writer.commit()?;
+    /// 用新的文档整体替换索引内容，并重新加载
+    ///
+    /// 删除和写入在同一次提交中完成，正在进行的查询继续使用旧的快照，
+    /// 重新加载后的查询只会看到新的文档，不会看到中间状态。
+    #[instrument(name = "index_replace", skip_all, fields(docs = docs.len()))]
+    pub fn replace_documents(&self, docs: &[AddressRecord]) -> Result<()> {
+        let started = Instant::now();
+        let mut index_writer = self.writer()?;
+        index_writer.delete_all_documents()?;
+        self.write_documents(&index_writer, docs)?;
+        self.report(IndexProgress::Committing);
+        commit_with_version(index_writer.prepare_commit()?)?;
+        self.reader.reload()?;
+        self.report(IndexProgress::Committed);
+        self.dictionary.write().unwrap().replace(docs);
+        info!(
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            num_docs = self.reader.searcher().num_docs(),
+            "索引已替换"
+        );
         Ok(())
     }
 
+    /// 整体替换同义词表，之后的查询立即使用新表，见 `AddressIndexBuilder::synonyms`
+    ///
+    /// 分词缓存中的结果是按旧表扩展后分词的，替换时一并清空。
+    pub fn replace_synonyms(&self, synonyms: &HashMap<String, String>) {
+        *self.synonyms.write().unwrap() = Synonyms::new(synonyms);
+        self.token_cache.clear();
+    }
+
+    /// 整体替换提示词表，之后的查询立即使用新表，见 `AddressIndexBuilder::hints`
+    pub fn replace_hints(&self, hints: &HashMap<String, String>) {
+        *self.hints.write().unwrap() = Synonyms::new(hints);
+    }
+
+    /// 按设置的内存预算和线程数创建索引写入器
+    fn writer(&self) -> Result<IndexWriter> {
+        let writer = match self.writer_threads {
+            Some(threads) => self
+                .index
+                .writer_with_num_threads(threads, self.writer_heap_size)?,
+            None => self.index.writer(self.writer_heap_size)?,
+        };
+        Ok(writer)
+    }
+
+    /// 并行构建文档并交给写入器
+    ///
+    /// 文档构建（拼接、清理字段）在 rayon 线程池中进行，写入器自身按 CPU 数量
+    /// 使用多个索引线程，大数据集（乡镇街道级）的写入不会卡在单线程上。
+    fn write_documents(&self, index_writer: &IndexWriter, docs: &[AddressRecord]) -> Result<()> {
+        let total = docs.len();
+        let written = AtomicUsize::new(0);
+        docs.par_iter().try_for_each(|record| {
+            index_writer.add_document(self.to_document(record)?)?;
+            let written = written.fetch_add(1, Ordering::Relaxed) + 1;
+            if written.is_multiple_of(PROGRESS_INTERVAL) || written == total {
+                self.report(IndexProgress::DocsWritten { written, total });
+            }
+            Ok(())
+        })
+    }
+
+    /// 调用进度回调（如已设置）
+    fn report(&self, progress: IndexProgress) {
+        if let Some(callback) = &self.progress {
+            callback(progress);
+        }
+    }
+
+    /// 把一条地址记录转换为 tantivy 文档
+    fn to_document(&self, record: &AddressRecord) -> Result<TantivyDocument> {
+        // 构建完整地址字符串
+        // 简单的拼接其实也行，因为我们已经禁用了 fieldnorm
+        // 为了更好的搜索体验，我们保留层级结构
+        // 使用空格分隔，以便更好地支持分词
+        let full = format!(
+            "{} {} {} {}",
+            record.province, record.city, record.district, record.county
+        );
+
+        let mut document = doc!(
+            self.province => record.province.as_str(),
+            self.city => record.city.as_str(),
+            self.district => record.district.as_str(),
+            self.county => record.county.as_str(),
+            self.full_address => full,
+            self.province_short => record.short_names.province.as_str(),
+            self.city_short => record.short_names.city.as_str(),
+            self.district_short => record.short_names.district.as_str(),
+            self.county_short => record.short_names.county.as_str(),
+            self.province_pinyin => record.pinyin.province.as_str(),
+            self.city_pinyin => record.pinyin.city.as_str(),
+            self.district_pinyin => record.pinyin.district.as_str(),
+            self.county_pinyin => record.pinyin.county.as_str(),
+            self.address_code => record.address_code.as_str(),
+            self.name => record.name(),
+            self.deep => u64::from(record.level.deep()),
+            self.population => record.population.unwrap_or(0)
+        );
+        if let Some((lat, lng)) = record.location {
+            document.add_f64(self.lat, lat);
+            document.add_f64(self.lng, lng);
+        }
+        if let Some(id) = record.id {
+            document.add_u64(self.id, id);
+        }
+        if let Some(pid) = record.pid {
+            document.add_u64(self.pid, pid);
+        }
+        for (name, value) in &record.extra {
+            let field = self.extra_field(name).ok_or_else(|| {
+                Text2LocationError::InvalidInput(format!("未注册的额外字段: {}", name))
+            })?;
+            document.add_text(field, value);
+        }
+        Ok(document)
+    }
+
+    /// 按名称查找已注册的额外字段
+    fn extra_field(&self, name: &str) -> Option<Field> {
+        self.extra_fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, field)| *field)
+    }
+
     /// 提交更改并重新加载索引
-    pub fn commit(&mut self) -> anyhow::Result<()> {
+    #[instrument(name = "commit", skip_all)]
+    pub fn commit(&mut self) -> Result<()> {
         self.reader.reload()?;
+        info!(
+            num_docs = self.reader.searcher().num_docs(),
+            "索引已重新加载"
+        );
+        Ok(())
+    }
+
+    /// 把索引的所有段合并为一个并重新加载，适合在批量写入完成后调用
+    ///
+    /// 多次写入或多个写入线程会产生多个段，查询时需要逐段搜索；合并后查询延迟更低，
+    /// 已删除（被替换）的文档也会被真正清除，索引体积随之缩小。
+    #[instrument(name = "optimize", skip_all)]
+    pub fn optimize(&self) -> Result<()> {
+        let started = Instant::now();
+        let metas = self.index.searchable_segment_metas()?;
+        let segments = metas.len();
+        if segments > 1 || metas.iter().any(|meta| meta.has_deletes()) {
+            let ids: Vec<_> = metas.iter().map(|meta| meta.id()).collect();
+            let mut index_writer = self.writer()?;
+            index_writer.merge(&ids).wait()?;
+            index_writer.wait_merging_threads()?;
+            self.reader.reload()?;
+        }
+        info!(
+            segments,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "段合并完成"
+        );
+        Ok(())
+    }
+
+    /// 预热索引：遍历各字段的词典、加载排序用的快速字段，并执行几条有代表性的查询
+    ///
+    /// 部署后的第一次查询需要加载分词词典和索引数据，比平时慢一个数量级；
+    /// 在开始接受请求前调用可以把这部分开销提前。预热查询不写入查询日志。
+    #[instrument(name = "warmup", skip_all)]
+    pub fn warmup(&self) -> Result<()> {
+        let started = Instant::now();
+        let searcher = self.reader.searcher();
+        let schema = self.index.schema();
+        let mut terms = 0usize;
+        for segment_reader in searcher.segment_readers() {
+            for (field, entry) in schema.fields() {
+                if !entry.is_indexed() {
+                    continue;
+                }
+                let inverted_index = segment_reader.inverted_index(field)?;
+                let mut stream = inverted_index
+                    .terms()
+                    .stream()
+                    .map_err(TantivyError::from)?;
+                while stream.advance() {
+                    terms += 1;
+                }
+            }
+            rank_key_tweaker(segment_reader, Ranking::default())(0, 0.0);
+        }
+
+        let options = SearchOptions::default();
+        for query_str in WARMUP_QUERIES {
+            let processed_query = self.preprocess_query(query_str);
+            let query = self.build_query(query_str, &processed_query, &options)?;
+            for result in self.iter_query(query, &options, None).take(options.limit) {
+                result?;
+            }
+            self.dictionary
+                .read()
+                .unwrap()
+                .resolve(&self.clean_query(query_str));
+        }
+        info!(
+            terms,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "索引预热完成"
+        );
+        Ok(())
+    }
+
+    /// 把索引连同额外字段、区划变更表和区划名称词典导出为单个索引文件（.t2l）
+    ///
+    /// 导出前先合并段，文件中只包含当前可搜索的数据。导出的文件可由
+    /// `AddressIndexBuilder::import` 在其他节点加载，无需数据文件和重新建索引。
+    #[instrument(name = "export", skip_all)]
+    pub fn export(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        self.optimize()?;
+
+        let directory = self.index.directory();
+        let mut names = vec![PathBuf::from("meta.json")];
+        for meta in self.index.searchable_segment_metas()? {
+            names.extend(
+                meta.list_files()
+                    .into_iter()
+                    .filter(|f| directory.exists(f).unwrap_or(false)),
+            );
+        }
+        let mut files = Vec::with_capacity(names.len());
+        for name in names {
+            let data = directory.atomic_read(&name).map_err(|e| {
+                Text2LocationError::Archive(format!("读取索引文件 {} 失败: {}", name.display(), e))
+            })?;
+            files.push((name.to_string_lossy().into_owned(), data));
+        }
+
+        let schema = self.index.schema();
+        let header = ArchiveHeader {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            extra_fields: self
+                .extra_fields
+                .iter()
+                .map(|(name, field)| (name.clone(), schema.get_field_entry(*field).is_indexed()))
+                .collect(),
+            redirects: self.redirects.clone(),
+            dictionary: self.dictionary.read().unwrap().entries().to_vec(),
+        };
+        let file = File::create(path).map_err(|e| {
+            Text2LocationError::Archive(format!("无法创建 {}: {}", path.display(), e))
+        })?;
+        Archive { header, files }.write(BufWriter::new(file))?;
+        info!(path = %path.display(), "索引已导出");
         Ok(())
     }
 
+    /// 读取所有存储的文档，还原为地址记录（不含人口、坐标和其他名称）
+    fn stored_records(&self) -> Result<Vec<AddressRecord>> {
+        let searcher = self.reader.searcher();
+        let mut records = Vec::new();
+        for segment_reader in searcher.segment_readers() {
+            let store = segment_reader
+                .get_store_reader(1)
+                .map_err(TantivyError::from)?;
+            for doc in store.iter::<TantivyDocument>(segment_reader.alive_bitset()) {
+                let doc = doc?;
+                let text = |field: Field| {
+                    doc.get_first(field)
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string()
+                };
+                let u64_value = |field: Field| doc.get_first(field).and_then(|v| v.as_u64());
+                let Some(level) = u64_value(self.deep).and_then(|d| AdminLevel::from_deep(d as u8))
+                else {
+                    continue;
+                };
+                records.push(AddressRecord {
+                    province: text(self.province),
+                    city: text(self.city),
+                    district: text(self.district),
+                    county: text(self.county),
+                    address_code: text(self.address_code),
+                    level,
+                    id: u64_value(self.id),
+                    pid: u64_value(self.pid),
+                    population: None,
+                    location: None,
+                    extra: BTreeMap::new(),
+                    short_names: LevelNames {
+                        province: text(self.province_short),
+                        city: text(self.city_short),
+                        district: text(self.district_short),
+                        county: text(self.county_short),
+                    },
+                    pinyin: LevelNames {
+                        province: text(self.province_pinyin),
+                        city: text(self.city_pinyin),
+                        district: text(self.district_pinyin),
+                        county: text(self.county_pinyin),
+                    },
+                    aliases: Vec::new(),
+                });
+            }
+        }
+        Ok(records)
+    }
+
+    /// 从索引文件加载地址索引，等同于 `AddressIndex::builder().import(path)`
+    pub fn import(path: impl AsRef<Path>) -> Result<Self> {
+        Self::builder().import(path)
+    }
+
     /// 创建配置了字段权重的 QueryParser
     /// 使用配置的权重值
-    fn create_query_parser(&self) -> QueryParser {
-        // 主要针对 full_address 进行搜索
-        // 其他字段作为辅助
-        let query_parser = QueryParser::for_index(&self.index, vec![self.full_address]);
+    fn create_query_parser(&self, field: Option<AdminLevel>) -> QueryParser {
+        // 主要针对 full_address 进行搜索，指定层级时只搜索该层级的名称字段
+        let field = match field {
+            Some(level) => self.level_field(level),
+            None => self.full_address,
+        };
+        let query_parser = QueryParser::for_index(&self.index, vec![field]);
 
         // query_parser.set_conjunction_by_default();
 
@@ -148,129 +1165,1675 @@ impl AddressIndex {
         query_parser
     }
 
-    /// 预处理查询字符串：分词、去重、用空格连接
-    fn preprocess_query(&self, query_str: &str) -> String {
-        let mut tokenizer = self.index.tokenizers().get("jieba").unwrap();
-        let mut token_stream = tokenizer.token_stream(query_str);
+    /// 某一层级的名称字段
+    fn level_field(&self, level: AdminLevel) -> Field {
+        match level {
+            AdminLevel::Province => self.province,
+            AdminLevel::City => self.city,
+            AdminLevel::District => self.district,
+            AdminLevel::County => self.county,
+        }
+    }
+
+    /// 按搜索选项和索引的排序权重确定排序方式
+    fn ranking(&self, options: &SearchOptions) -> Ranking {
+        Ranking {
+            bias: options.bias,
+            prefer_level: options.prefer_level,
+            weights: self.weights,
+        }
+    }
+
+    /// 去掉订单号、电话号码等噪声并扩展同义词，供分词和精确匹配使用
+    fn clean_query(&self, query_str: &str) -> String {
+        self.synonyms
+            .read()
+            .unwrap()
+            .expand(&strip_noise(query_str))
+    }
+
+    /// 去掉订单号、电话号码等噪声、扩展同义词后使用 jieba 对查询字符串分词，去掉空白词、停用词和查询运算符（AND、OR 等）并去重
+    ///
+    /// 最近分过词的查询从缓存中返回，见 `AddressIndexBuilder::token_cache_size`。
+    pub fn tokenize(&self, query_str: &str) -> Vec<String> {
+        if let Some(tokens) = self.token_cache.get(query_str) {
+            return tokens.to_vec();
+        }
+        let tokens = self.segment(query_str);
+        self.token_cache.insert(query_str, &tokens);
+        tokens
+    }
+
+    /// 各部分占用的内存，见 `MemoryReport`
+    pub fn memory_report(&self) -> Result<MemoryReport> {
+        let index = self
+            .reader
+            .searcher()
+            .space_usage()
+            .map_err(TantivyError::from)?
+            .total()
+            .get_bytes() as usize;
+        Ok(MemoryReport {
+            index,
+            dictionary: self.dictionary.read().unwrap().heap_size(),
+            token_cache: self.token_cache.heap_size(),
+            tables: self.synonyms.read().unwrap().heap_size()
+                + self.hints.read().unwrap().heap_size()
+                + self.redirects.heap_size()
+                + self.former_codes.heap_size()
+                + self.stopwords.heap_size(),
+        })
+    }
+
+    /// 分词缓存自索引创建以来的累计命中情况，缓存关闭时始终为 0
+    pub fn cache_stats(&self) -> CacheStats {
+        self.token_cache.stats()
+    }
+
+    /// 不经过缓存的分词
+    fn segment(&self, query_str: &str) -> Vec<String> {
+        let query_str = self.clean_query(query_str);
+        let mut tokenizer = self.tokenizer.clone();
+        let mut token_stream = tokenizer.token_stream(&query_str);
         let mut tokens = Vec::new();
         while token_stream.advance() {
-            tokens.push(token_stream.token().text.to_string());
+            let text = token_stream.token().text.trim();
+            if !text.is_empty() && !self.stopwords.contains(text) && !QUERY_KEYWORDS.contains(&text)
+            {
+                tokens.push(text.to_string());
+            }
         }
 
         // 去重
         tokens.sort();
         tokens.dedup();
+        tokens
+    }
 
-        tokens.join(" ")
+    /// 预处理查询字符串：分词、去重、用空格连接
+    fn preprocess_query(&self, query_str: &str) -> String {
+        self.tokenize(query_str).join(" ")
     }
 
-    /// 搜索地址，返回结果字符串数组
-    pub fn search_address(&self, query_str: &str) -> anyhow::Result<Vec<String>> {
-        let searcher = self.reader.searcher();
+    /// 各级全称中与查询分词匹配的片段，重叠或相邻的片段合并为一个
+    fn highlights(&self, result: &AddressResult, processed_query: &str) -> Vec<Highlight> {
+        // 开启繁体输出时结果中的名称已转换，查询词也按同样方式转换后再比较
+        let tokens: Vec<String> = processed_query
+            .split_whitespace()
+            .map(|token| self.display_name(token))
+            .collect();
+        let mut highlights = Vec::new();
+        for level in AdminLevel::ALL {
+            let name = result.level_name(level);
+            let mut ranges: Vec<(usize, usize)> = tokens
+                .iter()
+                .flat_map(|token| {
+                    name.match_indices(token.as_str())
+                        .map(|(start, m)| (start, start + m.len()))
+                })
+                .collect();
+            ranges.sort_unstable();
+            let mut merged: Vec<(usize, usize)> = Vec::new();
+            for (start, end) in ranges {
+                match merged.last_mut() {
+                    Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                    _ => merged.push((start, end)),
+                }
+            }
+            highlights.extend(merged.into_iter().map(|(start, end)| Highlight {
+                level,
+                start,
+                end,
+            }));
+        }
+        highlights
+    }
 
-        let processed_query = self.preprocess_query(query_str);
+    /// 将检索到的文档转换为查询结果
+    fn to_result(&self, doc: &TantivyDocument, score: Score) -> AddressResult {
+        // 层级字段只取第一个词，去除重复
+        let level_value = |field: Field| {
+            self.display_name(
+                doc.get_first(field)
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.split_whitespace().next().unwrap_or(s))
+                    .unwrap_or(""),
+            )
+        };
+        let address_code_val = doc
+            .get_first(self.address_code)
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let text_value = |field: Field| {
+            doc.get_first(field)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string()
+        };
+        let u64_value = |field: Field| doc.get_first(field).and_then(|v| v.as_u64());
+        let deep = u64_value(self.deep).unwrap_or_default() as u8;
+        let extra = self
+            .extra_fields
+            .iter()
+            .filter_map(|(name, field)| {
+                let value = doc.get_first(*field).and_then(|v| v.as_str())?;
+                Some((name.clone(), value.to_string()))
+            })
+            .collect();
 
-        // 使用配置了权重的查询解析器
-        let query_parser = self.create_query_parser();
-        // 不要强制 AND (set_conjunction_by_default)，因为分词模式可能导致查询词包含索引中不存在的词（如“京市”）
-        // 使用默认的 OR 逻辑，配合打分机制筛选结果
+        let province = level_value(self.province);
+        let city = level_value(self.city);
+        let district = level_value(self.district);
+        let county = level_value(self.county);
+        let full_address = join_address(
+            [
+                province.as_str(),
+                city.as_str(),
+                district.as_str(),
+                county.as_str(),
+            ],
+            &self.full_address_separator,
+        );
 
-        let query = query_parser.parse_query(&processed_query)?;
+        AddressResult {
+            address_code: address_code_val.to_string(),
+            province,
+            city,
+            district,
+            county,
+            full_address,
+            short_names: LevelNames {
+                province: self.display_name(&text_value(self.province_short)),
+                city: self.display_name(&text_value(self.city_short)),
+                district: self.display_name(&text_value(self.district_short)),
+                county: self.display_name(&text_value(self.county_short)),
+            },
+            pinyin: self.include_pinyin.then(|| LevelNames {
+                province: text_value(self.province_pinyin),
+                city: text_value(self.city_pinyin),
+                district: text_value(self.district_pinyin),
+                county: text_value(self.county_pinyin),
+            }),
+            id: u64_value(self.id),
+            pid: u64_value(self.pid),
+            deep,
+            granularity: AdminLevel::from_deep(deep).unwrap_or(AdminLevel::Province),
+            score,
+            extra,
+            redirected_from: None,
+            former_codes: self
+                .former_codes
+                .get(address_code_val)
+                .cloned()
+                .unwrap_or_default(),
+            probability: None,
+            highlights: Vec::new(),
+        }
+    }
 
-        // 获取前 10 个匹配结果
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+    /// 结果中展示的地名：开启繁体输出时用内置的简繁转换表转换
+    pub(crate) fn display_name(&self, name: &str) -> String {
+        #[cfg(feature = "traditional")]
+        if self.traditional {
+            return zhconv::zhconv(name, zhconv::Variant::ZhHant);
+        }
+        name.to_string()
+    }
 
-        let mut results = Vec::new();
-        for (_, doc_address) in top_docs {
-            let retrieved_doc: TantivyDocument = searcher.doc(doc_address)?;
-            let province_val = retrieved_doc
-                .get_first(self.province)
-                .and_then(|v| v.as_str())
-                .map(|s| s.split_whitespace().next().unwrap_or(s)) // 只取第一个词，去除重复
-                .unwrap_or("");
-            let city_val = retrieved_doc
-                .get_first(self.city)
-                .and_then(|v| v.as_str())
-                .map(|s| s.split_whitespace().next().unwrap_or(s))
-                .unwrap_or("");
-            let district_val = retrieved_doc
-                .get_first(self.district)
-                .and_then(|v| v.as_str())
-                .map(|s| s.split_whitespace().next().unwrap_or(s))
-                .unwrap_or("");
-            let county_val = retrieved_doc
-                .get_first(self.county)
-                .and_then(|v| v.as_str())
-                .map(|s| s.split_whitespace().next().unwrap_or(s))
-                .unwrap_or("");
-            let address_code_val = retrieved_doc
-                .get_first(self.address_code)
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-
-            let result = AddressResult {
-                address_code: address_code_val.to_string(),
-                province: province_val.to_string(),
-                city: city_val.to_string(),
-                district: district_val.to_string(),
-                county: county_val.to_string(),
-            };
+    /// 命中已撤销的区划时替换为现行的继任区划，并记录原编码
+    ///
+    /// 变更表中的编码可以连续跳转（A -> B -> C），继任区划不在索引中时保留原结果。
+    fn redirect(&self, searcher: &Searcher, result: AddressResult) -> Result<AddressResult> {
+        let Some(code) = successors(&self.redirects, &result.address_code).pop() else {
+            return Ok(result);
+        };
+        let Some(doc) = self.doc_by_code(searcher, code)? else {
+            return Ok(result);
+        };
+        let mut successor = self.to_result(&doc, result.score);
+        successor.redirected_from = Some(result.address_code);
+        Ok(successor)
+    }
 
-            results.push(result.to_string());
-        }
+    /// 按地址编码读取文档
+    fn doc_by_code(&self, searcher: &Searcher, code: &str) -> Result<Option<TantivyDocument>> {
+        let query = TermQuery::new(
+            Term::from_field_text(self.address_code, code),
+            IndexRecordOption::Basic,
+        );
+        let Some((_, doc_address)) = searcher
+            .search(&query, &TopDocs::with_limit(1))?
+            .into_iter()
+            .next()
+        else {
+            return Ok(None);
+        };
+        Ok(Some(searcher.doc(doc_address)?))
+    }
 
-        Ok(results)
+    /// 搜索地址，返回按得分排序的惰性结果迭代器
+    ///
+    /// 文档只有在被迭代到时才会从索引中读取并转换，
+    /// 命中地址按页向索引拉取，每页大小逐步翻倍。
+    pub fn search_iter(&self, query_str: &str) -> Result<SearchIter<'_>> {
+        let options = SearchOptions::default();
+        let query = self.build_query(query_str, &self.preprocess_query(query_str), &options)?;
+        Ok(self.iter_query(query, &options, None))
+    }
+
+    /// 根据原始查询、预处理后的查询和搜索选项构建 tantivy 查询
+    fn build_query(
+        &self,
+        query_str: &str,
+        processed_query: &str,
+        options: &SearchOptions,
+    ) -> Result<Box<dyn Query>> {
+        if options.require_location_signal && !self.has_location_signal(query_str) {
+            return Err(Text2LocationError::NoLocationSignal(query_str.to_string()));
+        }
+        // 使用配置了权重的查询解析器
+        let query_parser = self.create_query_parser(options.field);
+        // 不要强制 AND (set_conjunction_by_default)，因为分词模式可能导致查询词包含索引中不存在的词（如“京市”）
+        // 使用默认的 OR 逻辑，配合打分机制筛选结果
+        let mut query = query_parser.parse_query(processed_query)?;
+
+        // 原文中出现提示词时，给层级链中含有提示区划的结果加一个小的固定得分，
+        // 只在同名区划得分接近时改变排序，不会召回原查询没有命中的区划。
+        // 区划名称整体作为一个词匹配，不分词，避免“福州市”的“州市”命中“徐州市”
+        let hints = self.hints.read().unwrap();
+        let hints = hints.matches(query_str);
+        if !hints.is_empty() {
+            let mut clauses = vec![(Occur::Must, query)];
+            for region in hints {
+                let levels = AdminLevel::ALL
+                    .iter()
+                    .map(|&level| {
+                        let term = Term::from_field_text(self.level_field(level), region);
+                        let query = TermQuery::new(term, IndexRecordOption::Basic);
+                        (Occur::Should, Box::new(query) as Box<dyn Query>)
+                    })
+                    .collect();
+                let hint =
+                    ConstScoreQuery::new(Box::new(BooleanQuery::new(levels)), self.weights.hint);
+                clauses.push((Occur::Should, Box::new(hint) as Box<dyn Query>));
+            }
+            query = Box::new(BooleanQuery::new(clauses));
+        }
+        self.filter_query(query, options)
+    }
+
+    /// 按搜索选项中的层级和额外字段过滤条件包装查询
+    fn filter_query(
+        &self,
+        query: Box<dyn Query>,
+        options: &SearchOptions,
+    ) -> Result<Box<dyn Query>> {
+        let term_query = |term: Term| -> Box<dyn Query> {
+            Box::new(TermQuery::new(term, IndexRecordOption::Basic))
+        };
+        let mut filters = Vec::new();
+        // 层级过滤：要求文档的 deep 与指定层级一致
+        if let Some(level) = options.level {
+            filters.push(term_query(Term::from_field_u64(
+                self.deep,
+                u64::from(level.deep()),
+            )));
+        }
+        // 最低层级：要求文档的 deep 不小于指定层级
+        if let Some(level) = options.require_at_least {
+            filters.push(Box::new(RangeQuery::new(
+                Bound::Included(Term::from_field_u64(self.deep, u64::from(level.deep()))),
+                Bound::Unbounded,
+            )));
+        }
+        // 额外字段过滤：要求字段值完全一致
+        for (name, value) in &options.extra_filters {
+            let field = self
+                .extra_field(name)
+                .ok_or_else(|| QueryParserError::FieldDoesNotExist(name.clone()))?;
+            filters.push(term_query(Term::from_field_text(field, value)));
+        }
+        if filters.is_empty() {
+            return Ok(query);
+        }
+
+        // 过滤条件不参与打分，因此包一层得分为 0 的 ConstScoreQuery
+        let mut clauses = vec![(Occur::Must, query)];
+        for filter in filters {
+            let filter = ConstScoreQuery::new(filter, 0.0);
+            clauses.push((Occur::Must, Box::new(filter) as Box<dyn Query>));
+        }
+        Ok(Box::new(BooleanQuery::new(clauses)))
+    }
+
+    /// 创建按页拉取结果的迭代器
+    fn iter_query(
+        &self,
+        query: Box<dyn Query>,
+        options: &SearchOptions,
+        deadline: Option<Deadline>,
+    ) -> SearchIter<'_> {
+        SearchIter {
+            index: self,
+            searcher: self.reader.searcher(),
+            query,
+            ranking: self.ranking(options),
+            deadline,
+            offset: 0,
+            page_size: INITIAL_PAGE_SIZE,
+            page: Vec::new().into_iter(),
+            exhausted: false,
+        }
+    }
+
+    /// 按搜索选项搜索地址
+    #[instrument(name = "search", skip_all, fields(query = query_str))]
+    pub fn search(&self, query_str: &str, options: &SearchOptions) -> Result<Vec<AddressResult>> {
+        let started = Instant::now();
+        let deadline = Deadline::start(options);
+        let processed_query = self.preprocess_query(query_str);
+        let query = self.build_query(query_str, &processed_query, options)?;
+        self.collect_results(
+            query_str,
+            &processed_query,
+            query,
+            options,
+            deadline,
+            started,
+        )
+    }
+
+    /// 用正则表达式匹配本级区划全称，如 `search_regex("^兴宁", &options)` 找出所有以“兴宁”开头的区划
+    ///
+    /// 正则表达式在未分词的本级全称上匹配，只需匹配其中一部分，用 `^`、`$` 锚定开头和结尾；
+    /// 语法见 tantivy 的 `RegexQuery`（不支持反向引用和环视）。所有命中得分相同，
+    /// 按人口和编码排序，limit、层级和额外字段过滤等搜索选项同样生效。
+    #[instrument(name = "search_regex", skip_all, fields(pattern))]
+    pub fn search_regex(
+        &self,
+        pattern: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<AddressResult>> {
+        let started = Instant::now();
+        let deadline = Deadline::start(options);
+        let regex = RegexQuery::from_pattern(&anchor_pattern(pattern), self.name).map_err(|e| {
+            Text2LocationError::InvalidInput(format!("无效的正则表达式 {}: {}", pattern, e))
+        })?;
+        let query = self.filter_query(Box::new(regex), options)?;
+        self.collect_results(pattern, pattern, query, options, deadline, started)
+    }
+
+    /// 执行组合式查询条件，如 `Q::level(AdminLevel::District).and(Q::name_contains("兴宁"))`
+    ///
+    /// 条件见 `Q`；limit、层级和额外字段过滤等搜索选项同样生效。
+    #[instrument(name = "search_q", skip_all, fields(query = %q))]
+    pub fn search_q(&self, q: &Q, options: &SearchOptions) -> Result<Vec<AddressResult>> {
+        let started = Instant::now();
+        let deadline = Deadline::start(options);
+        let mut texts = Vec::new();
+        let query = self.compile_q(q, &mut texts)?;
+        let query = self.filter_query(query, options)?;
+        self.collect_results(
+            &q.to_string(),
+            &texts.join(" "),
+            query,
+            options,
+            deadline,
+            started,
+        )
+    }
+
+    /// 把组合式查询条件编译为 tantivy 查询，全文搜索条件分词后的文本收集到 `texts` 中用于高亮
+    fn compile_q(&self, q: &Q, texts: &mut Vec<String>) -> Result<Box<dyn Query>> {
+        let regex = |pattern: &str, field: Field| -> Result<Box<dyn Query>> {
+            let query = RegexQuery::from_pattern(pattern, field).map_err(|e| {
+                Text2LocationError::InvalidInput(format!("无效的正则表达式 {}: {}", pattern, e))
+            })?;
+            Ok(Box::new(ConstScoreQuery::new(Box::new(query), 1.0)))
+        };
+        let term = |term: Term| -> Result<Box<dyn Query>> {
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            Ok(Box::new(ConstScoreQuery::new(Box::new(query), 1.0)))
+        };
+        match q {
+            Q::Level(level) => term(Term::from_field_u64(self.deep, u64::from(level.deep()))),
+            Q::Name(name) => term(Term::from_field_text(self.name, name)),
+            Q::NameContains(text) => regex(&format!(".*{}.*", escape_regex(text)), self.name),
+            Q::NameRegex(pattern) => regex(&anchor_pattern(pattern), self.name),
+            Q::Code(code) => term(Term::from_field_text(self.address_code, code)),
+            Q::CodePrefix(prefix) => {
+                regex(&format!("{}.*", escape_regex(prefix)), self.address_code)
+            }
+            Q::Text(text) => {
+                let processed = self.preprocess_query(text);
+                let query = self.create_query_parser(None).parse_query(&processed)?;
+                texts.push(processed);
+                Ok(query)
+            }
+            Q::Extra(name, value) => {
+                let field = self
+                    .extra_field(name)
+                    .ok_or_else(|| QueryParserError::FieldDoesNotExist(name.clone()))?;
+                term(Term::from_field_text(field, value))
+            }
+            Q::And(clauses) | Q::Or(clauses) => {
+                let occur = if matches!(q, Q::And(_)) {
+                    Occur::Must
+                } else {
+                    Occur::Should
+                };
+                let clauses = clauses
+                    .iter()
+                    .map(|clause| Ok((occur, self.compile_q(clause, texts)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Box::new(BooleanQuery::new(clauses)))
+            }
+            // 只有 MustNot 子句的 BooleanQuery 不匹配任何文档，需要先匹配全部文档再排除
+            Q::Not(inner) => Ok(Box::new(BooleanQuery::new(vec![
+                (
+                    Occur::Must,
+                    Box::new(ConstScoreQuery::new(Box::new(AllQuery), 0.0)) as Box<dyn Query>,
+                ),
+                (Occur::MustNot, self.compile_q(inner, texts)?),
+            ]))),
+        }
+    }
+
+    /// 按页拉取命中并按搜索选项去重、合并解释，写入日志后返回结果
+    fn collect_results(
+        &self,
+        query_str: &str,
+        processed_query: &str,
+        query: Box<dyn Query>,
+        options: &SearchOptions,
+        deadline: Option<Deadline>,
+        started: Instant,
+    ) -> Result<Vec<AddressResult>> {
+        let mut seen = HashSet::new();
+        let mut codes = HashSet::new();
+        let mut interpretations: Vec<[String; 4]> = Vec::new();
+        let mut results = Vec::new();
+        for result in self.iter_query(query, options, deadline) {
+            if results.len() >= options.limit {
+                break;
+            }
+            let mut result = result?;
+            // 同一条省市区县链可能对应多个编码，去重时只保留第一条（得分最高）
+            if options.dedup && !seen.insert(result.chain()) {
+                continue;
+            }
+            if options.interpretations {
+                let matched = matched_chain(&result, processed_query);
+                if interpretations.iter().any(|i| compatible(i, &matched)) {
+                    continue;
+                }
+                interpretations.push(matched);
+            }
+            // 撤销区划被替换为继任区划后，可能与继任区划本身的命中重复
+            if !codes.insert(result.address_code.clone()) {
+                continue;
+            }
+            if options.highlight {
+                result.highlights = self.highlights(&result, processed_query);
+            }
+            results.push(result);
+        }
+        if options.interpretations {
+            assign_probabilities(&mut results);
+        }
+        self.finish_search(
+            query_str,
+            processed_query,
+            results.first(),
+            results.len(),
+            started,
+        );
+        Ok(results)
+    }
+
+    /// 只在指定层级的名称中搜索该层级的区划，如 `search_field(AdminLevel::District, "兴宁")`
+    ///
+    /// 适合调用方已经知道要找的是区县名还是城市名的场景，不会被其他层级的同名区划干扰。
+    pub fn search_field(&self, level: AdminLevel, query_str: &str) -> Result<Vec<AddressResult>> {
+        self.search(query_str, &SearchOptions::new().field(level).level(level))
+    }
+
+    /// 执行结构化查询
+    pub fn search_query(&self, query: &AddressQuery) -> Result<Vec<AddressResult>> {
+        self.search(&query.query, &query.options())
+    }
+
+    /// 搜索地址，返回按索引的标签语言格式化的结果字符串数组
+    pub fn search_address(&self, query_str: &str) -> Result<Vec<String>> {
+        // 获取前 10 个匹配结果
+        let results = self.search(query_str, &SearchOptions::default())?;
+        Ok(results
+            .iter()
+            .map(|r| r.localized(self.locale).to_string())
+            .collect())
+    }
+
+    /// 文本输出的标签语言，见 `AddressIndexBuilder::locale`
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    /// 当前使用的排序权重，见 `AddressIndexBuilder::weights`
+    pub fn weights(&self) -> RankingWeights {
+        self.weights
+    }
+
+    /// 替换排序权重，之后的查询立即按新权重排序，不需要重建索引
+    pub fn set_weights(&mut self, weights: RankingWeights) {
+        self.weights = weights;
+    }
+
+    /// 搜索地址的第一个结果，未找到时返回 `NotFound` 错误
+    pub fn find(&self, query_str: &str) -> Result<AddressResult> {
+        self.search_first(query_str)?
+            .ok_or_else(|| Text2LocationError::NotFound(query_str.to_string()))
     }
 
     /// 搜索地址的第一个结果，可能为 None
-    pub fn search_first(&self, query_str: &str) -> anyhow::Result<Option<AddressResult>> {
-        let searcher = self.reader.searcher();
+    #[instrument(name = "search", skip_all, fields(query = query_str))]
+    pub fn search_first(&self, query_str: &str) -> Result<Option<AddressResult>> {
+        let started = Instant::now();
+        let options = SearchOptions::default();
+        let processed_query = self.preprocess_query(query_str);
+        let query = self.build_query(query_str, &processed_query, &options)?;
+        let first = self.iter_query(query, &options, None).next().transpose()?;
+        self.finish_search(
+            query_str,
+            &processed_query,
+            first.as_ref(),
+            usize::from(first.is_some()),
+            started,
+        );
+        Ok(first)
+    }
+
+    /// 找出文本中所有出现的区划全称和简称及其位置，按最长匹配、互不重叠
+    ///
+    /// 名称来自已写入的地址记录（层级链中的全称、各级简称和 `AddressRecord::aliases`），
+    /// 不经过分词，适合从长文本中快速抽取地名。
+    pub fn scan(&self, text: &str) -> Vec<NameMatch> {
+        self.dictionary.read().unwrap().scan(text)
+    }
+
+    /// 粗略判断文本是否可能是地址，批量处理时可先用它跳过明显不是地址的输入，不必为其执行搜索
+    ///
+    /// 只做名称词典扫描、不查询索引，开销远小于 `resolve`。判断规则：
+    /// - 去掉噪声后不含汉字（纯数字、英文句子、符号）：不是地址
+    /// - 含有已知的区划全称或简称（含同义词），或含有省、市、路、号等地址用字：可能是地址
+    /// - 其余（人名、与地址无关的中文句子）：不是地址
+    pub fn is_probably_address(&self, text: &str) -> bool {
+        let cleaned = self.clean_query(text);
+        if !cleaned
+            .chars()
+            .any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c))
+        {
+            return false;
+        }
+        cleaned.chars().any(|c| ADDRESS_CHARS.contains(c)) || !self.scan(&cleaned).is_empty()
+    }
+
+    /// 文本中（去掉噪声、扩展同义词后）是否出现了表明地点的区划名称：任一层级的全称，
+    /// 或省、市、区县级的简称；乡镇街道的简称（如“人民”“中山路”）多是常用词，单独出现时不算
+    ///
+    /// 没有任何地名的查询只能靠分词后的部分词偶然命中，结果没有意义，
+    /// 见 `SearchOptions::require_location_signal`。
+    pub fn has_location_signal(&self, text: &str) -> bool {
+        self.dictionary
+            .read()
+            .unwrap()
+            .has_location_signal(&self.clean_query(text))
+    }
+
+    /// 被多个区划共用的本级全称（如“朝阳区”“城关镇”）及各自的上级区划，按共用的区划数降序排列
+    ///
+    /// 指定层级时只统计该层级的区划。只写这些名称的输入需要上级区划才能确定，
+    /// 可用于提示调用方补充上下文。
+    pub fn ambiguous_names(&self, level: Option<AdminLevel>) -> Vec<AmbiguousName> {
+        self.dictionary.read().unwrap().ambiguous_names(level)
+    }
 
+    /// 精确匹配优先、模糊搜索兜底的解析流程，返回最佳结果
+    ///
+    /// 先用 `resolve_exact` 做精确匹配，未能唯一确定区划时回退到 `search_first` 的分词打分搜索。
+    /// 对规范的输入（如“广东省梅州市兴宁市宁中镇”）更准确也更快。
+    ///
+    /// 使用默认搜索选项，不检查 `SearchOptions::require_location_signal`。
+    pub fn resolve(&self, query_str: &str) -> Result<Option<AddressResult>> {
+        match self.resolve_exact(query_str)? {
+            Some(result) => Ok(Some(result)),
+            None => self.search_first(query_str),
+        }
+    }
+
+    /// 只做精确匹配：用区划名称词典做最长匹配，文本中的全称和简称能唯一确定一个区划时
+    /// 返回该区划（得分为 `EXACT_MATCH_SCORE`），否则返回 None
+    #[instrument(name = "resolve_exact", skip_all, fields(query = query_str))]
+    pub fn resolve_exact(&self, query_str: &str) -> Result<Option<AddressResult>> {
+        let started = Instant::now();
+        let cleaned = self.clean_query(query_str);
+        let code = self
+            .dictionary
+            .read()
+            .unwrap()
+            .resolve(&cleaned)
+            .map(str::to_string);
+        let Some(code) = code else {
+            return Ok(None);
+        };
+        let Some(result) = self.get(&code)? else {
+            return Ok(None);
+        };
+        self.finish_search(query_str, &cleaned, Some(&result), 1, started);
+        Ok(Some(result))
+    }
+
+    /// 文本中互相矛盾的区划全称所在的层级（如“广东省朝阳区”中的省级和区县级），没有矛盾时为空
+    pub fn conflicting_levels(&self, query_str: &str) -> Vec<AdminLevel> {
+        let cleaned = self.clean_query(query_str);
+        self.dictionary.read().unwrap().conflicts(&cleaned)
+    }
+
+    /// 只返回地址编码及得分的搜索，编码直接从快速字段读取，不读取和解析存储的文档
+    ///
+    /// 排序、过滤和位置偏好与 `search` 相同，命中已撤销的区划时同样替换为继任编码。
+    /// 由于不读取各级名称，`dedup` 只按编码去重，`interpretations` 不生效。
+    #[instrument(name = "search_codes", skip_all, fields(query = query_str))]
+    pub fn search_codes(
+        &self,
+        query_str: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<(String, Score)>> {
+        let started = Instant::now();
+        let deadline = Deadline::start(options);
         let processed_query = self.preprocess_query(query_str);
+        let query = self.build_query(query_str, &processed_query, options)?;
+        let searcher = self.reader.searcher();
+        let mut results: Vec<(String, Score)> = Vec::new();
+        let mut offset = 0;
+        let mut page_size = options.limit.max(1);
+        'pages: while results.len() < options.limit {
+            let page = rank_page(
+                &searcher,
+                query.as_ref(),
+                self.ranking(options),
+                deadline,
+                page_size,
+                offset,
+            )?;
+            let exhausted = page.len() < page_size;
+            offset += page.len();
+            page_size *= 2;
+            for (rank_key, _) in page {
+                let code = self.current_code(&searcher, rank_key.address_code)?;
+                // 撤销区划被替换为继任编码后，可能与继任区划本身的命中重复
+                if results.iter().any(|(c, _)| *c == code) {
+                    continue;
+                }
+                results.push((code, rank_key.score));
+                if results.len() >= options.limit {
+                    break 'pages;
+                }
+            }
+            if exhausted {
+                break;
+            }
+        }
+        self.finish_code_search(
+            query_str,
+            &processed_query,
+            results.first(),
+            results.len(),
+            started,
+        );
+        Ok(results)
+    }
 
-        // 使用配置了权重的查询解析器
-        let query_parser = self.create_query_parser();
-        let query = query_parser.parse_query(&processed_query)?;
+    /// 与 `resolve` 相同的解析流程，只返回地址编码，不读取存储的文档
+    #[instrument(name = "resolve_code", skip_all, fields(query = query_str))]
+    pub fn resolve_code(&self, query_str: &str) -> Result<Option<String>> {
+        let started = Instant::now();
+        let cleaned = self.clean_query(query_str);
+        let code = self
+            .dictionary
+            .read()
+            .unwrap()
+            .resolve(&cleaned)
+            .map(str::to_string);
+        if let Some(code) = code {
+            let searcher = self.reader.searcher();
+            let code = self.current_code(&searcher, code)?;
+            let top = (code, EXACT_MATCH_SCORE);
+            self.finish_code_search(query_str, &cleaned, Some(&top), 1, started);
+            return Ok(Some(top.0));
+        }
+        let results = self.search_codes(query_str, &SearchOptions::new().limit(1))?;
+        Ok(results.into_iter().next().map(|(code, _)| code))
+    }
+
+    /// 按区划变更表得到现行编码，继任区划不在索引中时保留原编码
+    fn current_code(&self, searcher: &Searcher, code: String) -> Result<String> {
+        let Some(successor) = successors(&self.redirects, &code).pop() else {
+            return Ok(code);
+        };
+        let query = TermQuery::new(
+            Term::from_field_text(self.address_code, successor),
+            IndexRecordOption::Basic,
+        );
+        if searcher.search(&query, &Count)? > 0 {
+            Ok(successor.to_string())
+        } else {
+            Ok(code)
+        }
+    }
 
-        // 获取第一个匹配结果
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+    /// 按地址编码读取区划（得分为 `EXACT_MATCH_SCORE`），编码已撤销时返回继任区划
+    pub fn get(&self, address_code: &str) -> Result<Option<AddressResult>> {
+        let searcher = self.reader.searcher();
+        let Some(doc) = self.doc_by_code(&searcher, address_code)? else {
+            return Ok(None);
+        };
+        let result = self.to_result(&doc, EXACT_MATCH_SCORE);
+        self.redirect(&searcher, result).map(Some)
+    }
 
-        if let Some((_, doc_address)) = top_docs.first() {
-            let retrieved_doc: TantivyDocument = searcher.doc(*doc_address)?;
-            let province_val = retrieved_doc
-                .get_first(self.province)
-                .and_then(|v| v.as_str())
-                .map(|s| s.split_whitespace().next().unwrap_or(s)) // 只取第一个词，去除重复
-                .unwrap_or("");
-            let city_val = retrieved_doc
-                .get_first(self.city)
-                .and_then(|v| v.as_str())
-                .map(|s| s.split_whitespace().next().unwrap_or(s))
-                .unwrap_or("");
-            let district_val = retrieved_doc
-                .get_first(self.district)
-                .and_then(|v| v.as_str())
-                .map(|s| s.split_whitespace().next().unwrap_or(s))
-                .unwrap_or("");
-            let county_val = retrieved_doc
-                .get_first(self.county)
-                .and_then(|v| v.as_str())
-                .map(|s| s.split_whitespace().next().unwrap_or(s))
-                .unwrap_or("");
-            let address_code_val = retrieved_doc
-                .get_first(self.address_code)
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-
-            Ok(Some(AddressResult {
-                address_code: address_code_val.to_string(),
-                province: province_val.to_string(),
-                city: city_val.to_string(),
-                district: district_val.to_string(),
-                county: county_val.to_string(),
-            }))
+    /// 名称与指定区划相近的其他区划，如各地的“朝阳区”“城关镇”，编码不存在时返回空列表
+    ///
+    /// 按本级名称的分词重合程度打分：本级全称完全相同的同名区划（得分 `EXACT_MATCH_SCORE`）
+    /// 排在最前，其次是共用部分名称的区划（如“朝阳区”与“朝阳市”“朝阳县”），同分时按人口和编码排序。
+    /// 可用于审查哪些地名需要上级区划才能确定。
+    #[instrument(name = "similar", skip(self))]
+    pub fn similar(&self, address_code: &str, limit: usize) -> Result<Vec<AddressResult>> {
+        let Some(region) = self.get(address_code)? else {
+            return Ok(Vec::new());
+        };
+        let Some(level) = AdminLevel::from_deep(region.deep) else {
+            return Ok(Vec::new());
+        };
+        let name = region.level_name(level);
+
+        // 单字的分词（如“区”“镇”）几乎所有区划都有，不计入重合
+        let mut tokens = self.tokenize(name);
+        let short_name = region.short_names.get(level);
+        if !short_name.is_empty() && !tokens.iter().any(|t| t == short_name) {
+            tokens.push(short_name.to_string());
+        }
+        tokens.retain(|token| token.chars().count() > 1);
+
+        let constant = |query: Box<dyn Query>, score: Score| -> Box<dyn Query> {
+            Box::new(ConstScoreQuery::new(query, score))
+        };
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![
+            (
+                Occur::Should,
+                constant(
+                    Box::new(TermQuery::new(
+                        Term::from_field_text(self.name, name),
+                        IndexRecordOption::Basic,
+                    )),
+                    EXACT_MATCH_SCORE,
+                ),
+            ),
+            (
+                Occur::MustNot,
+                Box::new(TermQuery::new(
+                    Term::from_field_text(self.address_code, &region.address_code),
+                    IndexRecordOption::Basic,
+                )),
+            ),
+        ];
+        // 每个分词在任一层级的本级名称中出现记 1 分，上级名称中出现不算
+        for token in &tokens {
+            let levels = AdminLevel::ALL
+                .iter()
+                .map(|&level| {
+                    let own_level = BooleanQuery::new(vec![
+                        (
+                            Occur::Must,
+                            constant(
+                                Box::new(TermQuery::new(
+                                    Term::from_field_u64(self.deep, u64::from(level.deep())),
+                                    IndexRecordOption::Basic,
+                                )),
+                                0.0,
+                            ),
+                        ),
+                        (
+                            Occur::Must,
+                            Box::new(TermQuery::new(
+                                Term::from_field_text(self.level_field(level), token),
+                                IndexRecordOption::Basic,
+                            )),
+                        ),
+                    ]);
+                    (Occur::Should, Box::new(own_level) as Box<dyn Query>)
+                })
+                .collect();
+            clauses.push((
+                Occur::Should,
+                constant(Box::new(BooleanQuery::new(levels)), 1.0),
+            ));
+        }
+
+        let mut codes = HashSet::new();
+        let mut results = Vec::new();
+        let query: Box<dyn Query> = Box::new(BooleanQuery::new(clauses));
+        for result in self.iter_query(query, &SearchOptions::new(), None) {
+            if results.len() >= limit {
+                break;
+            }
+            let result = result?;
+            // 撤销区划替换为继任区划后，可能与已有结果或该区划本身重复
+            if result.address_code != region.address_code
+                && codes.insert(result.address_code.clone())
+            {
+                results.push(result);
+            }
+        }
+        Ok(results)
+    }
+
+    /// 逆地理编码：返回中心点距给定位置（纬度、经度）最近的区划，得分为 `EXACT_MATCH_SCORE`
+    ///
+    /// 逐个比较所有带坐标的区划，不区分层级，因此通常返回最近的乡镇街道；
+    /// 索引中没有任何坐标时返回 None。
+    #[instrument(name = "reverse", skip(self))]
+    pub fn reverse(&self, lat: f64, lng: f64) -> Result<Option<AddressResult>> {
+        let searcher = self.reader.searcher();
+        let mut nearest: Option<(f64, DocAddress)> = None;
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let fast_fields = segment_reader.fast_fields();
+            let (Ok(lats), Ok(lngs)) = (fast_fields.f64("lat"), fast_fields.f64("lng")) else {
+                continue;
+            };
+            let alive_bitset = segment_reader.alive_bitset();
+            for doc in 0..segment_reader.max_doc() {
+                if alive_bitset.is_some_and(|alive| !alive.is_alive(doc)) {
+                    continue;
+                }
+                let (Some(doc_lat), Some(doc_lng)) = (lats.first(doc), lngs.first(doc)) else {
+                    continue;
+                };
+                let distance = haversine_km((lat, lng), (doc_lat, doc_lng));
+                if nearest.is_none_or(|(best, _)| distance < best) {
+                    nearest = Some((distance, DocAddress::new(segment_ord as u32, doc)));
+                }
+            }
+        }
+        let Some((distance, doc_address)) = nearest else {
+            return Ok(None);
+        };
+        debug!(distance_km = distance, "逆地理编码完成");
+        let doc = searcher.doc::<TantivyDocument>(doc_address)?;
+        let result = self.to_result(&doc, EXACT_MATCH_SCORE);
+        self.redirect(&searcher, result).map(Some)
+    }
+
+    /// 查询结束后输出日志，并写入查询日志（如已配置）
+    fn finish_search(
+        &self,
+        query_str: &str,
+        processed_query: &str,
+        top: Option<&AddressResult>,
+        hits: usize,
+        started: Instant,
+    ) {
+        let latency_us = started.elapsed().as_micros() as u64;
+        debug!(hits, latency_us, "搜索完成");
+        if let Some(query_log) = &self.query_log {
+            let entry = QueryLogEntry::new(query_str, processed_query, top, hits, latency_us);
+            query_log.record(&entry);
+        }
+    }
+
+    /// 只返回编码的查询结束后输出日志，查询日志中不含层级名称
+    fn finish_code_search(
+        &self,
+        query_str: &str,
+        processed_query: &str,
+        top: Option<&(String, Score)>,
+        hits: usize,
+        started: Instant,
+    ) {
+        let latency_us = started.elapsed().as_micros() as u64;
+        debug!(hits, latency_us, "搜索完成");
+        if let Some(query_log) = &self.query_log {
+            let mut entry = QueryLogEntry::new(query_str, processed_query, None, hits, latency_us);
+            entry.top_code = top.map(|(code, _)| code.clone());
+            entry.score = top.map(|(_, score)| *score);
+            query_log.record(&entry);
+        }
+    }
+}
+
+/// 结果中与查询相关的层级链：截断到最后一个名称包含查询词的层级
+///
+/// 如查询“朝阳”时，朝阳市下的双塔区和朝阳县下的乡镇都截断为“辽宁省 朝阳市”，视为同一种解释。
+fn matched_chain(result: &AddressResult, processed_query: &str) -> [String; 4] {
+    let mut chain = [
+        result.province.clone(),
+        result.city.clone(),
+        result.district.clone(),
+        result.county.clone(),
+    ];
+    let matched = chain.iter().rposition(|name| {
+        processed_query
+            .split_whitespace()
+            .any(|token| name.contains(token))
+    });
+    let keep = matched.map_or(chain.len(), |i| i + 1);
+    for name in &mut chain[keep..] {
+        name.clear();
+    }
+    chain
+}
+
+/// 两条层级链在双方都有值的层级上一致，即一方是另一方的上级或相同
+fn compatible(a: &[String; 4], b: &[String; 4]) -> bool {
+    a.iter()
+        .zip(b)
+        .all(|(x, y)| x.is_empty() || y.is_empty() || x == y)
+}
+
+/// 把部分匹配的正则表达式转换为 tantivy 要求的整词匹配：
+/// 开头没有 `^` 时补 `.*`，结尾没有（未转义的）`$` 时补 `.*`
+fn anchor_pattern(pattern: &str) -> String {
+    let (prefix, rest) = match pattern.strip_prefix('^') {
+        Some(rest) => ("", rest),
+        None => (".*", pattern),
+    };
+    let (body, suffix) = match rest.strip_suffix('$') {
+        Some(body) if !body.ends_with('\\') => (body, ""),
+        _ => (rest, ".*"),
+    };
+    format!("{}{}{}", prefix, body, suffix)
+}
+
+/// 按得分占比计算每个结果的概率，得分全部非正时平均分配
+fn assign_probabilities(results: &mut [AddressResult]) {
+    let total: f32 = results.iter().map(|r| r.score.max(0.0)).sum();
+    let count = results.len() as f32;
+    for result in results.iter_mut() {
+        result.probability = Some(if total > 0.0 {
+            result.score.max(0.0) / total
         } else {
-            Ok(None)
+            1.0 / count
+        });
+    }
+}
+
+/// 默认缓存分词结果的查询数量
+pub const DEFAULT_TOKEN_CACHE_SIZE: usize = 4096;
+
+/// 分词缓存的累计命中情况，见 `AddressIndex::cache_stats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl CacheStats {
+    /// 命中率，没有查找过时为 0
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / total as f64
+    }
+
+    /// 两次统计之间的命中情况
+    pub fn since(&self, earlier: &CacheStats) -> CacheStats {
+        CacheStats {
+            hits: self.hits.saturating_sub(earlier.hits),
+            misses: self.misses.saturating_sub(earlier.misses),
+        }
+    }
+}
+
+/// 查询分词结果的缓存，按两代淘汰：当前一代写满后整体降为上一代，上一代被丢弃
+///
+/// 命中上一代的查询会被移回当前一代，因此反复出现的查询一直留在缓存中，
+/// 效果接近 LRU 而不需要维护访问顺序。
+struct TokenCache {
+    /// 每一代的容量，为 0 表示不缓存
+    capacity: usize,
+    generations: Mutex<[HashMap<String, Arc<[String]>>; 2]>,
+    /// 命中和未命中的次数，缓存关闭时不计数
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl TokenCache {
+    fn new(size: usize) -> Self {
+        Self {
+            capacity: size.div_ceil(2),
+            generations: Mutex::default(),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    fn get(&self, query: &str) -> Option<Arc<[String]>> {
+        if self.capacity == 0 {
+            return None;
+        }
+        let tokens = self.lookup(query);
+        let counter = if tokens.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        tokens
+    }
+
+    fn lookup(&self, query: &str) -> Option<Arc<[String]>> {
+        let mut generations = self.generations.lock().unwrap();
+        let [current, previous] = &mut *generations;
+        if let Some(tokens) = current.get(query) {
+            return Some(Arc::clone(tokens));
+        }
+        let tokens = previous.remove(query)?;
+        Self::push(current, previous, self.capacity, query, Arc::clone(&tokens));
+        Some(tokens)
+    }
+
+    fn heap_size(&self) -> usize {
+        self.generations.lock().unwrap().heap_size()
+    }
+
+    fn clear(&self) {
+        *self.generations.lock().unwrap() = Default::default();
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn insert(&self, query: &str, tokens: &[String]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut generations = self.generations.lock().unwrap();
+        let [current, previous] = &mut *generations;
+        Self::push(current, previous, self.capacity, query, tokens.into());
+    }
+
+    fn push(
+        current: &mut HashMap<String, Arc<[String]>>,
+        previous: &mut HashMap<String, Arc<[String]>>,
+        capacity: usize,
+        query: &str,
+        tokens: Arc<[String]>,
+    ) {
+        if current.len() >= capacity {
+            *previous = std::mem::take(current);
+        }
+        current.insert(query.to_string(), tokens);
+    }
+}
+
+/// 默认的停用词：粘贴的收货信息中常见、与区划无关的词
+pub const DEFAULT_STOPWORDS: [&str; 15] = [
+    "中国",
+    "中华人民共和国",
+    "地址",
+    "详细",
+    "详细地址",
+    "收货",
+    "收货地址",
+    "收货人",
+    "收件",
+    "收件人",
+    "收件地址",
+    "所在地区",
+    "联系人",
+    "电话",
+    "手机",
+];
+
+/// 提示词命中的区划额外获得的得分，远小于一个常见查询词的得分，只用于区分得分接近的同名区划
+pub const HINT_SCORE: Score = 2.0;
+
+/// 区划变更表最多连续跳转的次数，防止变更表中出现环
+const MAX_REDIRECTS: usize = 8;
+
+/// 按区划变更表从某个编码依次跳转得到的继任编码，最后一个为现行编码
+fn successors<'a>(redirects: &'a HashMap<String, String>, code: &str) -> Vec<&'a str> {
+    let mut chain: Vec<&str> = Vec::new();
+    let mut current = code;
+    while let Some(next) = redirects.get(current) {
+        if next == code || chain.contains(&next.as_str()) || chain.len() >= MAX_REDIRECTS {
+            break;
+        }
+        chain.push(next);
+        current = next;
+    }
+    chain
+}
+
+/// 反向整理区划变更表：每个继任编码对应的全部历史编码（已排序）
+fn former_codes(redirects: &HashMap<String, String>) -> HashMap<String, Vec<String>> {
+    let mut former: HashMap<String, Vec<String>> = HashMap::new();
+    for old in redirects.keys() {
+        for code in successors(redirects, old) {
+            former
+                .entry(code.to_string())
+                .or_default()
+                .push(old.clone());
+        }
+    }
+    for codes in former.values_mut() {
+        codes.sort();
+    }
+    former
+}
+
+/// `AddressIndex::warmup` 执行的查询：覆盖省市区县全称、简称和有歧义的地名
+const WARMUP_QUERIES: [&str; 4] = [
+    "北京市朝阳区",
+    "广东省深圳市南山区",
+    "上海浦东陆家嘴",
+    "朝阳",
+];
+
+/// 查询解析器当作运算符的词，混在英文备注中时不作为查询词，否则查询解析失败
+const QUERY_KEYWORDS: [&str; 4] = ["AND", "OR", "NOT", "IN"];
+
+/// `search_iter` 第一页拉取的命中数量
+const INITIAL_PAGE_SIZE: usize = 10;
+
+/// 排序键：得分降序，得分相同时按地址编码升序
+///
+/// 仅按得分排序时，同分结果的顺序取决于文档在索引中的位置，
+/// 重建索引后可能变化，因此用地址编码作为次级排序保证输出稳定。
+/// 打分时只计算得分，编码在取出命中后才从快速字段读取，见 `rank_page`。
+#[derive(Debug, Clone, PartialEq)]
+struct RankKey {
+    score: Score,
+    address_code: String,
+}
+
+/// 人口权重系数：得分乘以 `1 + POPULATION_BOOST * log10(1 + 人口)`
+///
+/// 取对数避免大城市完全压过文本相关性，只在文本得分接近时起决定作用，
+/// 例如“朝阳”默认匹配北京市朝阳区而不是辽宁朝阳市。
+const POPULATION_BOOST: Score = 0.05;
+
+/// 位置偏好的最大加权：与偏好位置重合的区划得分乘以 `1 + BIAS_BOOST`
+const BIAS_BOOST: Score = 0.5;
+
+/// 位置偏好的距离尺度（公里），距离每增加一个尺度，加权衰减为原来的一半左右
+const BIAS_SCALE_KM: f64 = 50.0;
+
+/// 偏好层级的区划的得分加权
+const LEVEL_PREFERENCE_BOOST: Score = 0.5;
+
+/// 文本得分以外的排序权重，默认值即 `POPULATION_BOOST` 等常量
+///
+/// 可以保存为 TOML 文件，由配置 `search.weights` 加载；`tuning::tune` 从标注的查询中拟合这些权重。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RankingWeights {
+    /// 人口权重系数：得分乘以 `1 + population * log10(1 + 人口)`
+    pub population: Score,
+    /// 位置偏好的最大加权，见 `SearchOptions::bias`
+    pub bias: Score,
+    /// 偏好层级的区划的得分加权，见 `SearchOptions::prefer_level`
+    pub level_preference: Score,
+    /// 提示词命中的区划额外获得的得分，见 `AddressIndexBuilder::hints`
+    pub hint: Score,
+    /// 各层级（省、市、区县、乡镇街道）区划的得分系数，默认都为 1
+    pub levels: [Score; 4],
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            population: POPULATION_BOOST,
+            bias: BIAS_BOOST,
+            level_preference: LEVEL_PREFERENCE_BOOST,
+            hint: HINT_SCORE,
+            levels: [1.0; 4],
+        }
+    }
+}
+
+/// 文本得分以外影响排序的搜索选项和权重
+#[derive(Debug, Clone, Copy, Default)]
+struct Ranking {
+    /// 位置偏好，见 `SearchOptions::bias`
+    bias: Option<(f64, f64)>,
+    /// 层级偏好，见 `SearchOptions::prefer_level`
+    prefer_level: Option<AdminLevel>,
+    /// 索引的排序权重
+    weights: RankingWeights,
+}
+
+/// 为每个段生成计算排序键的函数
+fn rank_key_tweaker(
+    segment_reader: &SegmentReader,
+    ranking: Ranking,
+) -> impl Fn(DocId, Score) -> Score {
+    let Ranking {
+        bias,
+        prefer_level,
+        weights,
+    } = ranking;
+    let population = segment_reader
+        .fast_fields()
+        .u64("population")
+        .ok()
+        .map(|column| column.first_or_default_col(0));
+    let coordinates = bias.and_then(|_| {
+        let fast_fields = segment_reader.fast_fields();
+        Some((fast_fields.f64("lat").ok()?, fast_fields.f64("lng").ok()?))
+    });
+    let deep = segment_reader.fast_fields().u64("deep").ok();
+
+    move |doc: DocId, score: Score| {
+        let population = population.as_ref().map_or(0, |column| column.get_val(doc));
+        let mut score = score * (1.0 + weights.population * (1.0 + population as Score).log10());
+
+        // 位置偏好：距离越近加权越大，没有坐标的区划不加权
+        if let (Some(origin), Some((lat, lng))) = (bias, &coordinates) {
+            if let (Some(lat), Some(lng)) = (lat.first(doc), lng.first(doc)) {
+                let distance = haversine_km(origin, (lat, lng));
+                score *= 1.0 + weights.bias / (1.0 + distance / BIAS_SCALE_KM) as Score;
+            }
+        }
+
+        // 层级系数和层级偏好：按本级层级加权
+        if let Some(deep) = deep.as_ref().and_then(|deep| deep.first(doc)) {
+            if let Some(weight) = weights.levels.get(deep as usize) {
+                score *= weight;
+            }
+            if prefer_level.is_some_and(|level| u64::from(level.deep()) == deep) {
+                score *= 1.0 + weights.level_preference;
+            }
+        }
+        score
+    }
+}
+
+/// 按排序键取出一页命中：跳过前 `offset` 条，最多 `limit` 条
+///
+/// 收集器只按得分取前若干条，同分的命中由文档位置决定取舍，重建索引后可能变化。
+/// 因此多取命中，直到本页首尾同分的命中全部取到，再按地址编码排好同分命中后截取本页；
+/// 只为与本页首尾同分或介于其间的命中读取编码。
+/// 设置了截止时间时，超时后停止遍历命中并返回 `Timeout` 错误。
+fn rank_page(
+    searcher: &Searcher,
+    query: &dyn Query,
+    ranking: Ranking,
+    deadline: Option<Deadline>,
+    limit: usize,
+    offset: usize,
+) -> Result<Vec<(RankKey, DocAddress)>> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+    let end = offset + limit;
+    let mut fetch = end + 1;
+    let hits = loop {
+        let hits = top_scores(searcher, query, ranking, deadline, fetch)?;
+        // 取到的命中少于请求的数量说明已取完；否则多取的最后一条得分须低于本页最后一条
+        if hits.len() < fetch || hits[fetch - 1].0 < hits[end - 1].0 {
+            break hits;
+        }
+        fetch *= 2;
+    };
+    if hits.len() <= offset {
+        return Ok(Vec::new());
+    }
+    let end = end.min(hits.len());
+    let (high, low) = (hits[offset].0, hits[end - 1].0);
+    let start = hits.partition_point(|(score, _)| *score > high);
+    let stop = hits.partition_point(|(score, _)| *score >= low);
+    let mut window = hits[start..stop]
+        .iter()
+        .map(|&(score, doc_address)| {
+            let address_code = fast_code(searcher, doc_address)?;
+            Ok((
+                RankKey {
+                    score,
+                    address_code,
+                },
+                doc_address,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    window.sort_by(|(a, _), (b, _)| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.address_code.cmp(&b.address_code))
+    });
+    window.truncate(end - start);
+    Ok(window.split_off(offset - start))
+}
+
+/// 按得分降序取出前 `limit` 条命中，设置了截止时间时超时返回 `Timeout` 错误
+fn top_scores(
+    searcher: &Searcher,
+    query: &dyn Query,
+    ranking: Ranking,
+    deadline: Option<Deadline>,
+    limit: usize,
+) -> Result<Vec<(Score, DocAddress)>> {
+    let collector =
+        TopDocs::with_limit(limit).tweak_score(move |segment_reader: &SegmentReader| {
+            rank_key_tweaker(segment_reader, ranking)
+        });
+    let Some(deadline) = deadline else {
+        return Ok(searcher.search(query, &collector)?);
+    };
+    deadline.check()?;
+    let collector = DeadlineCollector {
+        inner: collector,
+        deadline,
+        expired: AtomicBool::new(false),
+    };
+    let hits = searcher.search(query, &collector)?;
+    if collector.expired.load(Ordering::Relaxed) {
+        return Err(deadline.error());
+    }
+    Ok(hits)
+}
+
+/// 从快速字段读取文档的地址编码，不读取存储的文档
+fn fast_code(searcher: &Searcher, doc_address: DocAddress) -> Result<String> {
+    let mut code = String::new();
+    let codes = searcher
+        .segment_reader(doc_address.segment_ord)
+        .fast_fields()
+        .str("address_code")?;
+    if let Some(codes) = codes {
+        if let Some(ord) = codes.term_ords(doc_address.doc_id).next() {
+            codes
+                .ord_to_str(ord, &mut code)
+                .map_err(TantivyError::from)?;
+        }
+    }
+    Ok(code)
+}
+
+/// 查询的截止时间，在查询开始时按 `SearchOptions::timeout` 确定
+#[derive(Debug, Clone, Copy)]
+struct Deadline {
+    at: Instant,
+    timeout: Duration,
+}
+
+impl Deadline {
+    /// 按搜索选项从当前时刻开始计时，未设置时间上限时返回 None
+    fn start(options: &SearchOptions) -> Option<Self> {
+        options.timeout.map(|timeout| Self {
+            at: Instant::now() + timeout,
+            timeout,
+        })
+    }
+
+    fn expired(&self) -> bool {
+        Instant::now() >= self.at
+    }
+
+    fn error(&self) -> Text2LocationError {
+        Text2LocationError::Timeout(self.timeout)
+    }
+
+    /// 已超时时返回 `Timeout` 错误
+    fn check(&self) -> Result<()> {
+        if self.expired() {
+            return Err(self.error());
+        }
+        Ok(())
+    }
+}
+
+/// 遍历命中时每隔多少篇文档检查一次是否超时
+const DEADLINE_CHECK_INTERVAL: u32 = 1024;
+
+/// 在截止时间到达后停止遍历命中的收集器
+///
+/// tantivy 默认的遍历方式无法中途退出，因此自行驱动打分器逐篇收集，
+/// 超时后记下 `expired` 并放弃剩余的命中。
+struct DeadlineCollector<C> {
+    inner: C,
+    deadline: Deadline,
+    expired: AtomicBool,
+}
+
+impl<C: Collector> Collector for DeadlineCollector<C> {
+    type Fruit = C::Fruit;
+    type Child = C::Child;
+
+    fn for_segment(
+        &self,
+        segment_ord: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        self.inner.for_segment(segment_ord, segment_reader)
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.inner.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+    ) -> tantivy::Result<Self::Fruit> {
+        self.inner.merge_fruits(fruits)
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<<Self::Child as SegmentCollector>::Fruit> {
+        let mut child = self.for_segment(segment_ord, segment_reader)?;
+        if self.expired.load(Ordering::Relaxed) || self.deadline.expired() {
+            self.expired.store(true, Ordering::Relaxed);
+            return Ok(child.harvest());
+        }
+        let alive_bitset = segment_reader.alive_bitset();
+        let mut scorer = weight.scorer(segment_reader, 1.0)?;
+        let mut doc = scorer.doc();
+        let mut visited: u32 = 0;
+        while doc != TERMINATED {
+            if alive_bitset.is_none_or(|alive| alive.is_alive(doc)) {
+                child.collect(doc, scorer.score());
+            }
+            visited += 1;
+            if visited.is_multiple_of(DEADLINE_CHECK_INTERVAL) && self.deadline.expired() {
+                self.expired.store(true, Ordering::Relaxed);
+                break;
+            }
+            doc = scorer.advance();
+        }
+        Ok(child.harvest())
+    }
+}
+
+/// 搜索结果迭代器，按需读取文档
+pub struct SearchIter<'a> {
+    index: &'a AddressIndex,
+    searcher: Searcher,
+    query: Box<dyn Query>,
+    ranking: Ranking,
+    deadline: Option<Deadline>,
+    offset: usize,
+    page_size: usize,
+    page: std::vec::IntoIter<(RankKey, DocAddress)>,
+    exhausted: bool,
+}
+
+impl Iterator for SearchIter<'_> {
+    type Item = Result<AddressResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // 超时后只返回一次错误，之后迭代结束
+            if let Some(Err(e)) = self.deadline.map(|deadline| deadline.check()) {
+                self.deadline = None;
+                self.exhausted = true;
+                self.page = Vec::new().into_iter();
+                return Some(Err(e));
+            }
+            if let Some((rank_key, doc_address)) = self.page.next() {
+                let result = self
+                    .searcher
+                    .doc::<TantivyDocument>(doc_address)
+                    .map_err(Into::into)
+                    .and_then(|d| {
+                        let result = self.index.to_result(&d, rank_key.score);
+                        self.index.redirect(&self.searcher, result)
+                    });
+                return Some(result);
+            }
+            if self.exhausted {
+                return None;
+            }
+
+            // 当前页已消费完，拉取下一页命中地址
+            let page = rank_page(
+                &self.searcher,
+                self.query.as_ref(),
+                self.ranking,
+                self.deadline,
+                self.page_size,
+                self.offset,
+            );
+            match page {
+                Ok(top_docs) => {
+                    self.exhausted = top_docs.len() < self.page_size;
+                    self.offset += top_docs.len();
+                    self.page_size *= 2;
+                    self.page = top_docs.into_iter();
+                }
+                Err(e) => {
+                    self.deadline = None;
+                    self.exhausted = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 层级链相同、只有编码不同的区县，查询时得分必然相同
+    fn tied_district(code: &str) -> AddressRecord {
+        AddressRecord {
+            province: "测试省".to_string(),
+            city: "测试市".to_string(),
+            district: "朝阳区".to_string(),
+            county: String::new(),
+            address_code: code.to_string(),
+            level: AdminLevel::District,
+            id: None,
+            pid: None,
+            population: None,
+            location: None,
+            extra: BTreeMap::new(),
+            short_names: LevelNames::default(),
+            pinyin: LevelNames::default(),
+            aliases: Vec::new(),
+        }
+    }
+
+    /// 编码较大的区划先写入，文档顺序与编码顺序相反
+    fn tied_index(separate_segments: bool) -> AddressIndex {
+        let mut index = AddressIndex::builder().build().unwrap();
+        let records = [tied_district("990102000000"), tied_district("990101000000")];
+        if separate_segments {
+            for record in &records {
+                index.add_documents(std::slice::from_ref(record)).unwrap();
+            }
+        } else {
+            index.add_documents(&records).unwrap();
+        }
+        index.commit().unwrap();
+        index
+    }
+
+    #[test]
+    fn tied_scores_come_out_in_code_order_at_limit_one() {
+        for separate_segments in [false, true] {
+            let index = tied_index(separate_segments);
+            let codes = index
+                .search_codes("朝阳区", &SearchOptions::new().limit(1))
+                .unwrap();
+            assert_eq!(codes.len(), 1);
+            assert_eq!(codes[0].0, "990101000000");
+
+            let first = index.search_first("朝阳区").unwrap().unwrap();
+            assert_eq!(first.address_code, "990101000000");
+
+            let results = index
+                .search("朝阳区", &SearchOptions::new().limit(2))
+                .unwrap();
+            let codes: Vec<_> = results.iter().map(|r| r.address_code.as_str()).collect();
+            assert_eq!(codes, ["990101000000", "990102000000"]);
+            assert_eq!(results[0].score, results[1].score);
+        }
+    }
+
+    #[test]
+    fn tied_group_spanning_page_boundary_stays_in_code_order() {
+        let index = tied_index(false);
+        let searcher = index.reader.searcher();
+        let query = index
+            .build_query("朝阳区", "朝阳区", &SearchOptions::default())
+            .unwrap();
+        let page = |offset| {
+            rank_page(
+                &searcher,
+                query.as_ref(),
+                Ranking::default(),
+                None,
+                1,
+                offset,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(key, _)| key.address_code)
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(page(0), ["990101000000"]);
+        assert_eq!(page(1), ["990102000000"]);
+        assert!(page(2).is_empty());
+    }
+
+    #[test]
+    fn query_syntax_characters_do_not_fail_search() {
+        let index = tied_index(false);
+        let options = SearchOptions::new();
+        for query in [
+            "测试市:朝阳区",
+            "\"",
+            "朝阳区(",
+            "朝阳区 [3号楼",
+            "朝阳区^2 -测试市 +1",
+            "朝阳区 {备注 ~ * ! \\ 门口 <= 1",
+            "朝阳区 AND OR NOT IN",
+        ] {
+            let results = index.search(query, &options);
+            assert!(results.is_ok(), "{query}: {results:?}");
         }
+        let results = index.search("测试市:朝阳区", &options).unwrap();
+        assert_eq!(results[0].address_code, "990101000000");
     }
 }
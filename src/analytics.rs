@@ -0,0 +1,226 @@
+use crate::address_index::{AddressIndex, AddressResult, SearchOptions};
+use crate::error::Result;
+use crate::query_log::QueryLogEntry;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// 歧义判定阈值：得分第二且层级链不同的结果达到第一名得分的该比例时，视为地址冲突
+pub(crate) const CONFLICT_RATIO: f32 = 0.9;
+
+/// 未解析或低置信度查询的可能原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnresolvedCause {
+    /// 没有任何查询词命中已知地名
+    UnknownName,
+    /// 多个不同地址得分接近，无法确定是哪一个
+    Conflict,
+    /// 只有部分查询词命中，可能含有错别字
+    Typo,
+}
+
+impl fmt::Display for UnresolvedCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::UnknownName => "未知地名",
+            Self::Conflict => "地址冲突",
+            Self::Typo => "疑似错别字",
+        })
+    }
+}
+
+/// 一条未解析或低置信度的查询
+#[derive(Debug, Clone, Serialize)]
+pub struct UnresolvedQuery {
+    pub query: String,
+    /// 该查询在日志中出现的次数
+    pub count: usize,
+    pub cause: UnresolvedCause,
+    /// 当前索引给出的最佳结果
+    pub top: Option<AddressResult>,
+}
+
+/// 一批真实查询的覆盖率统计，由 `coverage` 计算，用于比较不同版本的解析效果
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageReport {
+    /// 查询总数，重复的查询分别计数
+    pub total: usize,
+    /// 去重后的查询数
+    pub distinct: usize,
+    /// 置信度足够（见 `classify_results`）的查询数
+    pub resolved: usize,
+    /// 最佳解释的概率（见 `SearchOptions::interpretations`）的平均值，没有结果的查询按 0 计
+    pub average_confidence: f32,
+    /// 各类原因的未解析查询数，重复的查询分别计数
+    pub causes: BTreeMap<UnresolvedCause, usize>,
+    /// 未解析或低置信度的查询，按出现次数降序排列
+    pub unresolved: Vec<UnresolvedQuery>,
+}
+
+impl CoverageReport {
+    /// 解析成功率，没有查询时为 0
+    pub fn resolution_rate(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.resolved as f64 / self.total as f64
+    }
+}
+
+/// 用当前索引重新执行查询，判断是否未解析或置信度低
+///
+/// 判断规则是启发式的：
+/// - 没有结果，或最佳结果不包含任何查询词：未知地名
+/// - 最佳结果只包含部分查询词：疑似错别字
+/// - 另一个不在同一分支上的地址得分接近第一名：地址冲突
+pub fn classify_query(
+    index: &AddressIndex,
+    query: &str,
+) -> Result<Option<(UnresolvedCause, Option<AddressResult>)>> {
+    let results = index.search(query, &SearchOptions::new().limit(5).dedup(true))?;
+    let Some(top) = results.first() else {
+        return Ok(Some((UnresolvedCause::UnknownName, None)));
+    };
+    Ok(classify_results(index, query, &results).map(|cause| (cause, Some(top.clone()))))
+}
+
+/// 按 `classify_query` 的规则判断已有的搜索结果，置信度足够时返回 None
+///
+/// `results` 应为按省市区县链去重、按得分排序的前几个结果，为空时视为未知地名。
+pub fn classify_results(
+    index: &AddressIndex,
+    query: &str,
+    results: &[AddressResult],
+) -> Option<UnresolvedCause> {
+    let Some(top) = results.first() else {
+        return Some(UnresolvedCause::UnknownName);
+    };
+
+    let tokens = index.tokenize(query);
+    let text = format!("{}{}{}{}", top.province, top.city, top.district, top.county);
+    // 结果中的地名可能已转换为繁体，查询词按同样的规则转换后再比较
+    let matched = tokens
+        .iter()
+        .filter(|t| text.contains(index.display_name(t).as_str()))
+        .count();
+
+    if matched == 0 {
+        Some(UnresolvedCause::UnknownName)
+    } else if matched < tokens.len() {
+        Some(UnresolvedCause::Typo)
+    } else if results
+        .iter()
+        .skip(1)
+        .any(|r| !same_branch(r, top) && r.score >= top.score * CONFLICT_RATIO)
+    {
+        Some(UnresolvedCause::Conflict)
+    } else {
+        None
+    }
+}
+
+/// 两个结果是否位于同一分支（一个是另一个的上级或下级）
+fn same_branch(a: &AddressResult, b: &AddressResult) -> bool {
+    [
+        (&a.province, &b.province),
+        (&a.city, &b.city),
+        (&a.district, &b.district),
+        (&a.county, &b.county),
+    ]
+    .iter()
+    .all(|(x, y)| x.is_empty() || y.is_empty() || x == y)
+}
+
+/// 用当前索引执行一批查询，统计解析成功率、平均置信度和未解析的查询
+///
+/// 重复的查询只执行一次，但在各项统计中按出现次数计入；空白查询被忽略。
+pub fn coverage<'a>(
+    index: &AddressIndex,
+    queries: impl IntoIterator<Item = &'a str>,
+) -> Result<CoverageReport> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for query in queries {
+        let query = query.trim();
+        if !query.is_empty() {
+            *counts.entry(query).or_default() += 1;
+        }
+    }
+
+    let options = SearchOptions::new()
+        .limit(5)
+        .dedup(true)
+        .interpretations(true);
+    let outcomes = counts
+        .par_iter()
+        .map(|(&query, &count)| {
+            let results = index.search(query, &options)?;
+            let confidence = results
+                .first()
+                .and_then(|top| top.probability)
+                .unwrap_or(0.0);
+            let cause = classify_results(index, query, &results);
+            Ok((query, count, confidence, cause, results.into_iter().next()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut report = CoverageReport {
+        total: 0,
+        distinct: outcomes.len(),
+        resolved: 0,
+        average_confidence: 0.0,
+        causes: BTreeMap::new(),
+        unresolved: Vec::new(),
+    };
+    let mut confidence_sum = 0.0;
+    for (query, count, confidence, cause, top) in outcomes {
+        report.total += count;
+        confidence_sum += confidence * count as f32;
+        match cause {
+            None => report.resolved += count,
+            Some(cause) => {
+                *report.causes.entry(cause).or_default() += count;
+                report.unresolved.push(UnresolvedQuery {
+                    query: query.to_string(),
+                    count,
+                    cause,
+                    top,
+                });
+            }
+        }
+    }
+    if report.total > 0 {
+        report.average_confidence = confidence_sum / report.total as f32;
+    }
+    report
+        .unresolved
+        .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.query.cmp(&b.query)));
+    Ok(report)
+}
+
+/// 重放查询日志，返回未解析或低置信度的查询，按出现次数降序排列
+pub fn analyze_query_log(
+    index: &AddressIndex,
+    entries: &[QueryLogEntry],
+) -> Result<Vec<UnresolvedQuery>> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for entry in entries {
+        *counts.entry(entry.query.as_str()).or_default() += 1;
+    }
+
+    let mut unresolved = Vec::new();
+    for (query, count) in counts {
+        if let Some((cause, top)) = classify_query(index, query)? {
+            unresolved.push(UnresolvedQuery {
+                query: query.to_string(),
+                count,
+                cause,
+                top,
+            });
+        }
+    }
+
+    unresolved.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.query.cmp(&b.query)));
+    Ok(unresolved)
+}
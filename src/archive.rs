@@ -0,0 +1,160 @@
+use crate::dictionary::Entry;
+use crate::error::{Result, Text2LocationError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+/// 索引文件（.t2l）开头的魔数
+const MAGIC: &[u8; 6] = b"T2LIDX";
+
+/// 索引文件的格式版本，布局发生不兼容的变化时递增
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// 索引文件中除 tantivy 文件以外的元数据
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ArchiveHeader {
+    /// 生成该文件的 text2location 版本
+    pub(crate) crate_version: String,
+    /// 额外字段（字段名，是否索引），导入时按同样的顺序注册
+    pub(crate) extra_fields: Vec<(String, bool)>,
+    /// 区划变更表（旧编码 -> 继任编码）
+    pub(crate) redirects: HashMap<String, String>,
+    /// 区划名称词典，包含各级简称和其他名称
+    pub(crate) dictionary: Vec<Entry>,
+}
+
+/// 单文件索引：元数据加上 tantivy 索引目录中的全部文件
+///
+/// 布局（整数均为小端）：魔数、格式版本（u32）、元数据 JSON 长度（u64）及内容、
+/// 文件个数（u32），之后每个文件依次为文件名长度（u32）、文件名、内容长度（u64）、内容。
+pub(crate) struct Archive {
+    pub(crate) header: ArchiveHeader,
+    pub(crate) files: Vec<(String, Vec<u8>)>,
+}
+
+impl Archive {
+    /// 写出索引文件
+    pub(crate) fn write(&self, mut out: impl Write) -> Result<()> {
+        let header = serde_json::to_vec(&self.header)
+            .map_err(|e| Text2LocationError::Archive(format!("元数据序列化失败: {}", e)))?;
+        let write = |out: &mut dyn Write| -> io::Result<()> {
+            out.write_all(MAGIC)?;
+            out.write_all(&ARCHIVE_VERSION.to_le_bytes())?;
+            out.write_all(&(header.len() as u64).to_le_bytes())?;
+            out.write_all(&header)?;
+            out.write_all(&(self.files.len() as u32).to_le_bytes())?;
+            for (name, data) in &self.files {
+                out.write_all(&(name.len() as u32).to_le_bytes())?;
+                out.write_all(name.as_bytes())?;
+                out.write_all(&(data.len() as u64).to_le_bytes())?;
+                out.write_all(data)?;
+            }
+            out.flush()
+        };
+        write(&mut out).map_err(|e| Text2LocationError::Archive(format!("写入失败: {}", e)))
+    }
+
+    /// 读取索引文件，魔数或格式版本不符时返回错误，词典中区划的层级无效时返回 `SchemaMismatch`
+    pub(crate) fn read(mut input: impl Read) -> Result<Self> {
+        let invalid = |e: io::Error| Text2LocationError::Archive(format!("读取失败: {}", e));
+
+        let mut magic = [0u8; 6];
+        input.read_exact(&mut magic).map_err(invalid)?;
+        if &magic != MAGIC {
+            return Err(Text2LocationError::Archive(
+                "不是 text2location 索引文件".to_string(),
+            ));
+        }
+        let version = read_u32(&mut input).map_err(invalid)?;
+        if version != ARCHIVE_VERSION {
+            return Err(Text2LocationError::Archive(format!(
+                "不支持的索引文件格式版本 {}（当前为 {}）",
+                version, ARCHIVE_VERSION
+            )));
+        }
+
+        let header_len = read_u64(&mut input).map_err(invalid)?;
+        let header = read_bytes(&mut input, header_len).map_err(invalid)?;
+        let header: ArchiveHeader = serde_json::from_slice(&header)
+            .map_err(|e| Text2LocationError::Archive(format!("元数据无效: {}", e)))?;
+        for entry in &header.dictionary {
+            entry.validate()?;
+        }
+
+        let count = read_u32(&mut input).map_err(invalid)?;
+        let mut files = Vec::new();
+        for _ in 0..count {
+            let name_len = read_u32(&mut input).map_err(invalid)?;
+            let name =
+                String::from_utf8(read_bytes(&mut input, u64::from(name_len)).map_err(invalid)?)
+                    .map_err(|_| {
+                        Text2LocationError::Archive("文件名不是有效的 UTF-8".to_string())
+                    })?;
+            let data_len = read_u64(&mut input).map_err(invalid)?;
+            let data = read_bytes(&mut input, data_len).map_err(invalid)?;
+            files.push((name, data));
+        }
+        Ok(Self { header, files })
+    }
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    input.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    input.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// 读取指定长度的内容，不按声明的长度预先分配，避免损坏的文件导致过量分配
+fn read_bytes(input: &mut impl Read, len: u64) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    input.take(len).read_to_end(&mut data)?;
+    if data.len() as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive_with_deep(deep: usize) -> Vec<u8> {
+        let entry = serde_json::json!({
+            "chain": ["广东省", "梅州市", "兴宁市", ""],
+            "deep": deep,
+            "short_names": ["广东", "梅州", "兴宁", ""],
+            "aliases": [],
+            "address_code": "441481000000",
+        });
+        let archive = Archive {
+            header: ArchiveHeader {
+                crate_version: env!("CARGO_PKG_VERSION").to_string(),
+                extra_fields: Vec::new(),
+                redirects: HashMap::new(),
+                dictionary: vec![serde_json::from_value(entry).unwrap()],
+            },
+            files: Vec::new(),
+        };
+        let mut out = Vec::new();
+        archive.write(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn out_of_range_deep_is_a_schema_mismatch() {
+        assert!(Archive::read(archive_with_deep(2).as_slice()).is_ok());
+        let err = Archive::read(archive_with_deep(4).as_slice())
+            .err()
+            .expect("层级 4 应被拒绝");
+        assert!(
+            matches!(err, Text2LocationError::SchemaMismatch(_)),
+            "{err:?}"
+        );
+    }
+}
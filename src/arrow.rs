@@ -0,0 +1,58 @@
+use crate::address_index::{AddressIndex, AddressResult};
+use crate::error::{Result, Text2LocationError};
+use arrow_array::{Array, ArrayRef, Float32Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Fields, Schema};
+use std::sync::Arc;
+
+/// 解析结果列，与 `resolve_arrow` 返回的 RecordBatch 一一对应
+const COLUMNS: [&str; 5] = ["province", "city", "district", "county", "address_code"];
+
+/// `resolve_arrow` 输出的列定义
+pub fn result_fields() -> Fields {
+    COLUMNS
+        .iter()
+        .map(|name| Field::new(*name, DataType::Utf8, true))
+        .chain(std::iter::once(Field::new(
+            "score",
+            DataType::Float32,
+            true,
+        )))
+        .collect()
+}
+
+/// 批量解析一列地址，返回与输入等长的列式结果
+///
+/// 输出列依次为 province、city、district、county、address_code（utf8）
+/// 和 score（float32）。输入为 null、空字符串或未找到匹配时，该行各列均为 null。
+pub fn resolve_arrow(index: &AddressIndex, addresses: &StringArray) -> Result<RecordBatch> {
+    let mut results = Vec::with_capacity(addresses.len());
+    for address in addresses.iter() {
+        let result = match address.map(str::trim) {
+            Some(query) if !query.is_empty() => index.resolve(query)?,
+            _ => None,
+        };
+        results.push(result);
+    }
+
+    let mut columns: Vec<ArrayRef> = Vec::with_capacity(COLUMNS.len() + 1);
+    let levels: [fn(&AddressResult) -> &str; 5] = [
+        |r| &r.province,
+        |r| &r.city,
+        |r| &r.district,
+        |r| &r.county,
+        |r| &r.address_code,
+    ];
+    for level in levels {
+        let values: StringArray = results.iter().map(|r| r.as_ref().map(level)).collect();
+        columns.push(Arc::new(values));
+    }
+    columns.push(Arc::new(
+        results
+            .iter()
+            .map(|r| r.as_ref().map(|r| r.score))
+            .collect::<Float32Array>(),
+    ));
+
+    RecordBatch::try_new(Arc::new(Schema::new(result_fields())), columns)
+        .map_err(|e| Text2LocationError::InvalidInput(e.to_string()))
+}
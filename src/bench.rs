@@ -0,0 +1,88 @@
+use crate::address_index::{AddressIndex, SearchOptions};
+use crate::error::{Result, Text2LocationError};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// 一次压测的结果，由 `bench` 计算，用于比较不同后端和配置在自有数据上的性能
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    /// 执行的查询数
+    pub queries: usize,
+    /// 并发执行查询的线程数
+    pub concurrency: usize,
+    /// 查询失败（如超时）的次数
+    pub errors: usize,
+    /// 总耗时（毫秒）
+    pub elapsed_ms: f64,
+    /// 吞吐量（每秒查询数）
+    pub throughput: f64,
+    /// 单条查询延迟的中位数（毫秒）
+    pub p50_ms: f64,
+    /// 单条查询延迟的 99 分位数（毫秒）
+    pub p99_ms: f64,
+    /// 压测期间分词缓存的命中率，缓存关闭时为 0
+    pub cache_hit_rate: f64,
+}
+
+/// 用 `concurrency` 个线程并发执行查询，统计吞吐量、延迟分位数和分词缓存命中率
+///
+/// 每条查询按 `options` 调用一次 `AddressIndex::search`，延迟包含分词、检索和结果组装。
+/// 压测前不会自动预热，需要排除冷启动开销时先调用 `AddressIndex::warmup`。
+pub fn bench<'a>(
+    index: &AddressIndex,
+    queries: impl IntoIterator<Item = &'a str>,
+    concurrency: usize,
+    options: &SearchOptions,
+) -> Result<BenchReport> {
+    let queries: Vec<&str> = queries.into_iter().collect();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(concurrency)
+        .build()
+        .map_err(|e| Text2LocationError::InvalidInput(format!("无法创建压测线程池: {}", e)))?;
+
+    let cache_before = index.cache_stats();
+    let started = Instant::now();
+    let timings: Vec<(Duration, bool)> = pool.install(|| {
+        queries
+            .par_iter()
+            .map(|query| {
+                let started = Instant::now();
+                let ok = index.search(query, options).is_ok();
+                (started.elapsed(), ok)
+            })
+            .collect()
+    });
+    let elapsed = started.elapsed();
+    let cache = index.cache_stats().since(&cache_before);
+
+    let mut latencies: Vec<Duration> = timings.iter().map(|&(latency, _)| latency).collect();
+    latencies.sort_unstable();
+    Ok(BenchReport {
+        queries: queries.len(),
+        concurrency: pool.current_num_threads(),
+        errors: timings.iter().filter(|&&(_, ok)| !ok).count(),
+        elapsed_ms: millis(elapsed),
+        throughput: if elapsed.is_zero() {
+            0.0
+        } else {
+            queries.len() as f64 / elapsed.as_secs_f64()
+        },
+        p50_ms: millis(percentile(&latencies, 0.5)),
+        p99_ms: millis(percentile(&latencies, 0.99)),
+        cache_hit_rate: cache.hit_rate(),
+    })
+}
+
+/// 已排序的延迟中的分位数（最近秩法），没有数据时为 0
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((sorted.len() as f64 * p).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1]
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
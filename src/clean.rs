@@ -0,0 +1,147 @@
+/// 成对的括号，括号内的备注整体去掉
+const BRACKETS: [(char, char); 6] = [
+    ('(', ')'),
+    ('（', '）'),
+    ('[', ']'),
+    ('【', '】'),
+    ('{', '}'),
+    ('〔', '〕'),
+];
+
+/// 查询语法中有特殊含义的 ASCII 字符（字段名、短语、分组、范围、加权、必选和排除等）
+const QUERY_SYNTAX: [char; 20] = [
+    ':', '"', '\'', '`', '(', ')', '[', ']', '{', '}', '^', '~', '*', '!', '\\', '+', '-', '<',
+    '>', '=',
+];
+
+/// 数字达到这个位数的字母数字串视为订单号、运单号或电话号码
+const MIN_NUMBER_DIGITS: usize = 7;
+
+/// 去掉地址文本中的噪声，在分词前调用
+///
+/// 快递面单、订单备注中常混有订单号、电话号码、表情符号、零宽字符和括号备注，
+/// 从微信、Excel 复制的文本还常带有 BOM、换行等控制字符和乱码，
+/// 它们分词后会进入 OR 查询、干扰排序。清理规则：
+///
+/// - 括号（中英文圆括号、方括号、花括号、【】）及其中的内容，支持嵌套，未闭合的括号保留
+/// - 含 7 位及以上数字的字母数字串（如 `SF1234567890`、`138-0000-0000`）和 `+86` 等国际区号，
+///   门牌号等短数字保留
+/// - 表情符号、变体选择符和零宽字符（零宽字符直接删除，不替换为空格）
+/// - 零宽字符之外的其他不可见字符：BOM、软连字符、双向文本控制符、韩文填充符等，直接删除
+/// - 控制字符（换行、制表符、`\0` 等）、私用区字符和替换字符 `U+FFFD`（编码错误产生的乱码）
+/// - 上述处理后剩下的查询语法字符（冒号、引号、未闭合的括号、`^`、`-` 等），
+///   否则它们会被查询解析器当作语法，导致解析失败或改变查询含义
+///
+/// 其余被去掉的部分替换为空格，避免前后文字被拼接成新词。
+pub fn strip_noise(text: &str) -> String {
+    let text: String = text
+        .chars()
+        .filter(|&c| !is_invisible(c))
+        .map(|c| {
+            if c.is_control() || is_emoji(c) || is_garbage(c) {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect();
+    strip_numbers(&strip_brackets(&text))
+        .chars()
+        .map(|c| if QUERY_SYNTAX.contains(&c) { ' ' } else { c })
+        .collect()
+}
+
+fn strip_brackets(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    // 尚未闭合的括号：（期望的右括号，左括号在 out 中的位置）
+    let mut open: Vec<(char, usize)> = Vec::new();
+    for c in text.chars() {
+        if let Some(&(_, close)) = BRACKETS.iter().find(|(left, _)| *left == c) {
+            open.push((close, out.len()));
+            out.push(c);
+        } else if open.last().is_some_and(|&(close, _)| close == c) {
+            let (_, start) = open.pop().unwrap();
+            out.truncate(start);
+            out.push(' ');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn strip_numbers(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut start = 0;
+    while start < chars.len() {
+        if !is_number_char(chars[start]) || chars[start] == '-' {
+            out.push(chars[start]);
+            start += 1;
+            continue;
+        }
+        let mut end = start;
+        while end < chars.len() && is_number_char(chars[end]) {
+            end += 1;
+        }
+        let run = &chars[start..end];
+        // 以 + 开头的是国际区号，如 +86
+        if run[0] == '+' || run.iter().filter(|c| c.is_ascii_digit()).count() >= MIN_NUMBER_DIGITS {
+            out.push(' ');
+        } else {
+            out.extend(run);
+        }
+        start = end;
+    }
+    out
+}
+
+/// 订单号、电话号码中可能出现的字符
+fn is_number_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '+'
+}
+
+/// 零宽字符、BOM、软连字符、双向文本控制符、韩文填充符等不可见字符
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}'
+            | '\u{034F}'
+            | '\u{061C}'
+            | '\u{115F}'
+            | '\u{1160}'
+            | '\u{17B4}'
+            | '\u{17B5}'
+            | '\u{180B}'..='\u{180F}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{206F}'
+            | '\u{3164}'
+            | '\u{FEFF}'
+            | '\u{FFA0}'
+            | '\u{FFF9}'..='\u{FFFB}'
+    )
+}
+
+/// 表情符号、杂项符号及其变体选择符
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1F000}'..='\u{1FAFF}'
+            | '\u{20E3}'
+            | '\u{2300}'..='\u{23FF}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{2B00}'..='\u{2BFF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{E0020}'..='\u{E007F}'
+            | '\u{3030}'
+            | '\u{303D}'
+            | '\u{3297}'
+            | '\u{3299}'
+    )
+}
+
+/// 私用区字符（部分输入法和聊天软件的自定义表情）和替换字符，通常是乱码
+fn is_garbage(c: char) -> bool {
+    matches!(c, '\u{E000}'..='\u{F8FF}' | '\u{FFFD}')
+}
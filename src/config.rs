@@ -0,0 +1,248 @@
+use crate::csv_loader::IndexingPolicy;
+use crate::error::{Result, Text2LocationError};
+use crate::locale::Locale;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 未配置数据文件时使用的默认路径
+pub const DEFAULT_DATA: &str = "./src/areas.csv";
+
+/// 未配置时批量任务检查点的保存目录
+pub const DEFAULT_JOBS: &str = "./.text2location/jobs";
+
+/// 配置文件（TOML），优先级：命令行参数 > `T2L_*` 环境变量 > 配置文件 > 默认值
+///
+/// ```toml
+/// data = "./src/areas.csv"
+/// # index = "./areas.t2l"
+/// indexing_policy = "leaf-only"
+/// query_log = "/var/log/text2location/queries.jsonl"
+/// redirects = "./data/changes.csv"
+/// jobs = "/var/lib/text2location/jobs"
+///
+/// [search]
+/// limit = 5
+/// synonyms = "./data/synonyms.csv"
+/// hints = "./data/hints.csv"
+/// weights = "./data/weights.toml"
+/// stopwords = ["中国", "收货地址", "快递"]
+///
+/// [writer]
+/// heap_size = 200000000
+/// threads = 4
+/// optimize = true
+///
+/// [output]
+/// separator = " "
+/// pinyin = true
+/// traditional = false
+/// locale = "en"
+///
+/// [datasets]
+/// 2019 = "./data/areas-2019.csv"
+///
+/// [server]
+/// tcp = "0.0.0.0:7070"
+/// workers = 8
+/// rate_limit = 20
+/// rate_burst = 40
+///
+/// [server.api_keys]
+/// logistics = "change-me"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// 行政区划数据文件
+    pub data: Option<PathBuf>,
+    /// 预构建的索引文件（由 export 子命令生成），设置后直接加载，不再读取数据文件建索引
+    pub index: Option<PathBuf>,
+    /// 索引策略：all-levels / leaf-only
+    pub indexing_policy: Option<IndexingPolicy>,
+    /// 查询日志（JSONL）文件
+    pub query_log: Option<PathBuf>,
+    /// 区划变更表（CSV，列 old_code,new_code），命中已撤销的区划时返回继任区划
+    pub redirects: Option<PathBuf>,
+    /// 批量任务（batch --job）检查点的保存目录
+    pub jobs: Option<PathBuf>,
+    pub search: SearchConfig,
+    pub writer: WriterConfig,
+    pub output: OutputConfig,
+    pub server: ServerConfig,
+    /// 命名数据集（名称 -> 数据文件），如不同年份的区划快照
+    pub datasets: BTreeMap<String, PathBuf>,
+}
+
+/// 查询相关配置
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchConfig {
+    /// search / repl 返回的候选结果数量
+    pub limit: Option<usize>,
+    /// 同义词表（CSV，列 term,expansion），查询时把地标、俗称扩展为区划名称
+    pub synonyms: Option<PathBuf>,
+    /// 提示词表（CSV，列 keyword,region），用道路、地标等关键词区分同名区划
+    pub hints: Option<PathBuf>,
+    /// 排序权重文件（TOML，由 tune 子命令生成）
+    pub weights: Option<PathBuf>,
+    /// 分词后丢弃的停用词，替换内置的默认列表，设为空列表可关闭
+    pub stopwords: Option<Vec<String>>,
+    /// 缓存最近多少个查询的分词结果，默认 `DEFAULT_TOKEN_CACHE_SIZE`，设为 0 关闭缓存
+    pub token_cache_size: Option<usize>,
+}
+
+/// 建索引时索引写入器的配置
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WriterConfig {
+    /// 内存预算（字节），默认 50MB；大数据集可调大，内存受限的容器可调小
+    pub heap_size: Option<usize>,
+    /// 写入线程数，默认按 CPU 数量
+    pub threads: Option<usize>,
+    /// 加载数据后把索引合并为一个段，启动稍慢，查询延迟更低（适合常驻服务）
+    pub optimize: bool,
+}
+
+/// 查询结果输出配置
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    /// 结果中 full_address 各级之间的分隔符，默认不加分隔符
+    pub separator: Option<String>,
+    /// 在结果中返回各级名称的拼音
+    pub pinyin: bool,
+    /// 以繁体中文返回地名（需要启用 traditional 功能）
+    pub traditional: bool,
+    /// 文本输出的标签语言：zh-CN（默认）/ en
+    pub locale: Locale,
+}
+
+/// 常驻服务配置，同时设置 `unix` 和 `tcp` 时两者都监听
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Unix 域套接字路径
+    pub unix: Option<PathBuf>,
+    /// TCP 监听地址
+    pub tcp: Option<String>,
+    /// 搜索线程数，默认按 CPU 数量；连接线程只负责读写，查询都在这些线程中执行
+    pub workers: Option<usize>,
+    /// TCP 服务中每个客户端（配置了 API key 时按 key，否则按 IP）每秒允许的请求数，默认不限流
+    pub rate_limit: Option<f64>,
+    /// 限流时允许的突发请求数，默认为每秒请求数（向上取整）
+    pub rate_burst: Option<u32>,
+    /// TCP 服务的 API key（名称 -> key），非空时每个连接须先发送 `AUTH <key>`，
+    /// 请求数按名称统计；名称只用于日志和统计，不会泄露 key
+    pub api_keys: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// 读取并解析配置文件
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| {
+            Text2LocationError::InvalidInput(format!("无法读取配置文件 {}: {}", path.display(), e))
+        })?;
+        toml::from_str(&content).map_err(|e| {
+            Text2LocationError::InvalidInput(format!("配置文件 {} 无效: {}", path.display(), e))
+        })
+    }
+
+    /// 用 `T2L_*` 环境变量覆盖配置，便于容器部署时不挂载配置文件：
+    ///
+    /// - `T2L_DATA`：行政区划数据文件
+    /// - `T2L_INDEX`：预构建的索引文件
+    /// - `T2L_LIMIT`：候选结果数量
+    /// - `T2L_TOKEN_CACHE`：分词缓存的查询数
+    /// - `T2L_BIND`：serve 的 TCP 监听地址，设置后不再使用配置文件中的 Unix 套接字
+    /// - `T2L_API_KEYS`：serve 的 API key，格式为 `名称=key`，多个用逗号分隔，替换配置文件中的设置
+    ///
+    /// 日志级别由 `T2L_LOG` 设置，在初始化日志时读取。
+    pub fn apply_env(&mut self) -> Result<()> {
+        if let Some(data) = env::var_os("T2L_DATA") {
+            self.data = Some(data.into());
+        }
+        if let Some(index) = env::var_os("T2L_INDEX") {
+            self.index = Some(index.into());
+        }
+        if let Some(limit) = env_usize("T2L_LIMIT")? {
+            self.search.limit = Some(limit);
+        }
+        if let Some(size) = env_usize("T2L_TOKEN_CACHE")? {
+            self.search.token_cache_size = Some(size);
+        }
+        if let Ok(bind) = env::var("T2L_BIND") {
+            self.server.tcp = Some(bind);
+            self.server.unix = None;
+        }
+        if let Ok(keys) = env::var("T2L_API_KEYS") {
+            self.server.api_keys = parse_api_keys(&keys)?;
+        }
+        Ok(())
+    }
+
+    /// 行政区划数据文件，未配置时为 `DEFAULT_DATA`
+    pub fn data_path(&self) -> &Path {
+        self.data.as_deref().unwrap_or(Path::new(DEFAULT_DATA))
+    }
+
+    /// 批量任务检查点的保存目录，未配置时为 `DEFAULT_JOBS`
+    pub fn jobs_dir(&self) -> &Path {
+        self.jobs.as_deref().unwrap_or(Path::new(DEFAULT_JOBS))
+    }
+
+    /// 数据集对应的数据文件，None 表示默认数据文件
+    pub fn dataset_path(&self, name: Option<&str>) -> Result<&Path> {
+        match name {
+            None => Ok(self.data_path()),
+            Some(name) => self
+                .datasets
+                .get(name)
+                .map(PathBuf::as_path)
+                .ok_or_else(|| Text2LocationError::InvalidInput(format!("未知的数据集: {}", name))),
+        }
+    }
+}
+
+/// 解析 `名称=key,名称=key` 格式的 API key 列表，空项忽略
+fn parse_api_keys(keys: &str) -> Result<BTreeMap<String, String>> {
+    keys.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((name, key)) if !name.trim().is_empty() && !key.trim().is_empty() => {
+                Ok((name.trim().to_string(), key.trim().to_string()))
+            }
+            _ => Err(Text2LocationError::InvalidInput(
+                "T2L_API_KEYS 的格式应为 名称=key，多个用逗号分隔".to_string(),
+            )),
+        })
+        .collect()
+}
+
+/// 读取表示数量的环境变量，未设置时为 None
+fn env_usize(name: &str) -> Result<Option<usize>> {
+    let Ok(value) = env::var(name) else {
+        return Ok(None);
+    };
+    value.trim().parse().map(Some).map_err(|_| {
+        Text2LocationError::InvalidInput(format!("{} 不是有效的数量: {}", name, value))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_keys_from_env_format() {
+        let keys = parse_api_keys("logistics=secret, billing = other ,").unwrap();
+        assert_eq!(keys["logistics"], "secret");
+        assert_eq!(keys["billing"], "other");
+        assert!(parse_api_keys("secret").is_err());
+        assert!(parse_api_keys("logistics=").is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::address_index::{AddressIndex, AddressResult};
+use crate::error::Result;
+use serde::Serialize;
+
+/// 从收货信息中拆分出的收件人、电话和地址，以及地址的解析结果
+#[derive(Debug, Clone, Serialize)]
+pub struct Consignee {
+    /// 收件人姓名
+    pub name: Option<String>,
+    /// 手机号或座机号（只保留数字）
+    pub phone: Option<String>,
+    /// 去掉姓名和电话后的地址文本
+    pub address: String,
+    /// 地址的最佳解析结果
+    pub result: Option<AddressResult>,
+}
+
+/// 常见的字段标签，拆分前替换为分隔符
+const LABELS: [&str; 12] = [
+    "收货人",
+    "收件人",
+    "联系人",
+    "姓名",
+    "手机号码",
+    "手机号",
+    "手机",
+    "联系电话",
+    "电话",
+    "详细地址",
+    "收货地址",
+    "地址",
+];
+
+/// 出现这些字的片段视为地址而不是姓名
+pub(crate) const ADDRESS_CHARS: &str = "省市区县镇乡街道路巷号村楼栋室弄";
+
+/// 拆分粘贴的收货信息（如“张三 13800000000 广东省深圳市南山区…”），并解析其中的地址
+///
+/// 拆分是启发式的：电话取第一个符合手机号或座机格式的数字串，
+/// 姓名取不含地址用字的 2 到 4 个汉字的片段，其余片段按原顺序拼接为地址。
+pub fn parse_consignee(index: &AddressIndex, text: &str) -> Result<Consignee> {
+    let (name, phone, address) = split_consignee(text);
+    let result = if address.is_empty() {
+        None
+    } else {
+        index.resolve(&address)?
+    };
+    Ok(Consignee {
+        name,
+        phone,
+        address,
+        result,
+    })
+}
+
+/// 只拆分不解析，返回（姓名，电话，地址）
+pub fn split_consignee(text: &str) -> (Option<String>, Option<String>, String) {
+    let mut text = text.to_string();
+    for label in LABELS {
+        text = text.replace(label, " ");
+    }
+    let (phone, text) = extract_phone(&text);
+
+    let mut name = None;
+    let mut address = Vec::new();
+    for segment in text
+        .split(|c: char| c.is_whitespace() || ",，;；、:：|/".contains(c))
+        .filter(|s| !s.is_empty())
+    {
+        if name.is_none() && is_name(segment) {
+            name = Some(segment.to_string());
+        } else {
+            address.push(segment);
+        }
+    }
+    (name, phone, address.concat())
+}
+
+/// 找出第一个电话号码，返回号码和去掉号码后的文本
+fn extract_phone(text: &str) -> (Option<String>, String) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut start = 0;
+    while start < chars.len() {
+        if !chars[start].is_ascii_digit() && chars[start] != '+' {
+            start += 1;
+            continue;
+        }
+        // 号码中允许出现空格和连字符，如 138-0000-0000、+86 138 0000 0000
+        let mut end = start + 1;
+        while end < chars.len() && (chars[end].is_ascii_digit() || "- ".contains(chars[end])) {
+            end += 1;
+        }
+        while end > start && !chars[end - 1].is_ascii_digit() {
+            end -= 1;
+        }
+        let run: String = chars[start..end].iter().collect();
+        if let Some(phone) = normalize_phone(&run) {
+            let mut rest: String = chars[..start].iter().collect();
+            rest.push(' ');
+            rest.extend(&chars[end..]);
+            return (Some(phone), rest);
+        }
+        start = end.max(start + 1);
+    }
+    (None, text.to_string())
+}
+
+/// 校验并规范化电话号码：11 位手机号，或以 0 开头的 10 到 12 位座机号
+fn normalize_phone(run: &str) -> Option<String> {
+    let digits: String = run.chars().filter(char::is_ascii_digit).collect();
+    let digits = match digits.strip_prefix("86") {
+        Some(rest) if run.starts_with('+') || rest.len() == 11 => rest.to_string(),
+        _ => digits,
+    };
+    let bytes = digits.as_bytes();
+    let mobile = bytes.len() == 11 && bytes[0] == b'1' && (b'3'..=b'9').contains(&bytes[1]);
+    let landline = (10..=12).contains(&bytes.len()) && bytes[0] == b'0';
+    (mobile || landline).then_some(digits)
+}
+
+/// 2 到 4 个汉字且不含地址用字的片段视为姓名
+fn is_name(segment: &str) -> bool {
+    let len = segment.chars().count();
+    (2..=4).contains(&len)
+        && segment
+            .chars()
+            .all(|c| ('\u{4e00}'..='\u{9fff}').contains(&c) || c == '·')
+        && !segment.chars().any(|c| ADDRESS_CHARS.contains(c))
+}
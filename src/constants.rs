@@ -0,0 +1,9 @@
+// 构建脚本和库共用的常量：build.rs 通过 `#[path]` 直接包含本文件，因此这里不能引用 crate 中的其他模块
+
+/// 索引字段布局的版本，随每次提交写入 tantivy 元数据（meta.json 的 payload）
+///
+/// 增删字段或改变字段含义时递增。
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// 索引写入器默认的内存预算（字节），所有写入线程共享
+pub const DEFAULT_WRITER_HEAP_SIZE: usize = 50_000_000;
@@ -1,26 +1,132 @@
-use serde::Deserialize;
-use std::collections::HashMap;
-use std::fs::File;
+use crate::error::{Result, Text2LocationError};
+use encoding_rs::GB18030;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::{self, Cursor};
 use std::path::Path;
+use std::str::FromStr;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Region {
     pub id: u64,
     pub pid: u64,
     pub deep: u8,
-    #[allow(dead_code)]
+    /// 简称，如“广东”
     pub name: String,
     #[allow(dead_code)]
     pub pinyin_prefix: String,
-    #[allow(dead_code)]
+    /// 简称的拼音，如“guang dong”
     pub pinyin: String,
     pub ext_id: String,
+    /// 全称，如“广东省”
     pub ext_name: String,
+    /// 可选的人口（或订单量）列，用于排序加权
+    #[serde(default)]
+    pub population: Option<u64>,
+    /// 可选的中心点纬度、经度列，用于按位置偏好排序
+    #[serde(default)]
+    pub lat: Option<f64>,
+    #[serde(default)]
+    pub lng: Option<f64>,
+}
+
+impl Region {
+    /// 中心点坐标，纬度和经度都存在时才返回
+    pub fn location(&self) -> Option<(f64, f64)> {
+        Some((self.lat?, self.lng?))
+    }
+}
+
+/// 输入文件的字符编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InputEncoding {
+    /// 按文件开头的内容判断：是有效的 UTF-8 时按 UTF-8 解码，否则按 GB18030 解码
+    #[default]
+    Auto,
+    Utf8,
+    /// GBK、GB2312 及其超集 GB18030，统一按 GB18030 解码
+    Gb18030,
+}
+
+impl InputEncoding {
+    /// 把字节解码为文本，UTF-8 文本开头的 BOM 会被去掉
+    ///
+    /// 按 GB18030 解码时无法识别的字节替换为 `U+FFFD`，查询前会被 `strip_noise` 去掉。
+    pub fn decode(self, bytes: &[u8]) -> io::Result<String> {
+        let utf8 = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        match self {
+            Self::Utf8 => String::from_utf8(utf8.to_vec()).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "不是有效的 UTF-8 文本，GBK 编码的文件需要指定 gbk 编码",
+                )
+            }),
+            Self::Gb18030 => Ok(GB18030.decode_without_bom_handling(bytes).0.into_owned()),
+            Self::Auto => self.detect(bytes).decode(bytes),
+        }
+    }
+
+    /// 确定整个文件使用的编码：指定了编码时原样返回，auto 时按文件开头的内容判断
+    ///
+    /// `head` 是文件开头的一段字节（不必是整个文件）：以 UTF-8 BOM 开头或是有效的 UTF-8
+    /// （末尾被截断的多字节字符不算错误）时为 UTF-8，否则为 GB18030。
+    pub fn detect(self, head: &[u8]) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        if head.starts_with(b"\xEF\xBB\xBF") {
+            return Self::Utf8;
+        }
+        match std::str::from_utf8(head) {
+            Ok(_) => Self::Utf8,
+            Err(e) if e.error_len().is_none() => Self::Utf8,
+            Err(_) => Self::Gb18030,
+        }
+    }
+}
+
+impl FromStr for InputEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "utf-8" | "utf8" => Ok(Self::Utf8),
+            "gbk" | "gb2312" | "gb18030" => Ok(Self::Gb18030),
+            other => Err(format!(
+                "未知的编码: {}（可选 auto / utf-8 / gbk / gb18030）",
+                other
+            )),
+        }
+    }
 }
 
-pub fn load_regions<P: AsRef<Path>>(path: P) -> anyhow::Result<Vec<Region>> {
-    let file = File::open(path)?;
-    let mut rdr = csv::Reader::from_reader(file);
+impl fmt::Display for InputEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Auto => "auto",
+            Self::Utf8 => "utf-8",
+            Self::Gb18030 => "gb18030",
+        })
+    }
+}
+
+/// 打开 CSV 文件，UTF-8 和 GBK（GB18030）编码的文件都可以读取，按内容自动判断
+///
+/// 从旧系统或 Windows 版 Excel 导出的 CSV 常为 GBK 编码，直接按 UTF-8 读取会在反序列化时失败。
+fn open_csv<P: AsRef<Path>>(path: P) -> Result<csv::Reader<Cursor<String>>> {
+    let bytes = fs::read(path).map_err(Text2LocationError::data_load)?;
+    let text = InputEncoding::Auto
+        .decode(&bytes)
+        .map_err(Text2LocationError::data_load)?;
+    Ok(csv::Reader::from_reader(Cursor::new(text)))
+}
+
+pub fn load_regions<P: AsRef<Path>>(path: P) -> Result<Vec<Region>> {
+    let mut rdr = open_csv(path)?;
     let mut regions = Vec::new();
     for result in rdr.deserialize() {
         let record: Region = result?;
@@ -29,6 +135,158 @@ pub fn load_regions<P: AsRef<Path>>(path: P) -> anyhow::Result<Vec<Region>> {
     Ok(regions)
 }
 
-pub fn build_region_map(regions: &[Region]) -> HashMap<u64, Region> {
-    regions.iter().map(|r| (r.id, r.clone())).collect()
+/// 按 id 索引区划，用于沿上级链查找
+///
+/// 只保存对 `regions` 的引用而不复制区划，乡镇街道级数据（60 万行以上）建索引时不会使内存翻倍。
+pub fn build_region_map(regions: &[Region]) -> HashMap<u64, &Region> {
+    regions.iter().map(|r| (r.id, r)).collect()
+}
+
+/// 按地址编码挂载的额外字段
+#[derive(Debug, Clone, Default)]
+pub struct ExtraFields {
+    /// 额外字段名，按 CSV 列顺序排列
+    pub names: Vec<String>,
+    /// 地址编码 -> (字段名 -> 值)
+    pub values: HashMap<String, BTreeMap<String, String>>,
+}
+
+/// 从 CSV 加载额外字段
+///
+/// 第一列为地址编码（对应 ext_id），其余每一列为一个额外字段，列名即字段名。
+/// 空值的单元格不会被挂载。
+pub fn load_extra_fields<P: AsRef<Path>>(path: P) -> Result<ExtraFields> {
+    let mut rdr = open_csv(path)?;
+    let names: Vec<String> = rdr.headers()?.iter().skip(1).map(str::to_string).collect();
+
+    let mut values = HashMap::new();
+    for result in rdr.records() {
+        let record = result?;
+        let Some(code) = record.get(0) else {
+            continue;
+        };
+        let fields: BTreeMap<String, String> = names
+            .iter()
+            .zip(record.iter().skip(1))
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(name, value)| (name.clone(), value.to_string()))
+            .collect();
+        values.insert(code.to_string(), fields);
+    }
+
+    Ok(ExtraFields { names, values })
+}
+
+/// 区划变更表中的一行：已撤销（合并、更名）区划的旧编码及其继任区划编码
+#[derive(Debug, Deserialize)]
+struct RedirectRow {
+    old_code: String,
+    new_code: String,
+}
+
+/// 从 CSV 加载区划变更表（列：old_code,new_code），返回旧编码 -> 继任编码
+pub fn load_redirects<P: AsRef<Path>>(path: P) -> Result<HashMap<String, String>> {
+    let mut rdr = open_csv(path)?;
+    let mut redirects = HashMap::new();
+    for result in rdr.deserialize() {
+        let row: RedirectRow = result?;
+        redirects.insert(row.old_code, row.new_code);
+    }
+    Ok(redirects)
+}
+
+/// 同义词表中的一行：词条（地标、俗称等）及其对应的区划名称
+#[derive(Debug, Deserialize)]
+struct SynonymRow {
+    term: String,
+    expansion: String,
+}
+
+/// 从 CSV 加载同义词表（列：term,expansion），返回词条 -> 区划名称
+pub fn load_synonyms<P: AsRef<Path>>(path: P) -> Result<HashMap<String, String>> {
+    let mut rdr = open_csv(path)?;
+    let mut synonyms = HashMap::new();
+    for result in rdr.deserialize() {
+        let row: SynonymRow = result?;
+        synonyms.insert(row.term, row.expansion);
+    }
+    Ok(synonyms)
+}
+
+/// 提示词表中的一行：关键词（道路、地标、公司名等）及其所在的区划名称
+#[derive(Debug, Deserialize)]
+struct HintRow {
+    keyword: String,
+    region: String,
+}
+
+/// 从 CSV 加载提示词表（列：keyword,region），返回关键词 -> 区划名称
+pub fn load_hints<P: AsRef<Path>>(path: P) -> Result<HashMap<String, String>> {
+    let mut rdr = open_csv(path)?;
+    let mut hints = HashMap::new();
+    for result in rdr.deserialize() {
+        let row: HintRow = result?;
+        hints.insert(row.keyword, row.region);
+    }
+    Ok(hints)
+}
+
+/// 标注查询表中的一行：查询文本及其应当解析到的区划编码
+#[derive(Debug, Deserialize)]
+struct LabeledRow {
+    query: String,
+    expected_code: String,
+}
+
+/// 从 CSV 加载标注的查询（列：query,expected_code），返回（查询，期望的区划编码）列表
+pub fn load_labeled_queries<P: AsRef<Path>>(path: P) -> Result<Vec<(String, String)>> {
+    let mut rdr = open_csv(path)?;
+    let mut pairs = Vec::new();
+    for result in rdr.deserialize() {
+        let row: LabeledRow = result?;
+        pairs.push((row.query, row.expected_code));
+    }
+    Ok(pairs)
+}
+
+/// 索引策略：决定哪些区划节点生成独立的文档
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IndexingPolicy {
+    /// 每个层级的节点（省、市、区县、乡镇街道）都生成文档
+    #[default]
+    #[serde(alias = "all")]
+    AllLevels,
+    /// 只为叶子节点（没有下级区划的节点）生成文档
+    #[serde(alias = "leaf")]
+    LeafOnly,
+}
+
+impl FromStr for IndexingPolicy {
+    type Err = Text2LocationError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "all" | "all-levels" => Ok(Self::AllLevels),
+            "leaf" | "leaf-only" => Ok(Self::LeafOnly),
+            other => Err(Text2LocationError::InvalidInput(format!(
+                "未知的索引策略: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// 按索引策略筛选需要生成文档的区划
+pub fn select_regions(regions: &[Region], policy: IndexingPolicy) -> Vec<&Region> {
+    match policy {
+        IndexingPolicy::AllLevels => regions.iter().collect(),
+        IndexingPolicy::LeafOnly => {
+            let parents: HashSet<u64> = regions.iter().map(|r| r.pid).collect();
+            regions
+                .iter()
+                .filter(|r| !parents.contains(&r.id))
+                .collect()
+        }
+    }
 }
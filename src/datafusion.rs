@@ -0,0 +1,85 @@
+use crate::address_index::AddressIndex;
+use crate::arrow::{resolve_arrow, result_fields};
+use ::datafusion::arrow::array::{AsArray, StructArray};
+use ::datafusion::arrow::compute::cast;
+use ::datafusion::arrow::datatypes::DataType;
+use ::datafusion::error::{DataFusionError, Result};
+use ::datafusion::logical_expr::{
+    ColumnarValue, ScalarFunctionArgs, ScalarUDF, ScalarUDFImpl, Signature, Volatility,
+};
+use ::datafusion::prelude::SessionContext;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// SQL 中使用的函数名
+pub const UDF_NAME: &str = "text2location";
+
+/// 创建 `text2location(addr)` 标量函数
+///
+/// 参数为字符串列，返回与 `resolve_arrow` 相同字段的结构体：
+/// province、city、district、county、address_code 和 score，
+/// 未找到匹配时各字段均为 null。
+pub fn text2location_udf(index: Arc<AddressIndex>) -> ScalarUDF {
+    ScalarUDF::from(Text2LocationUdf {
+        index,
+        signature: Signature::string(1, Volatility::Immutable),
+    })
+}
+
+/// 在会话中注册 `text2location(addr)`，之后即可在 SQL 中使用：
+/// `SELECT text2location(address)['city'] FROM t`
+pub fn register(ctx: &SessionContext, index: Arc<AddressIndex>) {
+    ctx.register_udf(text2location_udf(index));
+}
+
+struct Text2LocationUdf {
+    index: Arc<AddressIndex>,
+    signature: Signature,
+}
+
+impl fmt::Debug for Text2LocationUdf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Text2LocationUdf")
+            .field("signature", &self.signature)
+            .finish_non_exhaustive()
+    }
+}
+
+// 同一个索引上的函数才视为相同
+impl PartialEq for Text2LocationUdf {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.index, &other.index)
+    }
+}
+
+impl Eq for Text2LocationUdf {}
+
+impl Hash for Text2LocationUdf {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.index).hash(state);
+    }
+}
+
+impl ScalarUDFImpl for Text2LocationUdf {
+    fn name(&self) -> &str {
+        UDF_NAME
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Struct(result_fields()))
+    }
+
+    fn invoke_with_args(&self, args: ScalarFunctionArgs) -> Result<ColumnarValue> {
+        let addresses = args.args[0].to_array(args.number_rows)?;
+        // Utf8View / LargeUtf8 统一转换为 Utf8
+        let addresses = cast(&addresses, &DataType::Utf8)?;
+        let batch = resolve_arrow(&self.index, addresses.as_string::<i32>())
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        Ok(ColumnarValue::Array(Arc::new(StructArray::from(batch))))
+    }
+}
@@ -0,0 +1,65 @@
+use crate::address_index::{AddressIndex, AddressQuery, AddressResult};
+use crate::error::{Result, Text2LocationError};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// 多个相互独立的地址索引，例如不同年份的行政区划快照
+///
+/// 查询时按 `AddressQuery::dataset` 选择索引，未指定时使用默认索引，
+/// 便于按记录当时有效的区划编码重新处理历史数据。
+#[derive(Clone)]
+pub struct Datasets {
+    default: Arc<AddressIndex>,
+    named: BTreeMap<String, Arc<AddressIndex>>,
+}
+
+impl Datasets {
+    /// 只包含默认索引
+    pub fn new(default: Arc<AddressIndex>) -> Self {
+        Self {
+            default,
+            named: BTreeMap::new(),
+        }
+    }
+
+    /// 添加命名数据集，同名时替换
+    pub fn insert(&mut self, name: impl Into<String>, index: Arc<AddressIndex>) {
+        self.named.insert(name.into(), index);
+    }
+
+    /// 已添加的数据集名称
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.named.keys().map(String::as_str)
+    }
+
+    /// 默认索引
+    pub fn default_index(&self) -> &Arc<AddressIndex> {
+        &self.default
+    }
+
+    /// 按名称获取索引，None 表示默认索引
+    pub fn get(&self, name: Option<&str>) -> Result<&AddressIndex> {
+        match name {
+            None => Ok(&self.default),
+            Some(name) => self
+                .named
+                .get(name)
+                .map(|index| index.as_ref())
+                .ok_or_else(|| Text2LocationError::InvalidInput(format!("未知的数据集: {}", name))),
+        }
+    }
+
+    /// 依次预热所有数据集的索引，见 `AddressIndex::warmup`
+    pub fn warmup(&self) -> Result<()> {
+        self.default.warmup()?;
+        for index in self.named.values() {
+            index.warmup()?;
+        }
+        Ok(())
+    }
+
+    /// 在查询指定的数据集上执行结构化查询
+    pub fn search_query(&self, query: &AddressQuery) -> Result<Vec<AddressResult>> {
+        self.get(query.dataset.as_deref())?.search_query(query)
+    }
+}
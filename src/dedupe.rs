@@ -0,0 +1,108 @@
+use crate::address_index::{AddressIndex, AddressResult};
+use crate::clean::strip_noise;
+use crate::error::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 一行地址的标准化结果及其所属的簇
+#[derive(Debug, Clone, Serialize)]
+pub struct DedupeRow {
+    /// 簇编号，从 1 开始按首次出现的顺序分配，指向同一地址的行编号相同
+    pub cluster: usize,
+    /// 地址的最佳解析结果
+    pub result: Option<AddressResult>,
+    /// 去掉区划名称后规范化的详细地址，如“宁中路12号”
+    pub detail: String,
+}
+
+impl DedupeRow {
+    /// 标准地址：区划全称加详细地址，未解析时只有详细地址
+    pub fn standard_address(&self) -> String {
+        match &self.result {
+            Some(result) => format!("{}{}", result.full_address, self.detail),
+            None => self.detail.clone(),
+        }
+    }
+}
+
+/// 标准化一批地址并按“区划编码 + 详细地址”分簇，返回与输入一一对应的结果
+///
+/// 各行先用 `AddressIndex::resolve` 解析，再从原文开头去掉属于解析结果层级链的区划名称（全称或简称），
+/// 剩余部分去掉噪声、标点和空白，全角字母数字转为半角并转为小写，作为详细地址。
+/// 因此“广东省梅州市兴宁市宁中路１２号”和“梅州兴宁 宁中路12号”归入同一簇。
+/// 未解析的地址只与规范化后完全相同的地址归为一簇。
+pub fn dedupe<S: AsRef<str> + Sync>(
+    index: &AddressIndex,
+    addresses: &[S],
+) -> Result<Vec<DedupeRow>> {
+    let rows = addresses
+        .par_iter()
+        .map(|address| {
+            let address = address.as_ref();
+            let result = index.resolve(address)?;
+            let detail = detail(index, address, result.as_ref());
+            Ok((result, detail))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut clusters: HashMap<(Option<String>, String), usize> = HashMap::new();
+    Ok(rows
+        .into_iter()
+        .map(|(result, detail)| {
+            let key = (result.as_ref().map(|r| r.address_code.clone()), detail);
+            let next = clusters.len() + 1;
+            let cluster = *clusters.entry(key.clone()).or_insert(next);
+            DedupeRow {
+                cluster,
+                result,
+                detail: key.1,
+            }
+        })
+        .collect())
+}
+
+/// 去掉开头属于解析结果层级链的区划名称后，规范化剩余的详细地址
+fn detail(index: &AddressIndex, address: &str, result: Option<&AddressResult>) -> String {
+    let address = strip_noise(address);
+    let mut last = 0;
+    if let Some(result) = result {
+        let chain = [
+            &result.province,
+            &result.city,
+            &result.district,
+            &result.county,
+        ];
+        // 区划名称只出现在开头，每一级最多去掉一次，遇到其他文字就停止，
+        // 避免误删详细地址中与区划同名的部分（如宁中镇的“宁中路”）
+        let mut removed = [false; 4];
+        for m in index.scan(&address) {
+            if address[last..m.start].chars().any(char::is_alphanumeric) {
+                break;
+            }
+            // 直辖市的省、市两级同名，依次对应尚未去掉的层级
+            let Some(level) = (0..chain.len())
+                .find(|&i| !removed[i] && !chain[i].is_empty() && m.names.contains(chain[i]))
+            else {
+                break;
+            };
+            removed[level] = true;
+            last = m.end;
+        }
+    }
+    address[last..]
+        .chars()
+        .map(to_halfwidth)
+        .filter(|c| c.is_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// 全角字符（如“１２号Ａ座”中的数字和字母）转为对应的半角字符
+fn to_halfwidth(c: char) -> char {
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        '\u{3000}' => ' ',
+        _ => c,
+    }
+}
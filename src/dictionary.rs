@@ -0,0 +1,404 @@
+use crate::address::{complete, AddressRecord, AdminLevel};
+use crate::error::{Result, Text2LocationError};
+use crate::memory::HeapSize;
+use aho_corasick::{AhoCorasick, MatchKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 文本中的一处区划名称
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NameMatch {
+    /// 在原文中的起始字节位置
+    pub start: usize,
+    /// 在原文中的结束字节位置（不含）
+    pub end: usize,
+    /// 原文中匹配到的名称（全称或简称）
+    pub text: String,
+    /// 该名称可能指代的区划全称，简称可能对应多个全称（如“朝阳”）
+    pub names: Vec<String>,
+}
+
+/// 被多个区划共用的本级全称，如北京和长春都有的“朝阳区”，由 `AddressIndex::ambiguous_names` 返回
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AmbiguousName {
+    pub name: String,
+    /// 使用该名称的区划，按编码排序
+    pub regions: Vec<NamedRegion>,
+}
+
+/// 使用某个名称的一个区划
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NamedRegion {
+    pub address_code: String,
+    pub level: AdminLevel,
+    /// 上级区划的全称链，直辖市重复的一级只保留一次，如 `["吉林省", "长春市"]`
+    pub parents: Vec<String>,
+}
+
+/// 区划名称词典：用 Aho-Corasick 自动机在文本中查找区划全称及简称
+///
+/// 用于精确匹配优先的解析流程：输入中的区划名称能唯一确定一个区划时，
+/// 直接在词典中得到结果，不需要经过分词和打分。
+#[derive(Debug, Default)]
+pub(crate) struct NameDictionary {
+    /// 全称和简称构成的自动机，为 None 表示词典为空
+    automaton: Option<AhoCorasick>,
+    /// 模式编号 -> 该模式可能指代的全称编号
+    patterns: Vec<Vec<usize>>,
+    /// 全称编号 -> 区划全称
+    names: Vec<String>,
+    /// 已收录的区划
+    entries: Vec<Entry>,
+    /// 全称编号 -> 本级名称为该全称的区划
+    by_name: Vec<Vec<usize>>,
+}
+
+/// 词典中的一个区划，随索引文件一起导出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Entry {
+    /// 省市区县层级链，本级以下为空
+    chain: [String; 4],
+    /// 层级（deep）
+    deep: usize,
+    /// 各级简称，与 `chain` 一一对应
+    short_names: [String; 4],
+    /// 本级区划的其他名称
+    aliases: Vec<String>,
+    address_code: String,
+}
+
+impl Entry {
+    /// 检查从索引文件读入的区划：层级须为 0-3，否则按层级访问 `chain` 时会越界
+    pub(crate) fn validate(&self) -> Result<()> {
+        if u8::try_from(self.deep)
+            .ok()
+            .and_then(AdminLevel::from_deep)
+            .is_none()
+        {
+            return Err(Text2LocationError::SchemaMismatch(format!(
+                "词典中区划 {} 的层级 {} 无效，应为 0-3",
+                self.address_code, self.deep
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl HeapSize for Entry {
+    fn heap_size(&self) -> usize {
+        self.chain.heap_size()
+            + self.short_names.heap_size()
+            + self.aliases.heap_size()
+            + self.address_code.heap_size()
+    }
+}
+
+impl HeapSize for NameDictionary {
+    fn heap_size(&self) -> usize {
+        self.automaton.as_ref().map_or(0, AhoCorasick::memory_usage)
+            + self.patterns.heap_size()
+            + self.names.heap_size()
+            + self.entries.heap_size()
+            + self.by_name.heap_size()
+    }
+}
+
+impl NameDictionary {
+    /// 收录一批地址记录并重建自动机
+    pub(crate) fn extend(&mut self, records: &[AddressRecord]) {
+        self.entries.extend(records.iter().map(|record| Entry {
+            chain: [
+                record.province.clone(),
+                record.city.clone(),
+                record.district.clone(),
+                record.county.clone(),
+            ],
+            deep: usize::from(record.level.deep()),
+            short_names: [
+                record.short_names.province.clone(),
+                record.short_names.city.clone(),
+                record.short_names.district.clone(),
+                record.short_names.county.clone(),
+            ],
+            aliases: record.aliases.clone(),
+            address_code: record.address_code.clone(),
+        }));
+        self.rebuild();
+    }
+
+    /// 清空后收录新的地址记录
+    pub(crate) fn replace(&mut self, records: &[AddressRecord]) {
+        self.entries.clear();
+        self.extend(records);
+    }
+
+    /// 已收录的区划，用于导出索引文件
+    pub(crate) fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// 由导出的区划重建词典
+    pub(crate) fn from_entries(entries: Vec<Entry>) -> Self {
+        let mut dictionary = Self {
+            entries,
+            ..Self::default()
+        };
+        dictionary.rebuild();
+        dictionary
+    }
+
+    fn rebuild(&mut self) {
+        let mut name_ids: HashMap<&str, usize> = HashMap::new();
+        let mut names = Vec::new();
+        let mut by_name: Vec<Vec<usize>> = Vec::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            for (level, name) in entry.chain.iter().enumerate() {
+                if name.is_empty() {
+                    continue;
+                }
+                let id = *name_ids.entry(name).or_insert_with(|| {
+                    names.push(name.clone());
+                    by_name.push(Vec::new());
+                    names.len() - 1
+                });
+                if level == entry.deep {
+                    by_name[id].push(i);
+                }
+            }
+        }
+
+        // 全称各自是一个模式，简称指向所有使用该简称的全称
+        let mut pattern_ids: HashMap<&str, usize> = HashMap::new();
+        let mut pattern_texts: Vec<&str> = Vec::new();
+        let mut patterns: Vec<Vec<usize>> = Vec::new();
+        for (id, name) in names.iter().enumerate() {
+            pattern_ids.insert(name, pattern_texts.len());
+            pattern_texts.push(name);
+            patterns.push(vec![id]);
+        }
+        for entry in &self.entries {
+            // 各级简称指向同级的全称，其他名称指向本级全称
+            let short_names = entry.chain.iter().zip(&entry.short_names);
+            let aliases = entry
+                .aliases
+                .iter()
+                .map(|alias| (&entry.chain[entry.deep], alias));
+            for (name, alias) in short_names.chain(aliases) {
+                if name.is_empty() || alias.is_empty() || alias == name {
+                    continue;
+                }
+                let id = name_ids[name.as_str()];
+                let pattern = *pattern_ids.entry(alias).or_insert_with(|| {
+                    pattern_texts.push(alias);
+                    patterns.push(Vec::new());
+                    patterns.len() - 1
+                });
+                if !patterns[pattern].contains(&id) {
+                    patterns[pattern].push(id);
+                }
+            }
+        }
+
+        self.automaton = if pattern_texts.is_empty() {
+            None
+        } else {
+            AhoCorasick::builder()
+                .match_kind(MatchKind::LeftmostLongest)
+                .build(&pattern_texts)
+                .ok()
+        };
+        self.patterns = patterns;
+        self.names = names;
+        self.by_name = by_name;
+    }
+
+    /// 按最长匹配找出文本中所有不重叠的区划名称
+    pub(crate) fn scan(&self, text: &str) -> Vec<NameMatch> {
+        let Some(automaton) = &self.automaton else {
+            return Vec::new();
+        };
+        automaton
+            .find_iter(text)
+            .map(|m| NameMatch {
+                start: m.start(),
+                end: m.end(),
+                text: text[m.range()].to_string(),
+                names: self.patterns[m.pattern().as_usize()]
+                    .iter()
+                    .map(|&id| self.names[id].clone())
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// 文本中是否有足以表明地点的区划名称：任一层级的全称，或省、市、区县级的简称和别名
+    ///
+    /// 乡镇街道的简称（如“人民街道”的“人民”、“中山路街道”的“中山路”）多是常用词和路名，
+    /// 单独出现时不算。
+    pub(crate) fn has_location_signal(&self, text: &str) -> bool {
+        let Some(automaton) = &self.automaton else {
+            return false;
+        };
+        let county = usize::from(AdminLevel::County.deep());
+        automaton.find_iter(text).any(|m| {
+            self.patterns[m.pattern().as_usize()].iter().any(|&id| {
+                self.names[id] == text[m.range()]
+                    || self.by_name[id]
+                        .iter()
+                        .any(|&entry| self.entries[entry].deep < county)
+            })
+        })
+    }
+
+    /// 精确匹配：返回文本中的区划名称能完整且唯一确定的区划的编码
+    ///
+    /// 候选区划的本级名称必须出现在文本中，层级链与其余名称都不矛盾，
+    /// 且层级链上的每一级（直辖市重复的一级算一级）都能在文本中找到对应的名称，
+    /// 多个候选时取层级最深的。没有命中名称、名称之间互相矛盾、层级链不完整
+    /// （如只写了“朝阳区”）或最深的候选不唯一时返回 None，由调用方回退到模糊搜索。
+    pub(crate) fn resolve(&self, text: &str) -> Option<&str> {
+        let automaton = self.automaton.as_ref()?;
+        let mut matched: Vec<usize> = automaton
+            .find_iter(text)
+            .map(|m| m.pattern().as_usize())
+            .collect();
+        matched.sort_unstable();
+        matched.dedup();
+        let mentions = |name: &str| {
+            matched
+                .iter()
+                .filter(|&&p| self.patterns[p].iter().any(|&n| self.names[n] == name))
+                .count()
+        };
+
+        let mut best: Option<&Entry> = None;
+        let mut ambiguous = false;
+        for &pattern in &matched {
+            for &id in &self.patterns[pattern] {
+                for &i in &self.by_name[id] {
+                    let entry = &self.entries[i];
+                    let consistent = matched.iter().all(|&other| {
+                        self.patterns[other]
+                            .iter()
+                            .any(|&name| entry.chain.contains(&self.names[name]))
+                    });
+                    if !consistent
+                        || !complete(&entry.chain[..=entry.deep], matched.len(), mentions)
+                    {
+                        continue;
+                    }
+                    match best {
+                        Some(current) if current.deep == entry.deep => {
+                            ambiguous |= current.address_code != entry.address_code;
+                        }
+                        Some(current) if current.deep > entry.deep => {}
+                        _ => {
+                            best = Some(entry);
+                            ambiguous = false;
+                        }
+                    }
+                }
+            }
+        }
+        if ambiguous {
+            return None;
+        }
+        best.map(|entry| entry.address_code.as_str())
+    }
+
+    /// 文本中互相矛盾的区划全称所在的层级，没有矛盾时为空
+    ///
+    /// 两个全称不同时出现在任何一个区划的层级链中（如“广东省朝阳区”）时，两者所在的层级都算矛盾。
+    /// 简称和其他名称容易与道路等详细地址重合（如“中山路”中的“中山”），不参与判断。
+    pub(crate) fn conflicts(&self, text: &str) -> Vec<AdminLevel> {
+        let Some(automaton) = &self.automaton else {
+            return Vec::new();
+        };
+        // 前 names.len() 个模式依次是各个全称，模式编号即全称编号
+        let mut matched: Vec<usize> = automaton
+            .find_iter(text)
+            .map(|m| m.pattern().as_usize())
+            .filter(|&pattern| pattern < self.names.len())
+            .collect();
+        matched.sort_unstable();
+        matched.dedup();
+
+        let regions = |name: usize| self.by_name[name].iter().map(|&i| &self.entries[i]);
+        let compatible = |a: usize, b: usize| {
+            regions(a).any(|entry| entry.chain.contains(&self.names[b]))
+                || regions(b).any(|entry| entry.chain.contains(&self.names[a]))
+        };
+        let level = |name: usize| regions(name).map(|entry| entry.deep).min();
+        let mut levels = Vec::new();
+        for (i, &a) in matched.iter().enumerate() {
+            for &b in &matched[i + 1..] {
+                if !compatible(a, b) {
+                    levels.extend(level(a));
+                    levels.extend(level(b));
+                }
+            }
+        }
+        levels.sort_unstable();
+        levels.dedup();
+        levels
+            .into_iter()
+            .filter_map(|deep| AdminLevel::from_deep(u8::try_from(deep).ok()?))
+            .collect()
+    }
+
+    /// 被多个区划共用的本级全称，按共用的区划数降序、名称升序排列，指定层级时只统计该层级的区划
+    ///
+    /// 同一分支上的同名区划（如直辖市“北京市”的省、市两级）不算歧义；
+    /// 只写这些名称时需要上级区划才能确定是哪一个。
+    pub(crate) fn ambiguous_names(&self, level: Option<AdminLevel>) -> Vec<AmbiguousName> {
+        let mut ambiguous: Vec<AmbiguousName> = self
+            .names
+            .iter()
+            .zip(&self.by_name)
+            .filter_map(|(name, entries)| {
+                let mut regions: Vec<(Vec<&String>, &Entry)> = entries
+                    .iter()
+                    .map(|&i| &self.entries[i])
+                    .filter(|entry| {
+                        level.is_none_or(|level| usize::from(level.deep()) == entry.deep)
+                    })
+                    .map(|entry| {
+                        let mut chain: Vec<&String> = entry.chain[..=entry.deep].iter().collect();
+                        chain.dedup();
+                        (chain, entry)
+                    })
+                    .collect();
+                let mut branches: Vec<&[&String]> =
+                    regions.iter().map(|(chain, _)| chain.as_slice()).collect();
+                branches.sort_unstable();
+                branches.dedup();
+                if branches.len() < 2 {
+                    return None;
+                }
+                regions.sort_by(|(_, a), (_, b)| a.address_code.cmp(&b.address_code));
+                let regions = regions
+                    .into_iter()
+                    .filter_map(|(mut chain, entry)| {
+                        chain.pop();
+                        Some(NamedRegion {
+                            address_code: entry.address_code.clone(),
+                            level: AdminLevel::from_deep(u8::try_from(entry.deep).ok()?)?,
+                            parents: chain.into_iter().cloned().collect(),
+                        })
+                    })
+                    .collect();
+                Some(AmbiguousName {
+                    name: name.clone(),
+                    regions,
+                })
+            })
+            .collect();
+        ambiguous.sort_by(|a, b| {
+            b.regions
+                .len()
+                .cmp(&a.regions.len())
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        ambiguous
+    }
+}
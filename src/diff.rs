@@ -0,0 +1,104 @@
+use crate::csv_loader::Region;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// 区划变更类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// 新数据中新增的区划
+    Added,
+    /// 新数据中已不存在的区划
+    Removed,
+    /// 名称变更
+    Renamed,
+    /// 上级区划变更
+    Reparented,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Added => "新增",
+            Self::Removed => "删除",
+            Self::Renamed => "更名",
+            Self::Reparented => "变更上级",
+        })
+    }
+}
+
+/// 一条区划变更，按区划 id 对比新旧两份数据得到
+///
+/// 同一区划既更名又变更上级时会生成两条记录。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RegionChange {
+    pub change: ChangeKind,
+    pub id: u64,
+    /// 区划编码（新增时取新数据，其余取旧数据）
+    pub code: String,
+    pub old_name: Option<String>,
+    pub new_name: Option<String>,
+    pub old_parent: Option<u64>,
+    pub new_parent: Option<u64>,
+}
+
+/// 对比两份区划数据，按区划 id、变更类型排序返回变更列表
+pub fn diff_regions(old: &[Region], new: &[Region]) -> Vec<RegionChange> {
+    let old: BTreeMap<u64, &Region> = old.iter().map(|r| (r.id, r)).collect();
+    let new: BTreeMap<u64, &Region> = new.iter().map(|r| (r.id, r)).collect();
+
+    let mut changes = Vec::new();
+    for (id, before) in &old {
+        let Some(after) = new.get(id) else {
+            changes.push(RegionChange {
+                change: ChangeKind::Removed,
+                id: *id,
+                code: before.ext_id.clone(),
+                old_name: Some(before.ext_name.clone()),
+                new_name: None,
+                old_parent: Some(before.pid),
+                new_parent: None,
+            });
+            continue;
+        };
+        if before.ext_name != after.ext_name {
+            changes.push(RegionChange {
+                change: ChangeKind::Renamed,
+                id: *id,
+                code: before.ext_id.clone(),
+                old_name: Some(before.ext_name.clone()),
+                new_name: Some(after.ext_name.clone()),
+                old_parent: None,
+                new_parent: None,
+            });
+        }
+        if before.pid != after.pid {
+            changes.push(RegionChange {
+                change: ChangeKind::Reparented,
+                id: *id,
+                code: before.ext_id.clone(),
+                old_name: None,
+                new_name: None,
+                old_parent: Some(before.pid),
+                new_parent: Some(after.pid),
+            });
+        }
+    }
+    for (id, after) in &new {
+        if !old.contains_key(id) {
+            changes.push(RegionChange {
+                change: ChangeKind::Added,
+                id: *id,
+                code: after.ext_id.clone(),
+                old_name: None,
+                new_name: Some(after.ext_name.clone()),
+                old_parent: None,
+                new_parent: Some(after.pid),
+            });
+        }
+    }
+
+    changes.sort_by_key(|c| (c.id, c.change));
+    changes
+}
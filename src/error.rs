@@ -0,0 +1,60 @@
+use std::time::Duration;
+use thiserror::Error;
+
+/// 地址解析库的错误类型
+#[derive(Debug, Error)]
+pub enum Text2LocationError {
+    /// 读取或解析数据文件失败
+    #[error("数据加载失败: {0}")]
+    DataLoad(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    /// 创建、写入或读取索引失败
+    #[cfg(feature = "tantivy")]
+    #[error("索引构建失败: {0}")]
+    IndexBuild(#[from] tantivy::TantivyError),
+
+    /// 查询字符串或查询条件无法解析
+    #[cfg(feature = "tantivy")]
+    #[error("查询解析失败: {0}")]
+    QueryParse(#[from] tantivy::query::QueryParserError),
+
+    /// 调用方提供的参数不合法
+    #[error("参数无效: {0}")]
+    InvalidInput(String),
+
+    /// 索引文件无法读写，或格式、版本不兼容
+    #[error("索引文件无效: {0}")]
+    Archive(String),
+
+    /// 索引的 schema 版本或字段与当前版本（含额外字段的注册）不一致，需要重新构建索引
+    #[error("索引与当前版本不兼容: {0}")]
+    SchemaMismatch(String),
+
+    /// 没有找到匹配的地址
+    #[error("未找到匹配的地址: {0}")]
+    NotFound(String),
+
+    /// 查询中没有任何已知的区划名称，见 `SearchOptions::require_location_signal`
+    #[error("查询中没有可识别的地名: {0}")]
+    NoLocationSignal(String),
+
+    /// 查询超过 `SearchOptions::timeout` 设置的时间上限
+    #[error("查询超时（上限 {0:?}）")]
+    Timeout(Duration),
+}
+
+impl Text2LocationError {
+    /// 把读取数据时的任意错误（IO、表格、JSON 等）包装为数据加载错误
+    pub fn data_load(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self::DataLoad(err.into())
+    }
+}
+
+impl From<csv::Error> for Text2LocationError {
+    fn from(err: csv::Error) -> Self {
+        Self::data_load(err)
+    }
+}
+
+/// 地址解析库的 Result 类型
+pub type Result<T> = std::result::Result<T, Text2LocationError>;
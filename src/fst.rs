@@ -0,0 +1,189 @@
+use crate::address::{complete, haversine_km, AddressRecord};
+use crate::clean::strip_noise;
+use crate::error::{Result, Text2LocationError};
+use ::fst::automaton::{Automaton, Str};
+use ::fst::raw::Output;
+use ::fst::{IntoStreamer, Map, MapBuilder, Streamer};
+use std::collections::BTreeMap;
+
+/// 基于 FST 的区划名称查找表，只支持精确匹配和前缀匹配
+///
+/// 不建立倒排索引、不分词也不打分，内存占用远小于 `AddressIndex`，
+/// 适合只需要把规范地名对应到区划、或做地名输入补全的嵌入式场景；
+/// 不依赖 tantivy，可在关闭默认功能时单独启用。
+/// 名称包括每个区划的本级全称、简称和 `AddressRecord::aliases`。
+pub struct RegionFst {
+    /// 名称 -> `postings` 下标
+    map: Map<Vec<u8>>,
+    /// 使用该名称的区划在 `records` 中的下标
+    postings: Vec<Vec<usize>>,
+    records: Vec<AddressRecord>,
+}
+
+impl RegionFst {
+    /// 由地址记录构建查找表
+    pub fn new(records: Vec<AddressRecord>) -> Result<Self> {
+        let mut names: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (i, record) in records.iter().enumerate() {
+            let short_name = record.short_name();
+            let aliases = record.aliases.iter().map(String::as_str);
+            for name in [record.name(), short_name].into_iter().chain(aliases) {
+                if name.is_empty() {
+                    continue;
+                }
+                let ids = names.entry(name).or_default();
+                if ids.last() != Some(&i) {
+                    ids.push(i);
+                }
+            }
+        }
+
+        // FST 要求按字节序插入，BTreeMap 的遍历顺序正好满足
+        let mut builder = MapBuilder::memory();
+        let mut postings = Vec::with_capacity(names.len());
+        for (name, ids) in names {
+            builder
+                .insert(name, postings.len() as u64)
+                .map_err(|e| Text2LocationError::InvalidInput(format!("构建 FST 失败: {}", e)))?;
+            postings.push(ids);
+        }
+        let map = builder.into_map();
+
+        Ok(Self {
+            map,
+            postings,
+            records,
+        })
+    }
+
+    /// 名称（全称或简称）完全一致的区划
+    pub fn exact(&self, name: &str) -> Vec<&AddressRecord> {
+        self.map
+            .get(name)
+            .map(|id| self.records_of(id))
+            .unwrap_or_default()
+    }
+
+    /// 名称以给定前缀开头的区划，按名称的字节序排列，最多返回 `limit` 条
+    pub fn prefix(&self, prefix: &str, limit: usize) -> Vec<&AddressRecord> {
+        let mut results = Vec::new();
+        let mut stream = self
+            .map
+            .search(Str::new(prefix).starts_with())
+            .into_stream();
+        while let Some((_, id)) = stream.next() {
+            for record in self.records_of(id) {
+                if results.len() >= limit {
+                    return results;
+                }
+                // 简称和全称都以前缀开头时，同一区划只返回一次
+                if !results.iter().any(|r| std::ptr::eq(*r, record)) {
+                    results.push(record);
+                }
+            }
+        }
+        results
+    }
+
+    /// 从文本中按最长匹配依次切出区划名称，返回这些名称能完整且唯一确定的区划
+    ///
+    /// 判定规则与 `AddressIndex::resolve_exact` 相同：层级链与所有名称一致、
+    /// 每一级都在文本中出现，多个候选时取层级最深的；不唯一时返回 None。
+    pub fn resolve(&self, text: &str) -> Option<&AddressRecord> {
+        let text = strip_noise(text);
+        let mut segments: Vec<u64> = Vec::new();
+        let mut rest = text.as_str();
+        while let Some(c) = rest.chars().next() {
+            match self.longest_prefix(rest) {
+                Some((len, id)) => {
+                    if !segments.contains(&id) {
+                        segments.push(id);
+                    }
+                    rest = &rest[len..];
+                }
+                None => rest = &rest[c.len_utf8()..],
+            }
+        }
+        let mentions = |name: &str| {
+            segments
+                .iter()
+                .filter(|&&id| self.records_of(id).iter().any(|r| r.name() == name))
+                .count()
+        };
+
+        let mut best: Option<&AddressRecord> = None;
+        let mut ambiguous = false;
+        for &id in &segments {
+            for record in self.records_of(id) {
+                let chain = [
+                    record.province.clone(),
+                    record.city.clone(),
+                    record.district.clone(),
+                    record.county.clone(),
+                ];
+                let deep = usize::from(record.level.deep());
+                let consistent = segments.iter().all(|&other| {
+                    self.records_of(other)
+                        .iter()
+                        .any(|r| chain.iter().any(|name| name == r.name()))
+                });
+                if !consistent || !complete(&chain[..=deep], segments.len(), mentions) {
+                    continue;
+                }
+                match best {
+                    Some(current) if current.level == record.level => {
+                        ambiguous |= current.address_code != record.address_code;
+                    }
+                    Some(current) if current.level > record.level => {}
+                    _ => {
+                        best = Some(record);
+                        ambiguous = false;
+                    }
+                }
+            }
+        }
+        if ambiguous {
+            return None;
+        }
+        best
+    }
+
+    /// 中心点距给定位置（纬度、经度）最近的区划，没有任何区划带坐标时返回 None
+    pub fn nearest(&self, lat: f64, lng: f64) -> Option<&AddressRecord> {
+        self.records
+            .iter()
+            .filter_map(|record| {
+                let distance = haversine_km((lat, lng), record.location?);
+                Some((distance, record))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, record)| record)
+    }
+
+    /// 文本开头最长的已收录名称，返回其字节长度和 `postings` 下标
+    fn longest_prefix(&self, text: &str) -> Option<(usize, u64)> {
+        let fst = self.map.as_fst();
+        let mut node = fst.root();
+        let mut output = Output::zero();
+        let mut longest = None;
+        for (i, &byte) in text.as_bytes().iter().enumerate() {
+            let Some(t) = node.find_input(byte) else {
+                break;
+            };
+            let transition = node.transition(t);
+            output = output.cat(transition.out);
+            node = fst.node(transition.addr);
+            if node.is_final() {
+                longest = Some((i + 1, output.cat(node.final_output()).value()));
+            }
+        }
+        longest
+    }
+
+    fn records_of(&self, id: u64) -> Vec<&AddressRecord> {
+        self.postings[id as usize]
+            .iter()
+            .map(|&i| &self.records[i])
+            .collect()
+    }
+}
@@ -0,0 +1,59 @@
+use crate::address::AddressResult;
+#[cfg(feature = "tantivy")]
+use crate::address_index::AddressIndex;
+use crate::error::Result;
+#[cfg(feature = "fst")]
+use crate::fst::RegionFst;
+#[cfg(feature = "tantivy")]
+use rayon::prelude::*;
+
+/// 地址解析后端的统一接口
+///
+/// 由 `AddressIndex`（启用默认的 `tantivy` 功能时）和 `RegionFst`（启用 `fst` 功能时）实现，
+/// 应用可以面向该 trait 编写代码和测试，按场景替换后端。
+pub trait Geocoder {
+    /// 解析地址，返回最佳结果，没有结果时返回 None
+    fn resolve(&self, query: &str) -> Result<Option<AddressResult>>;
+
+    /// 批量解析，结果与输入一一对应：解析到区划、未找到（None）或出错
+    ///
+    /// 每条查询的错误只影响对应的结果，不会使整批失败。
+    fn resolve_batch(&self, queries: &[&str]) -> Vec<Result<Option<AddressResult>>> {
+        queries.iter().map(|query| self.resolve(query)).collect()
+    }
+
+    /// 逆地理编码：返回中心点距给定位置（纬度、经度）最近的区划
+    fn reverse(&self, lat: f64, lng: f64) -> Result<Option<AddressResult>>;
+}
+
+#[cfg(feature = "tantivy")]
+impl Geocoder for AddressIndex {
+    /// 精确匹配优先、模糊搜索兜底，见 `AddressIndex::resolve`
+    fn resolve(&self, query: &str) -> Result<Option<AddressResult>> {
+        AddressIndex::resolve(self, query)
+    }
+
+    /// 多线程并行解析
+    fn resolve_batch(&self, queries: &[&str]) -> Vec<Result<Option<AddressResult>>> {
+        queries
+            .par_iter()
+            .map(|query| AddressIndex::resolve(self, query))
+            .collect()
+    }
+
+    fn reverse(&self, lat: f64, lng: f64) -> Result<Option<AddressResult>> {
+        AddressIndex::reverse(self, lat, lng)
+    }
+}
+
+#[cfg(feature = "fst")]
+impl Geocoder for RegionFst {
+    /// 只做精确匹配，名称不能唯一确定区划时返回 None，见 `RegionFst::resolve`
+    fn resolve(&self, query: &str) -> Result<Option<AddressResult>> {
+        Ok(RegionFst::resolve(self, query).map(AddressResult::from))
+    }
+
+    fn reverse(&self, lat: f64, lng: f64) -> Result<Option<AddressResult>> {
+        Ok(self.nearest(lat, lng).map(AddressResult::from))
+    }
+}
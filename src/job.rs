@@ -0,0 +1,97 @@
+use crate::csv_loader::InputEncoding;
+use crate::error::{Result, Text2LocationError};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 批量任务的检查点，由 `batch --job` 定期保存，`batch --resume` 从中恢复
+///
+/// 记录任务的参数、已处理的查询数和此时输出、错误文件的长度。恢复时把这两个文件截断到该长度，
+/// 跳过已处理的查询继续执行，因此上次检查点之后写出的部分结果不会重复。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchJob {
+    /// 任务 ID，也是检查点文件名
+    pub id: String,
+    pub input: PathBuf,
+    pub output: PathBuf,
+    /// 输出格式，None 表示按输出文件扩展名推断
+    pub format: Option<String>,
+    /// 输入为表格时读取的工作表和列
+    pub sheet: Option<String>,
+    pub column: String,
+    pub dataset: Option<String>,
+    pub codes_only: bool,
+    /// 输出的结果列（`--columns`）
+    #[serde(default)]
+    pub columns: Option<String>,
+    /// 是否保留输入表格的各列（`--keep-input`）
+    #[serde(default)]
+    pub keep_input: bool,
+    /// 文本和 CSV 输入的编码（`--encoding`）
+    #[serde(default)]
+    pub encoding: InputEncoding,
+    /// 错误文件（`--errors`），None 表示输出文件旁的默认位置
+    #[serde(default)]
+    pub errors: Option<PathBuf>,
+    /// 是否跳过非地址的行（`--skip-non-address`）
+    #[serde(default)]
+    pub skip_non_address: bool,
+    /// 已处理的行数（按行读取时不含空行）
+    pub done: usize,
+    /// 保存检查点时输出文件的长度（字节）
+    pub output_len: u64,
+    /// 保存检查点时错误文件的长度（字节），还没有错误时为 0
+    #[serde(default)]
+    pub errors_len: u64,
+}
+
+impl BatchJob {
+    /// 任务的检查点文件路径，任务 ID 只能由字母、数字、`-` 和 `_` 组成
+    pub fn path(dir: &Path, id: &str) -> Result<PathBuf> {
+        let valid = !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(Text2LocationError::InvalidInput(format!(
+                "无效的任务 ID: {}（只能包含字母、数字、- 和 _）",
+                id
+            )));
+        }
+        Ok(dir.join(format!("{}.json", id)))
+    }
+
+    /// 读取检查点
+    pub fn load(dir: &Path, id: &str) -> Result<Self> {
+        let path = Self::path(dir, id)?;
+        if !path.exists() {
+            return Err(Text2LocationError::NotFound(format!("批量任务 {}", id)));
+        }
+        let content = fs::read_to_string(&path).map_err(Text2LocationError::data_load)?;
+        serde_json::from_str(&content).map_err(|e| {
+            Text2LocationError::InvalidInput(format!("检查点 {} 无效: {}", path.display(), e))
+        })
+    }
+
+    /// 保存检查点：先写临时文件再重命名，进程在写入时被终止也不会留下不完整的检查点
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir, &self.id)?;
+        fs::create_dir_all(dir).map_err(Text2LocationError::data_load)?;
+        let tmp = path.with_extension("json.tmp");
+        let content = serde_json::to_vec_pretty(self)
+            .map_err(|e| Text2LocationError::InvalidInput(format!("无法序列化检查点: {}", e)))?;
+        fs::write(&tmp, content).map_err(Text2LocationError::data_load)?;
+        fs::rename(&tmp, &path).map_err(Text2LocationError::data_load)
+    }
+
+    /// 任务完成后删除检查点
+    pub fn remove(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir, &self.id)?;
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(Text2LocationError::data_load(e))
+            }
+            _ => Ok(()),
+        }
+    }
+}
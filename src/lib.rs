@@ -0,0 +1,68 @@
+//! 中文地址解析：基于 tantivy + jieba 分词，将非标准的中文地址匹配到标准行政区划
+//!
+//! 索引相关的模块在默认的 `tantivy` 功能下编译；只需要 FST 名称查找的嵌入式场景可以用
+//! `--no-default-features --features fst` 去掉 tantivy 和 jieba 词典。
+
+pub mod address;
+#[cfg(feature = "tantivy")]
+pub mod address_index;
+#[cfg(feature = "tantivy")]
+pub mod analytics;
+#[cfg(feature = "tantivy")]
+pub mod archive;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "tantivy")]
+pub mod bench;
+pub mod clean;
+pub mod config;
+#[cfg(feature = "tantivy")]
+pub mod consignee;
+pub mod constants;
+pub mod csv_loader;
+#[cfg(feature = "datafusion")]
+pub mod datafusion;
+#[cfg(feature = "tantivy")]
+pub mod dataset;
+#[cfg(feature = "tantivy")]
+pub mod dedupe;
+#[cfg(feature = "tantivy")]
+pub mod dictionary;
+pub mod diff;
+pub mod error;
+#[cfg(feature = "fst")]
+pub mod fst;
+pub mod geocoder;
+pub mod job;
+#[cfg(feature = "tantivy")]
+pub mod llm;
+pub mod locale;
+#[cfg(feature = "tantivy")]
+pub mod memory;
+pub mod output;
+#[cfg(feature = "polars")]
+pub mod polars;
+#[cfg(feature = "tantivy")]
+pub mod prebuilt;
+#[cfg(feature = "tantivy")]
+pub mod query;
+#[cfg(feature = "tantivy")]
+pub mod query_log;
+#[cfg(feature = "tantivy")]
+pub mod resolver;
+#[cfg(feature = "tantivy")]
+pub mod server;
+#[cfg(feature = "tantivy")]
+pub mod synonyms;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+#[cfg(feature = "tantivy")]
+pub mod tuning;
+#[cfg(feature = "tantivy")]
+pub mod validation;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
+
+pub use error::{Result, Text2LocationError};
+#[cfg(feature = "tantivy")]
+pub use prebuilt::resolve;
@@ -0,0 +1,65 @@
+use crate::address_index::AddressQuery;
+use crate::error::{Result, Text2LocationError};
+use serde_json::{json, Value};
+
+/// 工具名称
+pub const TOOL_NAME: &str = "resolve_address";
+
+/// 单次调用允许返回的最大结果数量
+pub const MAX_LIMIT: usize = 20;
+
+/// OpenAI 风格的工具定义（`{"type": "function", "function": {...}}`）
+pub fn tool_definition() -> Value {
+    json!({
+        "type": "function",
+        "function": {
+            "name": TOOL_NAME,
+            "description": "将中文地址文本解析为标准行政区划（省、市、区县、乡镇街道）及其编码",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "待解析的地址文本，例如“广东兴宁”"
+                    },
+                    "level": {
+                        "type": "string",
+                        "enum": ["province", "city", "district", "county"],
+                        "description": "只返回指定层级的结果"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": MAX_LIMIT,
+                        "description": "返回结果数量上限，默认 10"
+                    }
+                },
+                "required": ["query"],
+                "additionalProperties": false
+            }
+        }
+    })
+}
+
+/// 解析并校验工具调用参数（模型返回的 JSON 字符串）
+pub fn parse_tool_arguments(arguments: &str) -> Result<AddressQuery> {
+    let mut query: AddressQuery = serde_json::from_str(arguments)
+        .map_err(|e| Text2LocationError::InvalidInput(format!("工具参数格式错误: {}", e)))?;
+
+    query.query = query.query.trim().to_string();
+    if query.query.is_empty() {
+        return Err(Text2LocationError::InvalidInput(
+            "query 不能为空".to_string(),
+        ));
+    }
+    if let Some(limit) = query.limit {
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(Text2LocationError::InvalidInput(format!(
+                "limit 必须在 1 到 {} 之间",
+                MAX_LIMIT
+            )));
+        }
+    }
+
+    Ok(query)
+}
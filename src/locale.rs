@@ -0,0 +1,94 @@
+use crate::address::AddressResult;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 文本输出使用的语言，决定 `AddressResult` 单行格式中的标签
+///
+/// 只影响标签，地名本身仍按数据（及 `AddressIndexBuilder::traditional`）输出。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Locale {
+    /// 简体中文：`编码: … | 省: … | 市: … | 区: … | 县: …`
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    /// 英文：`code: … | province: … | city: … | district: … | county: …`
+    #[serde(rename = "en")]
+    En,
+}
+
+impl Locale {
+    /// 所有支持的语言
+    pub const ALL: [Locale; 2] = [Self::ZhCn, Self::En];
+
+    /// 编码及省市区县各级的标签
+    pub(crate) fn labels(self) -> [&'static str; 5] {
+        match self {
+            Self::ZhCn => ["编码", "省", "市", "区", "县"],
+            Self::En => ["code", "province", "city", "district", "county"],
+        }
+    }
+
+    /// 没有匹配结果时的提示
+    pub fn not_found(self, query: &str) -> String {
+        match self {
+            Self::ZhCn => format!("未找到匹配结果: {}", query),
+            Self::En => format!("no match: {}", query),
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zh-CN" | "zh" => Ok(Self::ZhCn),
+            "en" => Ok(Self::En),
+            other => Err(format!("未知的语言: {}（可选 zh-CN/en）", other)),
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ZhCn => "zh-CN",
+            Self::En => "en",
+        })
+    }
+}
+
+/// 按指定语言格式化的查询结果，由 `AddressResult::localized` 创建
+#[derive(Debug, Clone, Copy)]
+pub struct Localized<'a> {
+    result: &'a AddressResult,
+    locale: Locale,
+}
+
+impl<'a> Localized<'a> {
+    pub(crate) fn new(result: &'a AddressResult, locale: Locale) -> Self {
+        Self { result, locale }
+    }
+}
+
+impl fmt::Display for Localized<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [code, province, city, district, county] = self.locale.labels();
+        let r = self.result;
+        write!(
+            f,
+            "{}: {} | {}: {} | {}: {} | {}: {} | {}: {}",
+            code,
+            r.address_code,
+            province,
+            r.province,
+            city,
+            r.city,
+            district,
+            r.district,
+            county,
+            r.county
+        )
+    }
+}
@@ -1,13 +1,41 @@
-mod address_index;
-mod csv_loader;
-
-use address_index::AddressIndex;
-use csv_loader::{build_region_map, load_regions, Region};
+use clap::{Parser, Subcommand};
+use rayon::prelude::*;
+use rayon::ThreadPool;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
 use std::collections::HashMap;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, IsTerminal, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use text2location::address_index::{
+    AddressIndex, AddressRecord, AdminLevel, IndexProgress, LevelNames, SearchOptions,
+};
+use text2location::analytics::{analyze_query_log, coverage, UnresolvedCause};
+use text2location::bench::bench;
+use text2location::config::Config;
+use text2location::csv_loader::{
+    build_region_map, load_hints, load_labeled_queries, load_redirects, load_regions,
+    load_synonyms, select_regions, IndexingPolicy, InputEncoding, Region,
+};
+use text2location::dataset::Datasets;
+use text2location::dedupe::dedupe;
+use text2location::diff::{diff_regions, ChangeKind};
+use text2location::job::BatchJob;
+use text2location::locale::Locale;
+use text2location::output::{parse_columns, Column, OutputFormat, ResultRow, ResultWriter};
+use text2location::query_log::{read_query_log, QueryLog};
+use text2location::server;
+use text2location::tuning::{load_weights, save_weights, tune};
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
 
+/// 沿上级链收集省市区县各级名称，`name` 选择使用全称（ext_name）还是简称（name）
 fn resolve_address(
     region: &Region,
-    map: &HashMap<u64, Region>,
+    map: &HashMap<u64, &Region>,
+    name: fn(&Region) -> &String,
 ) -> (String, String, String, String) {
     let mut province = String::new();
     let mut city = String::new();
@@ -17,55 +45,436 @@ fn resolve_address(
     let mut current = Some(region);
     while let Some(r) = current {
         match r.deep {
-            0 => province = r.ext_name.clone(),
-            1 => city = r.ext_name.clone(),
-            2 => district = r.ext_name.clone(),
-            3 => county = r.ext_name.clone(),
+            0 => province = name(r).clone(),
+            1 => city = name(r).clone(),
+            2 => district = name(r).clone(),
+            3 => county = name(r).clone(),
             _ => {} // Ignore deeper levels if any
         }
 
         if r.pid == 0 {
             break;
         }
-        current = map.get(&r.pid);
+        current = map.get(&r.pid).copied();
     }
 
     (province, city, district, county)
 }
 
-fn build_index(address_index: &AddressIndex, csv_path: &str) -> anyhow::Result<()> {
+/// 读取 CSV 数据，转换为待索引的地址记录
+fn load_records(
+    csv_path: &Path,
+    policy: IndexingPolicy,
+    progress: &dyn Fn(IndexProgress),
+) -> anyhow::Result<Vec<AddressRecord>> {
     // 加载 CSV 数据
-    println!("正在加载 CSV 数据...");
+    info!("正在加载 CSV 数据...");
     let regions = load_regions(csv_path)?;
+    progress(IndexProgress::RowsLoaded(regions.len()));
 
-    println!("读取到 {} 条记录，正在构建索引...", regions.len());
+    info!("读取到 {} 条记录，正在构建索引...", regions.len());
     let region_map = build_region_map(&regions);
 
-    // 批量处理以提高性能
-    let mut docs = Vec::with_capacity(regions.len());
-    for region in &regions {
-        let (province, city, district, county) = resolve_address(region, &region_map);
+    // 沿上级链收集名称的开销随数据量线性增长，按区划并行处理（结果保持原顺序）
+    let docs = select_regions(&regions, policy)
+        .into_par_iter()
+        .filter_map(|region| {
+            // 忽略超出乡镇街道的层级
+            let level = AdminLevel::from_deep(region.deep)?;
+            let (province, city, district, county) =
+                resolve_address(region, &region_map, |r| &r.ext_name);
+            let (short_province, short_city, short_district, short_county) =
+                resolve_address(region, &region_map, |r| &r.name);
+            let (pinyin_province, pinyin_city, pinyin_district, pinyin_county) =
+                resolve_address(region, &region_map, |r| &r.pinyin);
 
-        docs.push((province, city, district, county, region.ext_id.clone()));
-    }
-    address_index.add_documents(&docs)?;
-    println!("索引构建完成！");
+            Some(AddressRecord {
+                province,
+                city,
+                district,
+                county,
+                address_code: region.ext_id.clone(),
+                level,
+                id: Some(region.id),
+                pid: Some(region.pid),
+                population: region.population,
+                location: region.location(),
+                extra: Default::default(),
+                short_names: LevelNames {
+                    province: short_province,
+                    city: short_city,
+                    district: short_district,
+                    county: short_county,
+                },
+                pinyin: LevelNames {
+                    province: pinyin_province,
+                    city: pinyin_city,
+                    district: pinyin_district,
+                    county: pinyin_county,
+                },
+                aliases: Vec::new(),
+            })
+        })
+        .collect();
+    Ok(docs)
+}
 
-    Ok(())
+#[derive(Parser)]
+#[command(name = "text2location", about = "中文地址解析")]
+struct Cli {
+    /// 配置文件（TOML），命令行参数优先于其中的设置
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// 行政区划数据文件，默认 ./src/areas.csv
+    #[arg(long, global = true)]
+    data: Option<PathBuf>,
+
+    /// 预构建的索引文件（.t2l，由 export 生成），设置后不再读取数据文件建索引
+    #[arg(long, global = true)]
+    index: Option<PathBuf>,
+
+    /// 文本输出的标签语言：zh-CN / en
+    #[arg(long, global = true)]
+    locale: Option<Locale>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-fn main() -> anyhow::Result<()> {
-    let csv_path = "./src/areas.csv";
+#[derive(Subcommand)]
+enum Command {
+    /// 重放查询日志，按可能原因汇总未解析或低置信度的查询
+    Report {
+        /// JSONL 查询日志文件（由 TEXT2LOCATION_QUERY_LOG 生成）
+        log: PathBuf,
+        /// 每类原因最多列出的查询数量
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+    },
+    /// 执行一批真实查询，统计解析成功率、平均置信度和未解析的查询，用于比较不同版本的效果
+    Coverage {
+        /// 查询文件，每行一个地址
+        queries: PathBuf,
+        /// 最多列出的未解析查询数量
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+        /// 输出格式：text / json（完整报告，便于保存后比较）
+        #[arg(long, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// 并发执行一批查询，统计吞吐量、p50/p99 延迟和分词缓存命中率，用于比较不同后端和配置
+    Bench {
+        /// 查询文件，每行一个地址
+        #[arg(long)]
+        queries: PathBuf,
+        /// 并发执行查询的线程数
+        #[arg(long, default_value_t = 8)]
+        concurrency: usize,
+        /// 每条查询返回的候选结果数量
+        #[arg(long)]
+        limit: Option<usize>,
+        /// 输出格式：text / json
+        #[arg(long, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// 标准化 CSV 中的地址并按“区划编码 + 详细地址”分簇，输出附加簇编号的 CSV
+    ///
+    /// 输出保留原有各列，末尾追加 cluster_id、address_code 和 standard_address 三列。
+    Dedupe {
+        /// 输入的 CSV 文件（须有表头）
+        #[arg(long)]
+        input: PathBuf,
+        /// 地址所在列的表头名称
+        #[arg(long)]
+        key: String,
+        /// 输出文件，默认写到标准输出
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// 列出被多个区划共用的本级全称（如“朝阳区”）及各自的上级区划
+    Ambiguous {
+        /// 只列出该层级（deep：0 省 / 1 市 / 2 区县 / 3 乡镇街道）的名称
+        #[arg(long)]
+        deep: Option<u8>,
+        /// 输出格式：text / json
+        #[arg(long, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// 从标注的查询中拟合排序权重（人口权重、提示词得分和层级系数），保存为 TOML 文件
+    ///
+    /// 拟合从当前配置的权重开始，保存的文件可通过配置 search.weights 加载。
+    Tune {
+        /// 标注的查询（CSV，列 query,expected_code）
+        pairs: PathBuf,
+        /// 输出的权重文件
+        #[arg(long)]
+        output: PathBuf,
+        /// 最多迭代的轮数
+        #[arg(long, default_value_t = 3)]
+        rounds: usize,
+    },
+    /// 对比两份区划数据，列出新增、删除、更名和变更上级的区划
+    Diff {
+        /// 旧数据文件
+        old: PathBuf,
+        /// 新数据文件
+        new: PathBuf,
+        /// 输出格式：text / json / csv
+        #[arg(long, default_value_t = OutputFormat::Json)]
+        format: OutputFormat,
+    },
+    /// 构建索引并导出为单个索引文件（.t2l），供其他节点用 --index 直接加载
+    Export {
+        /// 输出的索引文件
+        output: PathBuf,
+        /// 使用配置文件 [datasets] 中的数据集，默认使用 --data
+        #[arg(long)]
+        dataset: Option<String>,
+    },
+    /// 解析单个地址，输出候选结果
+    Search {
+        /// 地址文本
+        query: String,
+        /// 候选结果数量上限，默认 10
+        #[arg(long)]
+        limit: Option<usize>,
+        /// 输出格式：text / json / csv / table，默认终端中为彩色表格，否则为 text
+        #[arg(long)]
+        format: Option<OutputFormat>,
+        /// 使用配置文件 [datasets] 中的数据集，默认使用 --data
+        #[arg(long)]
+        dataset: Option<String>,
+        /// 按不同解释去重并计算概率，概率在 json 格式中输出
+        #[arg(long)]
+        interpretations: bool,
+    },
+    /// 批量解析：每行一个地址，输出每个地址的最佳结果
+    Batch {
+        /// 输入文件，默认读取标准输入
+        #[arg(long)]
+        input: Option<PathBuf>,
+        /// 输出文件，默认写到标准输出
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// 输出格式：text / json / csv / table / parquet，默认按输出文件扩展名推断，否则为 text
+        #[arg(long)]
+        format: Option<OutputFormat>,
+        /// 输入为 Excel 表格时读取的工作表，默认第一个
+        #[arg(long)]
+        sheet: Option<String>,
+        /// 输入为 Excel 表格（或 --keep-input 时的 CSV）时读取的列：表头名称或从 1 开始的列号
+        #[arg(long, default_value = "1")]
+        column: String,
+        /// 使用配置文件 [datasets] 中的数据集，默认使用 --data
+        #[arg(long)]
+        dataset: Option<String>,
+        /// 每行只输出地址编码（未解析时为空行），不读取存储的文档，适合大批量解析
+        #[arg(long, conflicts_with = "format")]
+        codes_only: bool,
+        /// 输出的结果列及列名，如 province,city,code:district_code（不支持 text 格式）
+        ///
+        /// 可选 query、code（address_code）、province、city、district、county、
+        /// full_address、level、score，默认为 query,address_code,province,city,district,county,score
+        #[arg(long, conflicts_with = "codes_only")]
+        columns: Option<String>,
+        /// 在输入 CSV 或表格的原有各列之后追加结果列，而不是只输出结果列（不支持 text 格式）
+        #[arg(long, conflicts_with = "codes_only")]
+        keep_input: bool,
+        /// 文本和 CSV 输入的编码：auto / utf-8 / gbk / gb18030，auto 时按内容判断
+        #[arg(long, default_value_t)]
+        encoding: InputEncoding,
+        /// 出错行的记录文件（列：row,query,error），默认为输出文件旁的 <输出文件名>.errors.csv；
+        /// 输出到标准输出且未指定时只记录日志
+        #[arg(long)]
+        errors: Option<PathBuf>,
+        /// 跳过明显不是地址的行（纯数字、英文、人名等），按未解析输出而不执行搜索
+        #[arg(long)]
+        skip_non_address: bool,
+        /// 以该 ID 定期保存检查点，进程中断后可用 --resume 继续（需要 --input 和 --output）
+        #[arg(long)]
+        job: Option<String>,
+        /// 从检查点继续中断的批量任务，任务参数从检查点读取
+        #[arg(long, conflicts_with_all = [
+            "input", "output", "format", "sheet", "column", "dataset", "codes_only", "columns",
+            "keep_input", "encoding", "errors", "skip_non_address",
+            "job",
+        ])]
+        resume: Option<String>,
+    },
+    /// 交互式解析：逐行输入地址，打印候选结果及得分
+    Repl {
+        /// 每次查询显示的候选数量，默认 5
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// 常驻服务：在 Unix 域套接字或 TCP 端口上响应查询
+    ///
+    /// 每行一个响应：{"results": [...]} 或 {"error": "..."}。
+    /// Unix 套接字上还可以发送批量请求 {"batch": ["兴宁市", {"query": "朝阳区"}]}，
+    /// 响应为 {"batch": [...]}，与请求中的查询一一对应。
+    /// 未指定 --unix / --tcp 时使用配置文件 [server] 中的设置；两者都指定时同时监听。
+    /// TCP 服务可按配置文件 [server] 中的 rate_limit / rate_burst 对每个客户端限流；
+    /// 配置了 [server.api_keys] 或 T2L_API_KEYS 时，TCP 连接的第一行须为 AUTH <key>。
+    /// 收到 SIGTERM / SIGINT 后不再接受新连接，处理完已读取的请求后退出
+    Serve {
+        /// Unix 域套接字路径，请求为 JSON Lines，如 {"query": "兴宁市", "limit": 3}
+        #[arg(long)]
+        unix: Option<PathBuf>,
+        /// TCP 监听地址（如 127.0.0.1:7070），请求为每行一个地址
+        #[arg(long)]
+        tcp: Option<String>,
+        /// 数据文件、同义词表或提示词表变化时在后台重新加载，无需重启服务（需要启用 watch 功能）
+        #[arg(long)]
+        watch: bool,
+        /// 搜索线程数，默认按 CPU 数量
+        #[arg(long)]
+        workers: Option<usize>,
+    },
+    /// 从 Kafka 主题读取地址，把解析结果以 JSON 写入另一个主题
+    ///
+    /// 解析出错的消息写入 `{"query": ..., "error": ...}`，不中断消费。
+    #[cfg(feature = "kafka")]
+    Consume {
+        /// Kafka broker 地址，多个用逗号分隔
+        #[arg(long, value_delimiter = ',', required = true)]
+        brokers: Vec<String>,
+        /// 读取地址的主题
+        #[arg(long)]
+        topic: String,
+        /// 写入解析结果的主题
+        #[arg(long)]
+        out_topic: String,
+        /// 消费者组，用于保存已处理的位点
+        #[arg(long, default_value = "text2location")]
+        group: String,
+    },
+}
 
-    // 创建地址索引（使用默认权重）
-    let mut address_index = AddressIndex::new()?;
+/// 加载默认数据文件并创建可查询的地址索引
+fn load_index(config: &Config, query_log: Option<QueryLog>) -> anyhow::Result<AddressIndex> {
+    load_dataset(config, None, query_log)
+}
+
+/// 加载指定数据集（None 为默认数据文件）并创建可查询的地址索引
+fn load_dataset(
+    config: &Config,
+    dataset: Option<&str>,
+    query_log: Option<QueryLog>,
+) -> anyhow::Result<AddressIndex> {
+    let data_path = config.dataset_path(dataset)?;
+    let policy = indexing_policy(config)?;
 
-    if let Err(e) = build_index(&address_index, csv_path) {
-        panic!("加载 CSV 失败: {}", e);
+    // 创建地址索引（使用默认权重）
+    let mut builder = AddressIndex::builder();
+    if let Some(query_log) = query_log {
+        builder = builder.query_log(query_log);
+    }
+    if let Some(separator) = &config.output.separator {
+        builder = builder.full_address_separator(separator.as_str());
+    }
+    builder = builder
+        .pinyin(config.output.pinyin)
+        .locale(config.output.locale);
+    #[cfg(feature = "traditional")]
+    {
+        builder = builder.traditional(config.output.traditional);
+    }
+    #[cfg(not(feature = "traditional"))]
+    if config.output.traditional {
+        anyhow::bail!("繁体输出需要启用 traditional 功能：cargo build --features traditional");
     }
+    if let Some(heap_size) = config.writer.heap_size {
+        builder = builder.writer_heap_size(heap_size);
+    }
+    if let Some(threads) = config.writer.threads {
+        builder = builder.writer_threads(threads);
+    }
+    if let Some(path) = &config.search.synonyms {
+        builder = builder.synonyms(load_synonyms(path)?);
+    }
+    if let Some(path) = &config.search.hints {
+        builder = builder.hints(load_hints(path)?);
+    }
+    if let Some(path) = &config.search.weights {
+        builder = builder.weights(load_weights(path)?);
+    }
+    if let Some(stopwords) = &config.search.stopwords {
+        builder = builder.stopwords(stopwords);
+    }
+    if let Some(size) = config.search.token_cache_size {
+        builder = builder.token_cache_size(size);
+    }
+    // 区划变更表只对应默认数据集
+    if let (None, Some(path)) = (dataset, &config.redirects) {
+        builder = builder.redirects(load_redirects(path)?);
+    }
+    // 预构建的索引文件只对应默认数据集
+    if let (None, Some(path)) = (dataset, &config.index) {
+        return Ok(builder.import(path)?);
+    }
+    let mut address_index = builder.progress(render_progress).build()?;
+
+    let docs = load_records(data_path, policy, &render_progress)
+        .map_err(|e| anyhow::anyhow!("加载 CSV 失败: {}", e))?;
+    address_index.add_documents(&docs)?;
+    info!("索引构建完成！");
 
     // 提交更改并重新加载索引
     address_index.commit()?;
+    if config.writer.optimize {
+        address_index.optimize()?;
+    }
+    Ok(address_index)
+}
+
+/// 在终端上显示建索引的进度条，标准错误不是终端时不显示
+fn render_progress(progress: IndexProgress) {
+    const WIDTH: usize = 30;
+    let mut stderr = io::stderr();
+    if !stderr.is_terminal() {
+        return;
+    }
+    let _ = match progress {
+        IndexProgress::RowsLoaded(rows) => writeln!(stderr, "已读取 {} 行", rows),
+        IndexProgress::DocsWritten { written, total } => {
+            let filled = written * WIDTH / total.max(1);
+            write!(
+                stderr,
+                "\r写入文档 [{}{}] {}/{}",
+                "#".repeat(filled),
+                " ".repeat(WIDTH - filled),
+                written,
+                total
+            )
+        }
+        IndexProgress::Committing => writeln!(stderr, "\n正在提交索引..."),
+        IndexProgress::Committed => writeln!(stderr, "索引提交完成"),
+    };
+}
+
+/// 索引策略，可通过环境变量 TEXT2LOCATION_INDEXING_POLICY=leaf-only 只索引叶子节点
+fn indexing_policy(config: &Config) -> anyhow::Result<IndexingPolicy> {
+    match env::var("TEXT2LOCATION_INDEXING_POLICY") {
+        Ok(value) => Ok(value.parse::<IndexingPolicy>()?),
+        Err(_) => Ok(config.indexing_policy.unwrap_or_default()),
+    }
+}
+
+/// 设置 TEXT2LOCATION_QUERY_LOG（或配置文件 query_log）时把每次查询追加记录到该 JSONL 文件
+fn query_log(config: &Config) -> anyhow::Result<Option<QueryLog>> {
+    match env::var_os("TEXT2LOCATION_QUERY_LOG") {
+        Some(path) => Ok(Some(QueryLog::jsonl(path)?)),
+        None => match &config.query_log {
+            Some(path) => Ok(Some(QueryLog::jsonl(path)?)),
+            None => Ok(None),
+        },
+    }
+}
+
+/// 示例：对固定查询演示各种搜索方式
+fn run_demo(config: &Config) -> anyhow::Result<()> {
+    let address_index = load_index(config, query_log(config)?)?;
 
     // 执行搜索，返回字符串数组
     let query = "兴宁市";
@@ -75,12 +484,1518 @@ fn main() -> anyhow::Result<()> {
         println!("{}", result);
     }
 
+    // 合并层级链相同的结果
+    let options = SearchOptions::new().limit(10).dedup(true);
+    let deduped = address_index.search(query, &options)?;
+    println!("\n去重后 {} 条结果", deduped.len());
+
+    // 只保留区县级结果
+    let options = SearchOptions::new().level(AdminLevel::District);
+    let districts = address_index.search(query, &options)?;
+    println!("区县级 {} 条结果", districts.len());
+
     // 搜索第一个结果
     if let Some(first) = address_index.search_first(query)? {
-        println!("\n第一个结果: {}", first.to_string());
+        println!("\n第一个结果: {}", first);
     } else {
         println!("\n未找到匹配结果");
     }
 
     Ok(())
 }
+
+/// 解析单个地址，按指定格式输出候选结果
+fn run_search(
+    config: &Config,
+    query: &str,
+    limit: usize,
+    format: Option<OutputFormat>,
+    dataset: Option<&str>,
+    interpretations: bool,
+) -> anyhow::Result<()> {
+    let address_index = load_dataset(config, dataset, query_log(config)?)?;
+    let options = SearchOptions::new()
+        .limit(limit)
+        .interpretations(interpretations);
+    let results = address_index.search(query, &options)?;
+
+    let stdout = io::stdout();
+    let is_terminal = stdout.is_terminal();
+    let format = format.unwrap_or(if is_terminal {
+        OutputFormat::Table
+    } else {
+        OutputFormat::Text
+    });
+    let mut writer = ResultWriter::new(stdout.lock(), format)?.locale(address_index.locale());
+    if is_terminal {
+        writer = writer.colored(address_index.tokenize(query));
+    }
+    if results.is_empty() {
+        writer.write_row(&ResultRow {
+            query,
+            result: None,
+        })?;
+    }
+    for result in &results {
+        writer.write_row(&ResultRow {
+            query,
+            result: Some(result),
+        })?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// 判断输入文件是否为 Excel / ODS 表格
+fn is_spreadsheet(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            ["xlsx", "xlsm", "xls", "ods"]
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// 读取批量输入：文本文件或标准输入按行读取，表格文件读取指定列
+fn read_queries(
+    input: Option<&Path>,
+    sheet: Option<&str>,
+    column: &str,
+    encoding: InputEncoding,
+) -> anyhow::Result<Box<dyn Iterator<Item = io::Result<String>>>> {
+    match input {
+        Some(path) if is_spreadsheet(path) => read_spreadsheet(path, sheet, column),
+        Some(path) => Ok(decode_lines(BufReader::new(File::open(path)?), encoding)?),
+        None => Ok(decode_lines(io::stdin().lock(), encoding)?),
+    }
+}
+
+/// 按行读取并按 `encoding` 解码，auto 时按读到的第一段内容判断整个输入的编码
+///
+/// GBK / GB18030 的多字节字符不含换行符的字节，因此可以先按字节分行再解码。
+fn decode_lines(
+    mut reader: impl BufRead + 'static,
+    encoding: InputEncoding,
+) -> io::Result<Box<dyn Iterator<Item = io::Result<String>>>> {
+    let encoding = encoding.detect(reader.fill_buf()?);
+    Ok(Box::new(reader.split(b'\n').map(move |line| {
+        let mut line = line?;
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        encoding.decode(&line)
+    })))
+}
+
+#[cfg(feature = "xlsx")]
+fn read_spreadsheet(
+    path: &Path,
+    sheet: Option<&str>,
+    column: &str,
+) -> anyhow::Result<Box<dyn Iterator<Item = io::Result<String>>>> {
+    let cells = text2location::xlsx::read_column(path, sheet, column)?;
+    info!("从 {} 读取到 {} 行", path.display(), cells.len());
+    Ok(Box::new(cells.into_iter().map(Ok)))
+}
+
+#[cfg(not(feature = "xlsx"))]
+fn read_spreadsheet(
+    path: &Path,
+    _sheet: Option<&str>,
+    _column: &str,
+) -> anyhow::Result<Box<dyn Iterator<Item = io::Result<String>>>> {
+    anyhow::bail!(
+        "读取表格文件 {} 需要启用 xlsx 功能：cargo build --features xlsx",
+        path.display()
+    )
+}
+
+/// 批量输入的各行：查询和该行在输入表格中的各列（按行读取时为空）
+type BatchRows = Box<dyn Iterator<Item = io::Result<(String, Vec<String>)>>>;
+
+/// 批量输入：保留输入各列时带有输入表格的表头，否则表头为空
+struct BatchInput {
+    header: Vec<String>,
+    rows: BatchRows,
+}
+
+/// 读取批量输入，`keep_input` 时读取 CSV 或表格的所有列，否则同 `read_queries`
+fn read_batch_input(
+    input: Option<&Path>,
+    sheet: Option<&str>,
+    column: &str,
+    keep_input: bool,
+    encoding: InputEncoding,
+) -> anyhow::Result<BatchInput> {
+    if !keep_input {
+        let rows =
+            read_queries(input, sheet, column, encoding)?.map(|line| line.map(|q| (q, Vec::new())));
+        return Ok(BatchInput {
+            header: Vec::new(),
+            rows: Box::new(rows),
+        });
+    }
+    let Some(path) = input else {
+        anyhow::bail!("--keep-input 需要用 --input 指定 CSV 或表格文件");
+    };
+    let (header, rows) = if is_spreadsheet(path) {
+        read_spreadsheet_table(path, sheet)?
+    } else if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+    {
+        let text = encoding.decode(&std::fs::read(path)?)?;
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(io::Cursor::new(text));
+        let header = reader.headers()?.iter().map(str::to_string).collect();
+        let rows = reader.into_records().map(|record| {
+            record
+                .map(|record| record.iter().map(str::to_string).collect())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        });
+        (header, Box::new(rows) as Box<dyn Iterator<Item = _>>)
+    } else {
+        anyhow::bail!("--keep-input 需要 CSV 或表格输入: {}", path.display());
+    };
+
+    // 与表格的 --column 相同：先按表头名称查找，再按从 1 开始的列号
+    let index = header
+        .iter()
+        .position(|name: &String| name.trim() == column)
+        .or_else(|| column.parse::<usize>().ok().and_then(|n| n.checked_sub(1)))
+        .ok_or_else(|| anyhow::anyhow!("{} 中没有列: {}", path.display(), column))?;
+    let rows = rows
+        .map(move |row| row.map(|cells| (cells.get(index).cloned().unwrap_or_default(), cells)));
+    Ok(BatchInput {
+        header,
+        rows: Box::new(rows),
+    })
+}
+
+/// 表格的所有行
+type TableRows = Box<dyn Iterator<Item = io::Result<Vec<String>>>>;
+
+#[cfg(feature = "xlsx")]
+fn read_spreadsheet_table(
+    path: &Path,
+    sheet: Option<&str>,
+) -> anyhow::Result<(Vec<String>, TableRows)> {
+    let (header, rows) = text2location::xlsx::read_table(path, sheet)?;
+    info!("从 {} 读取到 {} 行", path.display(), rows.len());
+    Ok((header, Box::new(rows.into_iter().map(Ok))))
+}
+
+#[cfg(not(feature = "xlsx"))]
+fn read_spreadsheet_table(
+    path: &Path,
+    _sheet: Option<&str>,
+) -> anyhow::Result<(Vec<String>, TableRows)> {
+    anyhow::bail!(
+        "读取表格文件 {} 需要启用 xlsx 功能：cargo build --features xlsx",
+        path.display()
+    )
+}
+
+/// 解析 --columns 指定的输出列
+fn parse_batch_columns(spec: Option<&str>) -> anyhow::Result<Option<Vec<Column>>> {
+    spec.map(parse_columns)
+        .transpose()
+        .map_err(anyhow::Error::msg)
+}
+
+/// 批量任务每处理多少条查询保存一次检查点
+const CHECKPOINT_INTERVAL: usize = 1000;
+
+/// 批量解析的输出：只输出地址编码，或按格式输出完整结果
+enum BatchSink {
+    Codes(Box<dyn Write>),
+    Results(ResultWriter<Box<dyn Write>>),
+}
+
+impl BatchSink {
+    /// 解析一条查询并写出，`input` 为该行在输入表格中的各列，返回是否解析成功
+    ///
+    /// `search` 为 false 时不解析，直接按未解析写出。解析出错时按未解析写出（输出与输入仍逐行对应），并在内层返回解析错误；
+    /// 只有写出失败时才返回外层错误。
+    fn write(
+        &mut self,
+        address_index: &AddressIndex,
+        query: &str,
+        input: &[String],
+        search: bool,
+    ) -> io::Result<text2location::error::Result<bool>> {
+        match self {
+            Self::Codes(out) => {
+                let code = if search {
+                    address_index.resolve_code(query)
+                } else {
+                    Ok(None)
+                };
+                let written = code.as_ref().ok().and_then(Option::as_deref);
+                writeln!(out, "{}", written.unwrap_or_default())?;
+                Ok(code.map(|code| code.is_some()))
+            }
+            Self::Results(writer) => {
+                // 保留输入各列时空查询的行也会原样写出
+                let result = if !search || query.is_empty() {
+                    Ok(None)
+                } else {
+                    address_index.resolve(query)
+                };
+                writer.write_row_with_input(
+                    input,
+                    &ResultRow {
+                        query,
+                        result: result.as_ref().ok().and_then(Option::as_ref),
+                    },
+                )?;
+                Ok(result.map(|result| result.is_some()))
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Codes(out) => out.flush(),
+            Self::Results(writer) => writer.flush(),
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Codes(mut out) => out.flush(),
+            Self::Results(writer) => writer.finish(),
+        }
+    }
+}
+
+/// 批量解析中出错的行（读取失败或解析失败），写到旁路的错误文件
+///
+/// 错误文件为 CSV（列：row,query,error），第一次出错时才创建；没有错误文件时只记录日志。
+struct BatchErrors {
+    path: Option<PathBuf>,
+    writer: Option<csv::Writer<File>>,
+    /// 从检查点恢复时错误文件应保留的长度，为 0 时重新创建
+    resume_len: u64,
+    count: usize,
+}
+
+impl BatchErrors {
+    fn new(path: Option<PathBuf>, resume_len: u64) -> Self {
+        Self {
+            path,
+            writer: None,
+            resume_len,
+            count: 0,
+        }
+    }
+
+    /// 记录一行错误，`row` 为该行在输入中的行号（从 1 开始，不含表头）
+    fn record(&mut self, row: usize, query: &str, error: &str) -> anyhow::Result<()> {
+        self.count += 1;
+        warn!("第 {} 行出错：{}", row, error);
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if self.writer.is_none() {
+            self.writer = Some(if self.resume_len > 0 {
+                // 丢弃上次检查点之后记录的错误，从检查点处续写
+                let mut file = OpenOptions::new().write(true).open(path)?;
+                file.set_len(self.resume_len)?;
+                file.seek(SeekFrom::End(0))?;
+                csv::Writer::from_writer(file)
+            } else {
+                let mut writer = csv::Writer::from_path(path)?;
+                writer.write_record(["row", "query", "error"])?;
+                writer
+            });
+        }
+        let writer = self.writer.as_mut().expect("错误文件已打开");
+        writer.write_record([row.to_string().as_str(), query, error])?;
+        Ok(())
+    }
+
+    /// 刷新后错误文件的长度，用于保存检查点
+    fn len(&mut self) -> io::Result<u64> {
+        match (&mut self.writer, &self.path) {
+            (Some(writer), Some(path)) => {
+                writer.flush()?;
+                Ok(std::fs::metadata(path)?.len())
+            }
+            _ => Ok(self.resume_len),
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self.writer {
+            Some(mut writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 输出文件旁的默认错误文件：`<输出文件名>.errors.csv`
+fn default_errors_path(output: &Path) -> PathBuf {
+    output.with_extension("errors.csv")
+}
+
+/// 批量解析的输出设置
+struct BatchOutput<'a> {
+    /// 输出文件，None 时写到标准输出
+    path: Option<&'a Path>,
+    format: Option<OutputFormat>,
+    codes_only: bool,
+    /// 输出的结果列，None 时为默认列
+    columns: Option<Vec<Column>>,
+    /// 错误文件，None 时使用 `default_errors_path`
+    errors: Option<PathBuf>,
+    /// 不搜索 `AddressIndex::is_probably_address` 判断为非地址的行
+    skip_non_address: bool,
+}
+
+/// 批量解析：逐行读取地址，输出每个地址的最佳结果
+///
+/// 某一行读取失败（如格式错误的 CSV 记录）或解析出错时不中止，该行按未解析输出，
+/// 行号和错误原因记录到错误文件，结束时汇总。
+/// 指定 `job` 时定期保存检查点，并跳过检查点中已处理的查询、在输出文件末尾续写。
+fn run_batch(
+    config: &Config,
+    input: BatchInput,
+    output: BatchOutput<'_>,
+    dataset: Option<&str>,
+    mut job: Option<BatchJob>,
+) -> anyhow::Result<()> {
+    let BatchOutput {
+        path: output,
+        format,
+        codes_only,
+        columns,
+        errors,
+        skip_non_address,
+    } = output;
+    let format = match format {
+        Some(format) => format,
+        None => {
+            let inferred = output.and_then(OutputFormat::from_path);
+            if inferred.is_none()
+                && output.is_some_and(|p| p.extension() == Some("parquet".as_ref()))
+            {
+                anyhow::bail!("输出 Parquet 需要启用 parquet 功能：cargo build --features parquet");
+            }
+            inferred.unwrap_or_default()
+        }
+    };
+    if job.is_some()
+        && !codes_only
+        && !matches!(
+            format,
+            OutputFormat::Text | OutputFormat::Json | OutputFormat::Csv
+        )
+    {
+        anyhow::bail!(
+            "{} 格式在结束时才统一写出，无法保存检查点，可选 text/json/csv",
+            format
+        );
+    }
+    let keep_input = !input.header.is_empty();
+    if format == OutputFormat::Text && (columns.is_some() || keep_input) {
+        anyhow::bail!("text 格式不支持 --columns 和 --keep-input，可选 json/csv/table/parquet");
+    }
+    let address_index = load_dataset(config, dataset, query_log(config)?)?;
+
+    let skip = job.as_ref().map_or(0, |job| job.done);
+    let out: Box<dyn Write> = match (output, &job) {
+        (Some(path), Some(job)) if skip > 0 => {
+            // 丢弃上次检查点之后写出的部分结果，从检查点处续写
+            let mut file = OpenOptions::new().write(true).open(path)?;
+            file.set_len(job.output_len)?;
+            file.seek(SeekFrom::End(0))?;
+            Box::new(BufWriter::new(file))
+        }
+        (Some(path), _) => Box::new(BufWriter::new(File::create(path)?)),
+        (None, _) => Box::new(io::stdout().lock()),
+    };
+    let mut sink = if codes_only {
+        BatchSink::Codes(out)
+    } else {
+        let writer = if skip > 0 {
+            ResultWriter::append(out, format)?
+        } else {
+            ResultWriter::new(out, format)?
+        };
+        let writer = writer
+            .locale(address_index.locale())
+            .input_header(input.header);
+        BatchSink::Results(match columns {
+            Some(columns) => writer.columns(columns),
+            None => writer,
+        })
+    };
+    if skip > 0 {
+        info!("从检查点继续，跳过已处理的 {} 条", skip);
+    }
+
+    let errors_path = errors.or_else(|| output.map(default_errors_path));
+    let mut errors = BatchErrors::new(
+        errors_path.clone(),
+        job.as_ref().map_or(0, |job| job.errors_len),
+    );
+
+    // 按行读取时跳过空行，保留输入各列时每行都原样输出
+    let rows = input
+        .rows
+        .enumerate()
+        .filter(|(_, row)| {
+            keep_input
+                || row
+                    .as_ref()
+                    .map_or(true, |(query, _)| !query.trim().is_empty())
+        })
+        .skip(skip);
+    let mut total = 0;
+    let mut resolved = 0;
+    let mut skipped = 0;
+    for (i, row) in rows {
+        let number = i + 1;
+        match row {
+            Ok((query, cells)) => {
+                let query = query.trim();
+                let search = !skip_non_address || address_index.is_probably_address(query);
+                skipped += usize::from(!search);
+                match sink.write(&address_index, query, &cells, search)? {
+                    Ok(found) => resolved += usize::from(found),
+                    Err(e) => errors.record(number, query, &e.to_string())?,
+                }
+            }
+            // 无法读取的行（格式错误、编码错误）按空行输出，其他读取错误（如磁盘故障）中止
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                let _ = sink.write(&address_index, "", &[], false)?;
+                errors.record(number, "", &e.to_string())?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        total += 1;
+        if let (Some(job), Some(path)) = (&mut job, output) {
+            if total % CHECKPOINT_INTERVAL == 0 {
+                sink.flush()?;
+                job.done = skip + total;
+                job.output_len = std::fs::metadata(path)?.len();
+                job.errors_len = errors.len()?;
+                job.save(config.jobs_dir())?;
+            }
+        }
+    }
+    sink.finish()?;
+    let failed = errors.count;
+    errors.finish()?;
+    if let Some(job) = &job {
+        job.remove(config.jobs_dir())?;
+    }
+    info!(
+        "批量解析完成：共 {} 条，解析成功 {} 条，未解析 {} 条，出错 {} 条",
+        total,
+        resolved,
+        total - resolved - failed,
+        failed
+    );
+    if skipped > 0 {
+        info!("其中 {} 条判断为非地址，未执行搜索", skipped);
+    }
+    if let (true, Some(path)) = (failed > 0, &errors_path) {
+        info!("出错的行已记录到 {}", path.display());
+    }
+    Ok(())
+}
+
+/// 保存新批量任务的初始检查点后开始执行，同名任务的检查点已存在时报错
+fn start_batch_job(config: &Config, job: BatchJob) -> anyhow::Result<()> {
+    if BatchJob::path(config.jobs_dir(), &job.id)?.exists() {
+        anyhow::bail!("批量任务 {} 已存在，使用 --resume {} 继续", job.id, job.id);
+    }
+    job.save(config.jobs_dir())?;
+    run_batch_job(config, job)
+}
+
+/// 按任务参数执行可恢复的批量任务
+fn run_batch_job(config: &Config, job: BatchJob) -> anyhow::Result<()> {
+    let input = read_batch_input(
+        Some(&job.input),
+        job.sheet.as_deref(),
+        &job.column,
+        job.keep_input,
+        job.encoding,
+    )?;
+    let columns = parse_batch_columns(job.columns.as_deref())?;
+    let format = job
+        .format
+        .as_deref()
+        .map(str::parse)
+        .transpose()
+        .map_err(anyhow::Error::msg)?;
+    let output = job.output.clone();
+    let dataset = job.dataset.clone();
+    let codes_only = job.codes_only;
+    let errors = job.errors.clone();
+    let skip_non_address = job.skip_non_address;
+    run_batch(
+        config,
+        input,
+        BatchOutput {
+            path: Some(&output),
+            format,
+            codes_only,
+            columns,
+            errors,
+            skip_non_address,
+        },
+        dataset.as_deref(),
+        Some(job),
+    )
+}
+
+/// 启动常驻查询服务
+fn run_serve(
+    config: &Config,
+    unix: Option<&Path>,
+    tcp: Option<&str>,
+    watch: bool,
+    workers: Option<usize>,
+) -> anyhow::Result<()> {
+    // 命令行指定了任一监听方式时忽略配置文件中的设置
+    let (unix, tcp) = if unix.is_some() || tcp.is_some() {
+        (unix, tcp)
+    } else {
+        (config.server.unix.as_deref(), config.server.tcp.as_deref())
+    };
+    if unix.is_none() && tcp.is_none() {
+        anyhow::bail!("需要指定 --unix 或 --tcp，或在配置文件 [server] 中设置");
+    }
+
+    let query_log = query_log(config)?;
+    let address_index = Arc::new(load_index(config, query_log.clone())?);
+    // 重新加载期间持有，退出前等待正在进行的重建提交完成
+    let reloading = Arc::new(Mutex::new(()));
+    if watch {
+        watch_data(config, Arc::clone(&address_index), Arc::clone(&reloading))?;
+    }
+    let mut datasets = Datasets::new(address_index);
+    for name in config.datasets.keys() {
+        info!("加载数据集 {}", name);
+        let index = load_dataset(config, Some(name), query_log.clone())?;
+        datasets.insert(name.clone(), Arc::new(index));
+    }
+    // 预热完成后才开始监听，第一批请求不会因为加载词典和索引数据而变慢
+    datasets.warmup()?;
+    let datasets = Arc::new(datasets);
+    let pool = server::search_pool(workers.or(config.server.workers))?;
+    info!("搜索线程数 {}", pool.current_num_threads());
+
+    let mut access = server::Access::new();
+    if let Some(rate_limit) = config.server.rate_limit {
+        let burst = config
+            .server
+            .rate_burst
+            .unwrap_or(rate_limit.ceil().max(1.0) as u32);
+        access = access.rate_limit(server::RateLimiter::new(rate_limit, burst)?);
+        info!(
+            "TCP 服务按客户端限流：每秒 {} 个请求，突发 {} 个",
+            rate_limit, burst
+        );
+    }
+    for (name, key) in &config.server.api_keys {
+        access = access.api_key(name, key);
+    }
+    if !config.server.api_keys.is_empty() {
+        info!(
+            "TCP 服务需要 API key 认证，共 {} 个 key",
+            config.server.api_keys.len()
+        );
+    }
+    let access = Arc::new(access);
+
+    let shutdown = server::Shutdown::default();
+    handle_signals(&shutdown)?;
+    // 同时指定了 Unix 套接字和 TCP 时两者都监听，任一个出错退出时另一个也停止
+    let served: Vec<anyhow::Result<()>> = std::thread::scope(|scope| {
+        let shutdown = &shutdown;
+        let unix = unix.map(|path| {
+            let (datasets, pool) = (Arc::clone(&datasets), Arc::clone(&pool));
+            scope.spawn(move || {
+                let served = serve_unix(datasets, pool, path, shutdown);
+                shutdown.trigger();
+                served
+            })
+        });
+        let tcp = tcp.map(|addr| {
+            let (datasets, pool, access) = (
+                Arc::clone(&datasets),
+                Arc::clone(&pool),
+                Arc::clone(&access),
+            );
+            scope.spawn(move || {
+                let served = server::serve_tcp(datasets, pool, addr, access, shutdown);
+                shutdown.trigger();
+                Ok(served?)
+            })
+        });
+        [unix, tcp]
+            .into_iter()
+            .flatten()
+            .map(|listener| {
+                listener
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("监听线程异常退出")))
+            })
+            .collect()
+    });
+    served.into_iter().collect::<anyhow::Result<()>>()?;
+    let _reloading = reloading.lock();
+    info!("服务已退出");
+    Ok(())
+}
+
+#[cfg(unix)]
+fn serve_unix(
+    datasets: Arc<Datasets>,
+    pool: Arc<ThreadPool>,
+    path: &Path,
+    shutdown: &server::Shutdown,
+) -> anyhow::Result<()> {
+    Ok(server::serve_unix(datasets, pool, path, shutdown)?)
+}
+
+#[cfg(not(unix))]
+fn serve_unix(
+    _datasets: Arc<Datasets>,
+    _pool: Arc<ThreadPool>,
+    path: &Path,
+    _shutdown: &server::Shutdown,
+) -> anyhow::Result<()> {
+    anyhow::bail!("当前平台不支持 Unix 域套接字: {}", path.display())
+}
+
+/// 收到 SIGTERM / SIGINT 时停止服务：不再接受新连接，处理完已读取的请求后退出；
+/// 再次收到信号时立即退出
+#[cfg(unix)]
+fn handle_signals(shutdown: &server::Shutdown) -> anyhow::Result<()> {
+    use signal_hook::consts::{SIGINT, SIGTERM};
+    use signal_hook::iterator::Signals;
+
+    let mut signals = Signals::new([SIGTERM, SIGINT])?;
+    let shutdown = shutdown.clone();
+    std::thread::spawn(move || {
+        for signal in signals.forever() {
+            if shutdown.is_triggered() {
+                warn!("再次收到退出信号，立即退出");
+                std::process::exit(128 + signal);
+            }
+            info!("收到退出信号，停止接受新连接，处理完已读取的请求后退出");
+            shutdown.trigger();
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn handle_signals(_shutdown: &server::Shutdown) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// 监听的文件变化后需要重新加载的内容
+#[cfg(feature = "watch")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Watched {
+    Data,
+    Synonyms,
+    Hints,
+}
+
+/// 监听数据文件、同义词表和提示词表，文件变化时在后台重新加载：
+/// 数据文件变化时重建索引并整体替换，同义词表、提示词表变化时只替换对应的表
+///
+/// 重新加载失败（例如文件只写了一半）时保留旧的内容，下次变化时再重试。
+/// 使用预构建的索引文件时索引不是由数据文件生成的，只监听同义词表和提示词表。
+#[cfg(feature = "watch")]
+fn watch_data(
+    config: &Config,
+    address_index: Arc<AddressIndex>,
+    reloading: Arc<Mutex<()>>,
+) -> anyhow::Result<()> {
+    use notify::{RecursiveMode, Watcher};
+    use std::collections::HashSet;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let mut files = Vec::new();
+    match &config.index {
+        Some(index) => warn!(
+            "使用预构建的索引文件 {}，数据文件变化时不会重建索引",
+            index.display()
+        ),
+        None => files.push((config.data_path().to_path_buf(), Watched::Data)),
+    }
+    if let Some(path) = &config.search.synonyms {
+        files.push((path.clone(), Watched::Synonyms));
+    }
+    if let Some(path) = &config.search.hints {
+        files.push((path.clone(), Watched::Hints));
+    }
+    if files.is_empty() {
+        anyhow::bail!("使用预构建的索引文件时 --watch 只监听同义词表和提示词表，但两者都没有配置");
+    }
+    let policy = indexing_policy(config)?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    // 文件常以“写临时文件再改名”的方式更新，因此监听所在目录而不是文件本身
+    let mut dirs = HashSet::new();
+    for (path, _) in &files {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        if dirs.insert(dir.clone()) {
+            watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        }
+        info!("监听 {} 的变化", path.display());
+    }
+
+    std::thread::spawn(move || {
+        // watcher 被释放后就不再产生事件
+        let _watcher = watcher;
+        let changed_in = |event: &notify::Event| -> Vec<Watched> {
+            if event.kind.is_access() {
+                return Vec::new();
+            }
+            files
+                .iter()
+                .filter(|(path, _)| {
+                    event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name() == path.file_name())
+                })
+                .map(|&(_, watched)| watched)
+                .collect()
+        };
+        while let Ok(event) = rx.recv() {
+            let mut changed = match event {
+                Ok(event) => changed_in(&event),
+                Err(e) => {
+                    warn!("监听文件出错: {}", e);
+                    continue;
+                }
+            };
+            if changed.is_empty() {
+                continue;
+            }
+            // 一次保存通常触发多个事件，等文件稳定后再重新加载
+            while let Ok(event) = rx.recv_timeout(Duration::from_millis(500)) {
+                if let Ok(event) = event {
+                    changed.extend(changed_in(&event));
+                }
+            }
+
+            let _reloading = reloading.lock();
+            for (path, watched) in &files {
+                if !changed.contains(watched) {
+                    continue;
+                }
+                info!("{} 已变化，正在重新加载...", path.display());
+                let result = match watched {
+                    Watched::Data => load_records(path, policy, &|_| {})
+                        .and_then(|docs| Ok(address_index.replace_documents(&docs)?)),
+                    Watched::Synonyms => load_synonyms(path)
+                        .map(|synonyms| address_index.replace_synonyms(&synonyms))
+                        .map_err(Into::into),
+                    Watched::Hints => load_hints(path)
+                        .map(|hints| address_index.replace_hints(&hints))
+                        .map_err(Into::into),
+                };
+                if let Err(e) = result {
+                    warn!("重新加载 {} 失败，继续使用旧的内容: {}", path.display(), e);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(feature = "watch"))]
+fn watch_data(
+    _config: &Config,
+    _address_index: Arc<AddressIndex>,
+    _reloading: Arc<Mutex<()>>,
+) -> anyhow::Result<()> {
+    anyhow::bail!("--watch 需要启用 watch 功能：cargo build --features watch")
+}
+
+/// 持续消费 Kafka 主题中的地址，写出解析结果
+///
+/// 每条消息的内容为一个地址，输出为 `{"query": ..., "result": ...}` 形式的 JSON，
+/// 并沿用输入消息的 key。每批消息全部写出后才提交位点，进程中断时最多重复处理一批。
+#[cfg(feature = "kafka")]
+fn run_consume(
+    config: &Config,
+    brokers: Vec<String>,
+    topic: &str,
+    out_topic: &str,
+    group: String,
+) -> anyhow::Result<()> {
+    use kafka::consumer::{Consumer, FetchOffset, GroupOffsetStorage};
+    use kafka::producer::{Producer, Record};
+
+    let address_index = load_index(config, query_log(config)?)?;
+    let mut consumer = Consumer::from_hosts(brokers.clone())
+        .with_topic(topic.to_string())
+        .with_group(group)
+        .with_fallback_offset(FetchOffset::Earliest)
+        .with_offset_storage(Some(GroupOffsetStorage::Kafka))
+        .create()?;
+    let mut producer = Producer::from_hosts(brokers).create()?;
+    info!("开始消费主题 {}，结果写入 {}", topic, out_topic);
+
+    loop {
+        let message_sets = consumer.poll()?;
+        if message_sets.is_empty() {
+            continue;
+        }
+        let mut total = 0;
+        for message_set in message_sets.iter() {
+            for message in message_set.messages() {
+                let line = String::from_utf8_lossy(message.value);
+                let query = line.trim();
+                let result = if query.is_empty() {
+                    Ok(None)
+                } else {
+                    address_index.resolve(query)
+                };
+                // 查询出错的消息写入错误记录后照常提交位点，避免一条坏消息反复阻塞消费
+                let value = match result {
+                    Ok(result) => serde_json::to_vec(&ResultRow {
+                        query,
+                        result: result.as_ref(),
+                    })?,
+                    Err(e) => {
+                        warn!("消息解析失败: {}: {}", query, e);
+                        serde_json::to_vec(&serde_json::json!({
+                            "query": query,
+                            "error": e.to_string(),
+                        }))?
+                    }
+                };
+                producer.send(&Record::from_key_value(out_topic, message.key, value))?;
+                total += 1;
+            }
+            consumer.consume_messageset(message_set)?;
+        }
+        consumer.commit_consumed()?;
+        info!("已处理 {} 条消息", total);
+    }
+}
+
+/// 重放查询日志，输出未解析查询报告
+fn run_report(config: &Config, log: &Path, top: usize) -> anyhow::Result<()> {
+    let entries = read_query_log(log)?;
+    info!("读取到 {} 条查询日志", entries.len());
+    let address_index = load_index(config, None)?;
+    let unresolved = analyze_query_log(&address_index, &entries)?;
+
+    for cause in [
+        UnresolvedCause::UnknownName,
+        UnresolvedCause::Conflict,
+        UnresolvedCause::Typo,
+    ] {
+        let group: Vec<_> = unresolved.iter().filter(|q| q.cause == cause).collect();
+        let total: usize = group.iter().map(|q| q.count).sum();
+        println!("{} ({} 条查询, 共 {} 次)", cause, group.len(), total);
+        for query in group.iter().take(top) {
+            match &query.top {
+                Some(result) => println!(
+                    "  {:>6}  {}  ->  {}",
+                    query.count,
+                    query.query,
+                    result.localized(address_index.locale())
+                ),
+                None => println!("  {:>6}  {}", query.count, query.query),
+            }
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// 执行查询文件中的查询，输出覆盖率报告
+fn run_coverage(
+    config: &Config,
+    queries: &Path,
+    top: usize,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    if !matches!(format, OutputFormat::Text | OutputFormat::Json) {
+        anyhow::bail!("coverage 不支持 {} 格式，可选 text/json", format);
+    }
+    let queries = std::fs::read_to_string(queries)?;
+    let address_index = load_index(config, None)?;
+    let report = coverage(&address_index, queries.lines())?;
+
+    if format == OutputFormat::Json {
+        serde_json::to_writer_pretty(io::stdout().lock(), &report)?;
+        println!();
+        return Ok(());
+    }
+    println!("查询 {} 条（去重后 {} 条）", report.total, report.distinct);
+    println!(
+        "解析成功 {} 条（{:.1}%）",
+        report.resolved,
+        report.resolution_rate() * 100.0
+    );
+    println!("平均置信度 {:.3}", report.average_confidence);
+    let causes: Vec<String> = report
+        .causes
+        .iter()
+        .map(|(cause, count)| format!("{} {}", cause, count))
+        .collect();
+    println!(
+        "未解析 {} 条：{}",
+        report.total - report.resolved,
+        causes.join(" / ")
+    );
+    if !report.unresolved.is_empty() {
+        println!("\n未解析的查询（按出现次数）:");
+    }
+    for query in report.unresolved.iter().take(top) {
+        match &query.top {
+            Some(result) => println!(
+                "  {:>6}  {}  {}  ->  {}",
+                query.count,
+                query.cause,
+                query.query,
+                result.localized(address_index.locale())
+            ),
+            None => println!("  {:>6}  {}  {}", query.count, query.cause, query.query),
+        }
+    }
+    Ok(())
+}
+
+/// 预热索引后并发执行查询文件中的查询，输出压测结果
+fn run_bench(
+    config: &Config,
+    queries: &Path,
+    concurrency: usize,
+    limit: usize,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    if !matches!(format, OutputFormat::Text | OutputFormat::Json) {
+        anyhow::bail!("bench 不支持 {} 格式，可选 text/json", format);
+    }
+    let queries = std::fs::read_to_string(queries)?;
+    let queries = queries.lines().map(str::trim).filter(|q| !q.is_empty());
+    let address_index = load_index(config, None)?;
+    address_index.warmup()?;
+    let options = SearchOptions::new().limit(limit);
+    let report = bench(&address_index, queries, concurrency, &options)?;
+
+    if format == OutputFormat::Json {
+        serde_json::to_writer_pretty(io::stdout().lock(), &report)?;
+        println!();
+        return Ok(());
+    }
+    println!(
+        "查询 {} 条，并发 {}，失败 {} 条，耗时 {:.1} ms",
+        report.queries, report.concurrency, report.errors, report.elapsed_ms
+    );
+    println!("吞吐量 {:.1} 次/秒", report.throughput);
+    println!(
+        "延迟 p50 {:.3} ms / p99 {:.3} ms",
+        report.p50_ms, report.p99_ms
+    );
+    println!("分词缓存命中率 {:.1}%", report.cache_hit_rate * 100.0);
+    Ok(())
+}
+
+/// 读取 CSV，按地址分簇后写出附加簇编号的 CSV
+fn run_dedupe(
+    config: &Config,
+    input: &Path,
+    key: &str,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    let mut reader = csv::Reader::from_path(input)?;
+    let headers = reader.headers()?.clone();
+    let Some(column) = headers.iter().position(|h| h == key) else {
+        anyhow::bail!("{} 中没有名为 {} 的列", input.display(), key);
+    };
+    let records = reader.records().collect::<Result<Vec<_>, _>>()?;
+    let addresses: Vec<&str> = records
+        .iter()
+        .map(|record| record.get(column).unwrap_or_default())
+        .collect();
+
+    let address_index = load_index(config, None)?;
+    let rows = dedupe(&address_index, &addresses)?;
+
+    let out: Box<dyn Write> = match output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = csv::Writer::from_writer(out);
+    let mut header = headers.clone();
+    header.extend(["cluster_id", "address_code", "standard_address"]);
+    writer.write_record(&header)?;
+    for (record, row) in records.iter().zip(&rows) {
+        let mut record = record.clone();
+        let code = row.result.as_ref().map_or("", |r| r.address_code.as_str());
+        record.extend([
+            row.cluster.to_string().as_str(),
+            code,
+            &row.standard_address(),
+        ]);
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+
+    let clusters = rows.iter().map(|row| row.cluster).max().unwrap_or(0);
+    info!("共 {} 行，分为 {} 个簇", rows.len(), clusters);
+    Ok(())
+}
+
+/// 输出被多个区划共用的名称
+fn run_ambiguous(config: &Config, deep: Option<u8>, format: OutputFormat) -> anyhow::Result<()> {
+    if !matches!(format, OutputFormat::Text | OutputFormat::Json) {
+        anyhow::bail!("ambiguous 不支持 {} 格式，可选 text/json", format);
+    }
+    let level = match deep {
+        Some(deep) => match AdminLevel::from_deep(deep) {
+            Some(level) => Some(level),
+            None => anyhow::bail!("无效的层级 {}，可选 0-3", deep),
+        },
+        None => None,
+    };
+    let address_index = load_index(config, None)?;
+    let names = address_index.ambiguous_names(level);
+
+    let mut out = io::stdout().lock();
+    for name in &names {
+        if format == OutputFormat::Json {
+            serde_json::to_writer(&mut out, name)?;
+            writeln!(out)?;
+            continue;
+        }
+        writeln!(out, "{}（{} 个区划）", name.name, name.regions.len())?;
+        for region in &name.regions {
+            writeln!(
+                out,
+                "  {}  {}",
+                region.address_code,
+                region.parents.concat()
+            )?;
+        }
+    }
+    info!("共 {} 个名称被多个区划使用", names.len());
+    Ok(())
+}
+
+/// 拟合排序权重并保存
+fn run_tune(config: &Config, pairs: &Path, output: &Path, rounds: usize) -> anyhow::Result<()> {
+    let pairs = load_labeled_queries(pairs)?;
+    info!("读取到 {} 条标注的查询", pairs.len());
+    let mut address_index = load_index(config, None)?;
+    let report = tune(&mut address_index, &pairs, rounds)?;
+    save_weights(output, &report.weights)?;
+
+    println!(
+        "准确率 {:.1}% -> {:.1}%",
+        report.baseline.accuracy() * 100.0,
+        report.tuned.accuracy() * 100.0
+    );
+    println!("MRR {:.3} -> {:.3}", report.baseline.mrr, report.tuned.mrr);
+    println!("权重已保存到 {}", output.display());
+    Ok(())
+}
+
+/// 对比两份区划数据，把变更列表写到标准输出
+fn run_diff(old: &Path, new: &Path, format: OutputFormat) -> anyhow::Result<()> {
+    let changes = diff_regions(&load_regions(old)?, &load_regions(new)?);
+    let mut out = io::stdout().lock();
+    match format {
+        OutputFormat::Text => {
+            for c in &changes {
+                let detail = match (c.change, &c.old_name, &c.new_name) {
+                    (ChangeKind::Reparented, _, _) => format!(
+                        "上级 {} -> {}",
+                        c.old_parent.unwrap_or_default(),
+                        c.new_parent.unwrap_or_default()
+                    ),
+                    (_, Some(old), Some(new)) => format!("{} -> {}", old, new),
+                    (_, Some(name), None) | (_, None, Some(name)) => name.clone(),
+                    (_, None, None) => String::new(),
+                };
+                writeln!(out, "{} {} {}", c.change, c.code, detail)?;
+            }
+        }
+        OutputFormat::Json => {
+            for c in &changes {
+                serde_json::to_writer(&mut out, c)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            for c in &changes {
+                writer.serialize(c)?;
+            }
+            writer.flush()?;
+        }
+        other => anyhow::bail!("diff 不支持 {} 格式，可选 text/json/csv", other),
+    }
+
+    let count = |kind| changes.iter().filter(|c| c.change == kind).count();
+    info!(
+        "新增 {} 个，删除 {} 个，更名 {} 个，变更上级 {} 个",
+        count(ChangeKind::Added),
+        count(ChangeKind::Removed),
+        count(ChangeKind::Renamed),
+        count(ChangeKind::Reparented)
+    );
+    Ok(())
+}
+
+/// 交互式解析，历史记录保存在 ~/.text2location_history
+fn run_repl(config: &Config, limit: usize) -> anyhow::Result<()> {
+    let address_index = load_index(config, None)?;
+    let options = SearchOptions::new().limit(limit);
+
+    let history_path =
+        env::var_os("HOME").map(|home| Path::new(&home).join(".text2location_history"));
+    let mut editor = DefaultEditor::new()?;
+    if let Some(path) = &history_path {
+        // 首次运行时历史文件不存在，忽略即可
+        let _ = editor.load_history(path);
+    }
+
+    println!("输入地址进行解析，Ctrl-D 退出");
+    loop {
+        let line = match editor.readline("text2location> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let query = line.trim();
+        if query.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(query)?;
+
+        let stdout = io::stdout();
+        let colored = stdout.is_terminal();
+        repl_answer(&address_index, &options, query, stdout.lock(), colored)?;
+    }
+
+    if let Some(path) = &history_path {
+        editor.save_history(path)?;
+    }
+    Ok(())
+}
+
+/// 输出交互模式中一行输入的解析结果；查询出错时只输出错误信息，不结束会话
+fn repl_answer<W: Write>(
+    address_index: &AddressIndex,
+    options: &SearchOptions,
+    query: &str,
+    mut out: W,
+    colored: bool,
+) -> io::Result<()> {
+    let results = match address_index.search(query, options) {
+        Ok(results) => results,
+        Err(e) => return writeln!(out, "  查询失败: {}", e),
+    };
+    if results.is_empty() {
+        return writeln!(out, "  未找到匹配结果");
+    }
+    let mut writer = ResultWriter::new(out, OutputFormat::Table)?;
+    if colored {
+        writer = writer.colored(address_index.tokenize(query));
+    }
+    for result in &results {
+        writer.write_row(&ResultRow {
+            query,
+            result: Some(result),
+        })?;
+    }
+    writer.finish()
+}
+
+fn main() -> anyhow::Result<()> {
+    // 日志输出到 stderr，级别可通过 T2L_LOG 或 RUST_LOG 调整（默认 info，tantivy 内部日志只输出 warn 以上）
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_env("T2L_LOG")
+                .or_else(|_| EnvFilter::try_from_default_env())
+                .unwrap_or_else(|_| EnvFilter::new("info,tantivy=warn")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    let cli = Cli::parse();
+    let mut config = match &cli.config {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    config.apply_env()?;
+    if let Some(locale) = cli.locale {
+        config.output.locale = locale;
+    }
+    if let Some(data) = cli.data {
+        config.data = Some(data);
+    }
+    if let Some(index) = cli.index {
+        config.index = Some(index);
+    }
+    let config = &config;
+    let limit_or = |limit: Option<usize>, default| limit.or(config.search.limit).unwrap_or(default);
+
+    match cli.command {
+        None => run_demo(config),
+        Some(Command::Report { log, top }) => run_report(config, &log, top),
+        Some(Command::Coverage {
+            queries,
+            top,
+            format,
+        }) => run_coverage(config, &queries, top, format),
+        Some(Command::Bench {
+            queries,
+            concurrency,
+            limit,
+            format,
+        }) => run_bench(config, &queries, concurrency, limit_or(limit, 10), format),
+        Some(Command::Dedupe { input, key, output }) => {
+            run_dedupe(config, &input, &key, output.as_deref())
+        }
+        Some(Command::Ambiguous { deep, format }) => run_ambiguous(config, deep, format),
+        Some(Command::Tune {
+            pairs,
+            output,
+            rounds,
+        }) => run_tune(config, &pairs, &output, rounds),
+        Some(Command::Diff { old, new, format }) => run_diff(&old, &new, format),
+        Some(Command::Export { output, dataset }) => {
+            let address_index = load_dataset(config, dataset.as_deref(), None)?;
+            address_index.export(&output)?;
+            Ok(())
+        }
+        Some(Command::Search {
+            query,
+            limit,
+            format,
+            dataset,
+            interpretations,
+        }) => run_search(
+            config,
+            &query,
+            limit_or(limit, 10),
+            format,
+            dataset.as_deref(),
+            interpretations,
+        ),
+        Some(Command::Batch {
+            input,
+            output,
+            format,
+            sheet,
+            column,
+            dataset,
+            codes_only,
+            columns,
+            keep_input,
+            encoding,
+            errors,
+            skip_non_address,
+            job,
+            resume,
+        }) => match (job, resume) {
+            (Some(id), _) => {
+                let (Some(input), Some(output)) = (input, output) else {
+                    anyhow::bail!("--job 需要同时指定 --input 和 --output，中断后才能恢复");
+                };
+                start_batch_job(
+                    config,
+                    BatchJob {
+                        id,
+                        input,
+                        output,
+                        format: format.map(|format| format.to_string()),
+                        sheet,
+                        column,
+                        dataset,
+                        codes_only,
+                        columns,
+                        keep_input,
+                        encoding,
+                        errors,
+                        skip_non_address,
+                        done: 0,
+                        output_len: 0,
+                        errors_len: 0,
+                    },
+                )
+            }
+            (None, Some(id)) => run_batch_job(config, BatchJob::load(config.jobs_dir(), &id)?),
+            (None, None) => run_batch(
+                config,
+                read_batch_input(
+                    input.as_deref(),
+                    sheet.as_deref(),
+                    &column,
+                    keep_input,
+                    encoding,
+                )?,
+                BatchOutput {
+                    path: output.as_deref(),
+                    format,
+                    codes_only,
+                    columns: parse_batch_columns(columns.as_deref())?,
+                    errors,
+                    skip_non_address,
+                },
+                dataset.as_deref(),
+                None,
+            ),
+        },
+        Some(Command::Repl { limit }) => run_repl(config, limit_or(limit, 5)),
+        Some(Command::Serve {
+            unix,
+            tcp,
+            watch,
+            workers,
+        }) => run_serve(config, unix.as_deref(), tcp.as_deref(), watch, workers),
+        #[cfg(feature = "kafka")]
+        Some(Command::Consume {
+            brokers,
+            topic,
+            out_topic,
+            group,
+        }) => run_consume(config, brokers, &topic, &out_topic, group),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repl_index() -> AddressIndex {
+        let mut index = AddressIndex::builder().build().unwrap();
+        let record = AddressRecord {
+            province: "北京市".to_string(),
+            city: "北京市".to_string(),
+            district: "朝阳区".to_string(),
+            county: String::new(),
+            address_code: "110105000000".to_string(),
+            level: AdminLevel::District,
+            id: None,
+            pid: None,
+            population: None,
+            location: None,
+            extra: Default::default(),
+            short_names: LevelNames::default(),
+            pinyin: LevelNames::default(),
+            aliases: Vec::new(),
+        };
+        index.add_documents(&[record]).unwrap();
+        index.commit().unwrap();
+        index
+    }
+
+    fn answer(index: &AddressIndex, options: &SearchOptions, query: &str) -> String {
+        let mut out = Vec::new();
+        repl_answer(index, options, query, &mut out, false).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// 在临时目录中准备迷你数据文件和查询文件，返回配置和目录
+    fn batch_fixture(name: &str) -> (Config, PathBuf) {
+        let dir = env::temp_dir().join(format!("t2l-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("areas.csv"),
+            "id,pid,deep,name,pinyin_prefix,pinyin,ext_id,ext_name\n\
+             44,0,0,广东,g,guang dong,440000000000,广东省\n\
+             4403,44,1,深圳,s,shen zhen,440300000000,深圳市\n\
+             4414,44,1,梅州,m,mei zhou,441400000000,梅州市\n\
+             441481,4414,2,兴宁,x,xing ning,441481000000,兴宁市\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("queries.txt"),
+            "兴宁市\n深圳市\n\n梅州\n不存在的地方\n广东省兴宁市\n",
+        )
+        .unwrap();
+        let config = Config {
+            data: Some(dir.join("areas.csv")),
+            jobs: Some(dir.join("jobs")),
+            ..Config::default()
+        };
+        (config, dir)
+    }
+
+    fn batch_job(dir: &Path, output: &str, done: usize, output_len: u64) -> BatchJob {
+        BatchJob {
+            id: output.replace('.', "-"),
+            input: dir.join("queries.txt"),
+            output: dir.join(output),
+            format: Some("csv".to_string()),
+            sheet: None,
+            column: "query".to_string(),
+            dataset: None,
+            codes_only: false,
+            columns: None,
+            keep_input: false,
+            encoding: InputEncoding::Auto,
+            errors: None,
+            skip_non_address: false,
+            done,
+            output_len,
+            errors_len: 0,
+        }
+    }
+
+    #[test]
+    fn resumed_batch_job_matches_an_uninterrupted_run() {
+        let (config, dir) = batch_fixture("resume");
+        run_batch_job(&config, batch_job(&dir, "full.csv", 0, 0)).unwrap();
+        let full = std::fs::read_to_string(dir.join("full.csv")).unwrap();
+        let lines: Vec<&str> = full.lines().collect();
+        assert_eq!(lines.len(), 6, "{full}");
+
+        // 检查点之前是表头和两行结果，之后是中断时写了一半的行
+        let kept: usize = lines[..3].iter().map(|line| line.len() + 1).sum();
+        std::fs::write(
+            dir.join("resumed.csv"),
+            format!("{}兴宁市,4414", &full[..kept]),
+        )
+        .unwrap();
+        let job = batch_job(&dir, "resumed.csv", 2, kept as u64);
+        job.save(config.jobs_dir()).unwrap();
+        run_batch_job(&config, job.clone()).unwrap();
+
+        let resumed = std::fs::read_to_string(dir.join("resumed.csv")).unwrap();
+        assert_eq!(resumed, full);
+        assert!(!BatchJob::path(config.jobs_dir(), &job.id).unwrap().exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn repl_query_error_is_printed_and_session_continues() {
+        let index = repl_index();
+        let options = SearchOptions::new().require_location_signal(true);
+        let failed = answer(&index, &options, "今天天气不错");
+        assert!(failed.starts_with("  查询失败: "), "{failed}");
+        let answered = answer(&index, &options, "北京市朝阳区");
+        assert!(answered.contains("110105000000"), "{answered}");
+    }
+}
@@ -0,0 +1,83 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::mem::size_of;
+use std::sync::Arc;
+
+/// 地址索引各部分占用的内存（字节），由 `AddressIndex::memory_report` 统计，用于边缘部署的容量规划
+///
+/// 除索引数据外都是按容量估算的堆内存，不含分配器开销，实际占用略高。
+/// jieba 分词词典由 tantivy-jieba 在进程内全局共享（首次分词时加载），无法单独统计，不计入。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryReport {
+    /// 索引数据（各段文件）的大小：内存索引和导入的索引文件全部保存在 RamDirectory 中，
+    /// 磁盘上的索引（`open_in_dir`）通过 mmap 按需载入，这部分只是上限
+    pub index: usize,
+    /// 区划名称词典：名称自动机、全称、层级链和简称
+    pub dictionary: usize,
+    /// 分词结果缓存
+    pub token_cache: usize,
+    /// 同义词表、提示词表、区划变更表和停用词
+    pub tables: usize,
+}
+
+impl MemoryReport {
+    /// 各部分之和
+    pub fn total(&self) -> usize {
+        self.index + self.dictionary + self.token_cache + self.tables
+    }
+}
+
+/// 估算值占用的堆内存（不含值本身的大小）
+pub(crate) trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+impl HeapSize for usize {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize, const N: usize> HeapSize for [T; N] {
+    fn heap_size(&self) -> usize {
+        self.iter().map(HeapSize::heap_size).sum()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Arc<[T]> {
+    fn heap_size(&self) -> usize {
+        // 引用计数的两个计数器和切片本身
+        2 * size_of::<usize>()
+            + self.len() * size_of::<T>()
+            + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for HashMap<K, V> {
+    fn heap_size(&self) -> usize {
+        // hashbrown 每个槽位另有 1 字节控制位
+        self.capacity() * (size_of::<(K, V)>() + 1)
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for HashSet<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * (size_of::<T>() + 1) + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
@@ -0,0 +1,633 @@
+use crate::address::{AddressResult, AdminLevel};
+use crate::locale::Locale;
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use unicode_width::UnicodeWidthStr;
+
+/// 结果输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// 带标签的单行文本（`AddressResult::localized` 的格式）
+    #[default]
+    Text,
+    /// 每行一个 JSON 对象（JSON Lines）
+    Json,
+    /// 带表头的 CSV
+    Csv,
+    /// 按列对齐的表格
+    Table,
+    /// Parquet 列式文件（需要启用 `parquet` 功能）
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl OutputFormat {
+    /// 根据输出文件扩展名推断格式，无法识别时返回 None
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "json" | "jsonl" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "txt" => Some(Self::Text),
+            #[cfg(feature = "parquet")]
+            "parquet" => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "table" => Ok(Self::Table),
+            #[cfg(feature = "parquet")]
+            "parquet" => Ok(Self::Parquet),
+            #[cfg(not(feature = "parquet"))]
+            "parquet" => Err("输出 Parquet 需要启用 parquet 功能".to_string()),
+            other => Err(format!(
+                "未知的输出格式: {}（可选 text/json/csv/table）",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Text => "text",
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Table => "table",
+            #[cfg(feature = "parquet")]
+            Self::Parquet => "parquet",
+        })
+    }
+}
+
+/// 默认的输出列，CSV 表头和表格表头共用
+const COLUMNS: [&str; 7] = [
+    "query",
+    "address_code",
+    "province",
+    "city",
+    "district",
+    "county",
+    "score",
+];
+
+/// 可以输出的结果字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnField {
+    /// 原始查询
+    Query,
+    /// 地址编码，也可写作 `code`
+    AddressCode,
+    Province,
+    City,
+    District,
+    County,
+    /// 各级全称拼接的完整地址，也可写作 `address`
+    FullAddress,
+    /// 解析到的层级（province / city / district / county），也可写作 `granularity`
+    Level,
+    /// 得分
+    Score,
+}
+
+impl ColumnField {
+    /// 字段的标准名称，也是未重命名时的列名
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::AddressCode => "address_code",
+            Self::Province => "province",
+            Self::City => "city",
+            Self::District => "district",
+            Self::County => "county",
+            Self::FullAddress => "full_address",
+            Self::Level => "level",
+            Self::Score => "score",
+        }
+    }
+}
+
+impl FromStr for ColumnField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "query" => Ok(Self::Query),
+            "address_code" | "code" => Ok(Self::AddressCode),
+            "province" => Ok(Self::Province),
+            "city" => Ok(Self::City),
+            "district" => Ok(Self::District),
+            "county" => Ok(Self::County),
+            "full_address" | "address" => Ok(Self::FullAddress),
+            "level" | "granularity" => Ok(Self::Level),
+            "score" => Ok(Self::Score),
+            other => Err(format!(
+                "未知的输出列: {}（可选 query/code/province/city/district/county/full_address/level/score）",
+                other
+            )),
+        }
+    }
+}
+
+/// 一个输出列：结果字段及其列名
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub field: ColumnField,
+    pub name: String,
+}
+
+/// 解析 `字段` 或 `字段:列名`，如 `code:district_code`
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, name) = match s.split_once(':') {
+            Some((field, name)) => (field.parse::<ColumnField>()?, name.trim()),
+            None => (s.parse::<ColumnField>()?, ""),
+        };
+        Ok(Self {
+            field,
+            name: if name.is_empty() {
+                field.name().to_string()
+            } else {
+                name.to_string()
+            },
+        })
+    }
+}
+
+/// 解析逗号分隔的输出列定义，如 `province,city,code:district_code`
+pub fn parse_columns(spec: &str) -> Result<Vec<Column>, String> {
+    let columns = spec
+        .split(',')
+        .filter(|column| !column.trim().is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<Column>, _>>()?;
+    if columns.is_empty() {
+        return Err("至少需要一个输出列".to_string());
+    }
+    Ok(columns)
+}
+
+/// 默认的输出列：`COLUMNS`
+fn default_columns() -> Vec<Column> {
+    COLUMNS
+        .iter()
+        .map(|name| name.parse().expect("默认列名有效"))
+        .collect()
+}
+
+/// 一行输出：查询及其解析结果（未找到时为 None）
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultRow<'a> {
+    pub query: &'a str,
+    pub result: Option<&'a AddressResult>,
+}
+
+impl ResultRow<'_> {
+    /// 某个字段的值，未解析时为 None（查询除外）
+    fn value(&self, field: ColumnField) -> Option<String> {
+        if field == ColumnField::Query {
+            return Some(self.query.to_string());
+        }
+        let result = self.result?;
+        Some(match field {
+            ColumnField::Query => unreachable!(),
+            ColumnField::AddressCode => result.address_code.clone(),
+            ColumnField::Province => result.province.clone(),
+            ColumnField::City => result.city.clone(),
+            ColumnField::District => result.district.clone(),
+            ColumnField::County => result.county.clone(),
+            ColumnField::FullAddress => result.full_address.clone(),
+            ColumnField::Level => ColumnField::from(result.granularity).name().to_string(),
+            ColumnField::Score => format!("{:.3}", result.score),
+        })
+    }
+
+    /// 按列展开为字符串，未解析时除查询外为空
+    fn cells(&self, columns: &[Column]) -> Vec<String> {
+        columns
+            .iter()
+            .map(|column| self.value(column.field).unwrap_or_default())
+            .collect()
+    }
+}
+
+impl From<AdminLevel> for ColumnField {
+    fn from(level: AdminLevel) -> Self {
+        match level {
+            AdminLevel::Province => Self::Province,
+            AdminLevel::City => Self::City,
+            AdminLevel::District => Self::District,
+            AdminLevel::County => Self::County,
+        }
+    }
+}
+
+/// 指定了输出列或保留输入各列时 JSON 格式的一行：按列的顺序输出的对象
+struct JsonRow<'a> {
+    input_header: &'a [String],
+    input: &'a [String],
+    columns: &'a [Column],
+    row: &'a ResultRow<'a>,
+}
+
+impl Serialize for JsonRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        for (name, value) in self.input_header.iter().zip(self.input) {
+            map.serialize_entry(name, value)?;
+        }
+        for column in self.columns {
+            match (column.field, self.row.result) {
+                (ColumnField::Score, Some(result)) => {
+                    map.serialize_entry(&column.name, &result.score)?
+                }
+                (field, _) => map.serialize_entry(&column.name, &self.row.value(field))?,
+            }
+        }
+        map.end()
+    }
+}
+
+/// 按指定格式逐行写出结果
+///
+/// 表格格式需要知道所有行的宽度、Parquet 需要按列写出，因此会缓存到
+/// `finish` 时统一输出，其余格式逐行直接写出。
+pub struct ResultWriter<W: Write> {
+    sink: Sink<W>,
+    /// 彩色表格中需要高亮的查询词，为 None 时输出纯文本
+    highlight: Option<Vec<String>>,
+    /// 文本格式的标签语言
+    locale: Locale,
+    /// 输出的结果列，默认为 `COLUMNS`
+    columns: Vec<Column>,
+    /// 是否通过 `columns` 指定了结果列，未指定时 JSON 格式输出完整结果
+    custom_columns: bool,
+    /// 保留在结果列之前的输入表格的列名
+    input_header: Vec<String>,
+}
+
+/// 缓存到 `finish` 时输出的一行：输入的各列、结果各列的值和得分
+type BufferedRow = (Vec<String>, Vec<Option<String>>, Option<f32>);
+
+enum Sink<W: Write> {
+    Text(W),
+    Json(W),
+    /// CSV 写入器，以及是否还需要写表头（表头在写第一行前按最终的列名写出）
+    Csv(Box<csv::Writer<W>>, bool),
+    Table(W, Vec<BufferedRow>),
+    #[cfg(feature = "parquet")]
+    Parquet(W, Vec<BufferedRow>),
+}
+
+impl<W: Write> ResultWriter<W> {
+    pub fn new(out: W, format: OutputFormat) -> io::Result<Self> {
+        Self::with_header(out, format, true)
+    }
+
+    /// 续写已有的输出（如恢复中断的批量任务），与 `new` 相同但 CSV 不再写表头
+    pub fn append(out: W, format: OutputFormat) -> io::Result<Self> {
+        Self::with_header(out, format, false)
+    }
+
+    fn with_header(out: W, format: OutputFormat, header: bool) -> io::Result<Self> {
+        let sink = match format {
+            OutputFormat::Text => Sink::Text(out),
+            OutputFormat::Json => Sink::Json(out),
+            OutputFormat::Csv => Sink::Csv(Box::new(csv::Writer::from_writer(out)), header),
+            OutputFormat::Table => Sink::Table(out, Vec::new()),
+            #[cfg(feature = "parquet")]
+            OutputFormat::Parquet => Sink::Parquet(out, Vec::new()),
+        };
+        Ok(Self {
+            sink,
+            highlight: None,
+            locale: Locale::default(),
+            columns: default_columns(),
+            custom_columns: false,
+            input_header: Vec::new(),
+        })
+    }
+
+    /// 文本格式使用的标签语言，默认简体中文
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// 表格格式使用彩色输出：层级列按颜色区分，得分附带条形图，
+    /// 并高亮与查询词匹配的部分。只应在输出到终端时开启。
+    pub fn colored(mut self, tokens: Vec<String>) -> Self {
+        self.highlight = Some(tokens);
+        self
+    }
+
+    /// 选择并重命名输出的结果列（见 `parse_columns`），文本格式不受影响
+    pub fn columns(mut self, columns: Vec<Column>) -> Self {
+        self.columns = columns;
+        self.custom_columns = true;
+        self
+    }
+
+    /// 在结果列之前保留输入表格的各列，之后用 `write_row_with_input` 写出每行的原值
+    pub fn input_header(mut self, header: Vec<String>) -> Self {
+        self.input_header = header;
+        self
+    }
+
+    /// 写出一行
+    pub fn write_row(&mut self, row: &ResultRow<'_>) -> io::Result<()> {
+        self.write_row_with_input(&[], row)
+    }
+
+    /// 写出一行，`input` 为该行在输入表格中的原值，与 `input_header` 对应
+    pub fn write_row_with_input(
+        &mut self,
+        input: &[String],
+        row: &ResultRow<'_>,
+    ) -> io::Result<()> {
+        self.write_header()?;
+        let columns = &self.columns;
+        match &mut self.sink {
+            Sink::Text(out) => match row.result {
+                Some(result) => writeln!(out, "{}", result.localized(self.locale)),
+                None => writeln!(out, "{}", self.locale.not_found(row.query)),
+            },
+            Sink::Json(out) => {
+                if !self.custom_columns && self.input_header.is_empty() {
+                    serde_json::to_writer(&mut *out, row)?;
+                } else {
+                    let object = JsonRow {
+                        input_header: &self.input_header,
+                        input,
+                        columns,
+                        row,
+                    };
+                    serde_json::to_writer(&mut *out, &object)?;
+                }
+                writeln!(out)
+            }
+            Sink::Csv(writer, _) => {
+                let record = input.iter().cloned().chain(row.cells(columns));
+                Ok(writer.write_record(record)?)
+            }
+            Sink::Table(_, rows) => {
+                let values = columns.iter().map(|c| row.value(c.field)).collect();
+                rows.push((input.to_vec(), values, row.result.map(|r| r.score)));
+                Ok(())
+            }
+            #[cfg(feature = "parquet")]
+            Sink::Parquet(_, rows) => {
+                let values = columns.iter().map(|c| row.value(c.field)).collect();
+                rows.push((input.to_vec(), values, row.result.map(|r| r.score)));
+                Ok(())
+            }
+        }
+    }
+
+    /// 所有列名：输入表格的列在前，结果列在后
+    fn header(&self) -> Vec<String> {
+        self.input_header
+            .iter()
+            .chain(self.columns.iter().map(|column| &column.name))
+            .cloned()
+            .collect()
+    }
+
+    /// CSV 格式在写第一行前写出表头
+    fn write_header(&mut self) -> io::Result<()> {
+        let header = self.header();
+        if let Sink::Csv(writer, pending @ true) = &mut self.sink {
+            writer.write_record(header)?;
+            *pending = false;
+        }
+        Ok(())
+    }
+
+    /// 刷新已写出的行；表格和 Parquet 格式在 `finish` 前不写出任何内容
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.write_header()?;
+        match &mut self.sink {
+            Sink::Text(out) | Sink::Json(out) => out.flush(),
+            Sink::Csv(writer, _) => writer.flush(),
+            Sink::Table(..) => Ok(()),
+            #[cfg(feature = "parquet")]
+            Sink::Parquet(..) => Ok(()),
+        }
+    }
+
+    /// 结束输出，写出缓存的表格并刷新
+    pub fn finish(mut self) -> io::Result<()> {
+        self.write_header()?;
+        let header = self.header();
+        let columns = self.columns;
+        let input_len = self.input_header.len();
+        match self.sink {
+            Sink::Text(mut out) | Sink::Json(mut out) => out.flush(),
+            Sink::Csv(mut writer, _) => writer.flush(),
+            #[cfg(feature = "parquet")]
+            Sink::Parquet(mut out, rows) => {
+                out.write_all(&parquet_bytes(&header, input_len, &columns, &rows)?)?;
+                out.flush()
+            }
+            Sink::Table(mut out, rows) => {
+                let rows: Vec<(Vec<String>, Option<f32>)> = rows
+                    .into_iter()
+                    .map(|(input, values, score)| {
+                        let cells = input
+                            .into_iter()
+                            .chain(values.into_iter().map(Option::unwrap_or_default))
+                            .collect();
+                        (cells, score)
+                    })
+                    .collect();
+                let mut widths = vec![0usize; header.len()];
+                for row in std::iter::once(&header).chain(rows.iter().map(|(row, _)| row)) {
+                    for (width, cell) in widths.iter_mut().zip(row) {
+                        *width = (*width).max(cell.width());
+                    }
+                }
+
+                let Some(tokens) = self.highlight else {
+                    for row in std::iter::once(&header).chain(rows.iter().map(|(row, _)| row)) {
+                        let line = row
+                            .iter()
+                            .zip(&widths)
+                            .map(|(cell, &width)| pad(cell, width))
+                            .collect::<Vec<_>>()
+                            .join("  ");
+                        writeln!(out, "{}", line.trim_end())?;
+                    }
+                    return out.flush();
+                };
+
+                let header_line = header
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, &width)| pad(cell, width))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                writeln!(out, "{}{}{}", BOLD, header_line.trim_end(), RESET)?;
+
+                // 省市区县列按层级着色，输入表格的列不着色
+                let colors: Vec<Option<&str>> = std::iter::repeat_n(None, input_len)
+                    .chain(columns.iter().map(|column| level_color(column.field)))
+                    .collect();
+                let max_score = rows
+                    .iter()
+                    .filter_map(|(_, score)| *score)
+                    .fold(0.0f32, f32::max);
+                for (row, score) in &rows {
+                    let mut cells = Vec::with_capacity(row.len());
+                    for ((cell, &width), color) in row.iter().zip(&widths).zip(&colors) {
+                        let padding = " ".repeat(width.saturating_sub(cell.width()));
+                        let cell = match color {
+                            // 省市区县列：按层级着色并高亮匹配的查询词
+                            Some(color) => {
+                                format!("{}{}{}", color, highlight_tokens(cell, &tokens), RESET)
+                            }
+                            None => cell.clone(),
+                        };
+                        cells.push(format!("{}{}", cell, padding));
+                    }
+                    if let Some(score) = score {
+                        cells.push(score_bar(*score, max_score));
+                    }
+                    writeln!(out, "{}", cells.join("  ").trim_end())?;
+                }
+                out.flush()
+            }
+        }
+    }
+}
+
+/// 省市区县列的颜色，其他列不着色
+fn level_color(field: ColumnField) -> Option<&'static str> {
+    match field {
+        ColumnField::Province => Some(LEVEL_COLORS[0]),
+        ColumnField::City => Some(LEVEL_COLORS[1]),
+        ColumnField::District => Some(LEVEL_COLORS[2]),
+        ColumnField::County => Some(LEVEL_COLORS[3]),
+        _ => None,
+    }
+}
+
+/// 把缓存的结果行编码为 Parquet 文件内容
+///
+/// 得分写为 float32 列；输入表格的列和查询列不为 null，未解析的行其余各列均为 null。
+#[cfg(feature = "parquet")]
+fn parquet_bytes(
+    header: &[String],
+    input_len: usize,
+    columns: &[Column],
+    rows: &[BufferedRow],
+) -> io::Result<Vec<u8>> {
+    use arrow_array::{ArrayRef, Float32Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+
+    let mut fields = Vec::with_capacity(header.len());
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(header.len());
+    for (i, name) in header[..input_len].iter().enumerate() {
+        let values: StringArray = rows
+            .iter()
+            .map(|(input, _, _)| Some(input.get(i).map_or("", String::as_str)))
+            .collect();
+        fields.push(Field::new(name, DataType::Utf8, false));
+        arrays.push(Arc::new(values));
+    }
+    for (i, column) in columns.iter().enumerate() {
+        if column.field == ColumnField::Score {
+            let values: Float32Array = rows.iter().map(|(_, _, score)| *score).collect();
+            fields.push(Field::new(&column.name, DataType::Float32, true));
+            arrays.push(Arc::new(values));
+            continue;
+        }
+        let values: StringArray = rows
+            .iter()
+            .map(|(_, values, _)| values[i].as_deref())
+            .collect();
+        let nullable = column.field != ColumnField::Query;
+        fields.push(Field::new(&column.name, DataType::Utf8, nullable));
+        arrays.push(Arc::new(values));
+    }
+
+    let batch =
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).map_err(io::Error::other)?;
+    let mut writer =
+        ArrowWriter::try_new(Vec::new(), batch.schema(), None).map_err(io::Error::other)?;
+    writer.write(&batch).map_err(io::Error::other)?;
+    writer.into_inner().map_err(io::Error::other)
+}
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+/// 高亮开始 / 结束：加粗并加下划线，保留当前前景色
+const HIGHLIGHT_ON: &str = "\x1b[1;4m";
+const HIGHLIGHT_OFF: &str = "\x1b[22;24m";
+/// 省、市、区县、乡镇街道列的颜色：青、绿、黄、品红
+const LEVEL_COLORS: [&str; 4] = ["\x1b[36m", "\x1b[32m", "\x1b[33m", "\x1b[35m"];
+/// 得分条形图的最大长度（字符数）
+const SCORE_BAR_WIDTH: usize = 10;
+
+/// 高亮文本中出现的查询词，较长的词优先匹配
+fn highlight_tokens(text: &str, tokens: &[String]) -> String {
+    let mut tokens: Vec<&str> = tokens
+        .iter()
+        .map(String::as_str)
+        .filter(|t| !t.is_empty())
+        .collect();
+    tokens.sort_by_key(|t| std::cmp::Reverse(t.len()));
+
+    let mut result = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some(token) = tokens.iter().find(|t| rest.starts_with(**t)) {
+            result.push_str(HIGHLIGHT_ON);
+            result.push_str(token);
+            result.push_str(HIGHLIGHT_OFF);
+            rest = &rest[token.len()..];
+        } else {
+            let ch = rest.chars().next().unwrap_or_default();
+            result.push(ch);
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+    result
+}
+
+/// 相对于最高得分的条形图
+fn score_bar(score: f32, max_score: f32) -> String {
+    let filled = if max_score > 0.0 {
+        ((score / max_score) * SCORE_BAR_WIDTH as f32).round() as usize
+    } else {
+        0
+    };
+    format!(
+        "\x1b[34m{}\x1b[2m{}{}",
+        "█".repeat(filled.min(SCORE_BAR_WIDTH)),
+        "░".repeat(SCORE_BAR_WIDTH.saturating_sub(filled)),
+        RESET
+    )
+}
+
+/// 按显示宽度（中文字符占两列）在右侧补齐空格
+fn pad(cell: &str, width: usize) -> String {
+    let padding = width.saturating_sub(cell.width());
+    format!("{}{}", cell, " ".repeat(padding))
+}
@@ -0,0 +1,55 @@
+use crate::address_index::{AddressIndex, AddressResult};
+use ::polars::prelude::*;
+
+/// 解析结果结构体的字段名，与 `resolve_series` 返回的 Struct 字段一一对应
+const FIELDS: [&str; 5] = ["province", "city", "district", "county", "address_code"];
+
+/// 把一列地址字符串解析为 Struct 类型的 Series
+///
+/// 结构体字段依次为 province、city、district、county、address_code（String）
+/// 和 score（Float32），可以用 `DataFrame::unnest` 展开为多列。
+/// 输入为 null、空字符串或未找到匹配时，该行各字段均为 null。
+pub fn resolve_series(index: &AddressIndex, addresses: &Series) -> PolarsResult<Series> {
+    let addresses = addresses.str()?;
+    let mut results = Vec::with_capacity(addresses.len());
+    for address in addresses.iter() {
+        let result = match address.map(str::trim) {
+            Some(query) if !query.is_empty() => index
+                .resolve(query)
+                .map_err(|e| polars_err!(ComputeError: "地址解析失败: {}", e))?,
+            _ => None,
+        };
+        results.push(result);
+    }
+
+    let levels: [fn(&AddressResult) -> &str; 5] = [
+        |r| &r.province,
+        |r| &r.city,
+        |r| &r.district,
+        |r| &r.county,
+        |r| &r.address_code,
+    ];
+    let mut fields: Vec<Series> = FIELDS
+        .iter()
+        .zip(levels)
+        .map(|(name, level)| {
+            StringChunked::from_iter_options(
+                (*name).into(),
+                results.iter().map(|r| r.as_ref().map(level)),
+            )
+            .into_series()
+        })
+        .collect();
+    fields.push(
+        Float32Chunked::from_iter_options(
+            "score".into(),
+            results.iter().map(|r| r.as_ref().map(|r| r.score)),
+        )
+        .into_series(),
+    );
+
+    Ok(
+        StructChunked::from_series(addresses.name().clone(), results.len(), fields.iter())?
+            .into_series(),
+    )
+}
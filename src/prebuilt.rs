@@ -0,0 +1,37 @@
+use crate::address_index::{AddressIndex, AddressResult};
+use crate::error::Result;
+use std::sync::OnceLock;
+use tracing::warn;
+
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/index_loader.rs"));
+}
+
+pub(crate) use embedded::load_index_directory;
+
+/// 进程内共享的内置索引，首次使用时加载
+static INDEX: OnceLock<AddressIndex> = OnceLock::new();
+
+/// 编译时嵌入的内置索引，首次调用时加载（需读取全部文档重建名称词典），之后直接返回同一个实例
+///
+/// 使用默认的构建选项，需要自定义输出选项时请用 `AddressIndexBuilder::prebuilt` 自行创建。
+pub fn index() -> Result<&'static AddressIndex> {
+    if let Some(index) = INDEX.get() {
+        return Ok(index);
+    }
+    // 并发首次调用时可能各自加载一次，只保留先完成的那个
+    let index = AddressIndex::builder().prebuilt()?;
+    Ok(INDEX.get_or_init(|| index))
+}
+
+/// 用内置索引解析地址，返回最佳结果，如 `resolve("广东兴宁")`
+///
+/// 适合不想管理 `AddressIndex` 实例的简单脚本，解析流程与 `AddressIndex::resolve` 相同。
+/// 加载索引或查询出错时记录日志并返回 None，需要区分错误时请使用 `index()`。
+pub fn resolve(query: &str) -> Option<AddressResult> {
+    let result = index().and_then(|index| index.resolve(query));
+    result.unwrap_or_else(|e| {
+        warn!("解析失败: {}", e);
+        None
+    })
+}
@@ -0,0 +1,158 @@
+use crate::address_index::AdminLevel;
+use std::fmt;
+use std::ops::Not;
+
+/// 组合式查询条件，由 `AddressIndex::search_q` 编译为 tantivy 的 BooleanQuery 执行
+///
+/// 用 `Q::level(..)`、`Q::name_contains(..)` 等创建单个条件，再用 `and`、`or` 和 `!` 组合，
+/// 调用方不需要直接接触 tantivy 的类型：
+///
+/// ```ignore
+/// let q = Q::level(AdminLevel::District)
+///     .and(Q::name_contains("兴宁"))
+///     .and(Q::code_prefix("44").or(Q::code_prefix("45")));
+/// let results = index.search_q(&q, &SearchOptions::new())?;
+/// ```
+///
+/// 只有 `Q::text` 按全文搜索打分，其余条件命中时得分相同，结果再按人口和编码排序。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Q {
+    /// 本级层级为指定层级
+    Level(AdminLevel),
+    /// 本级全称等于给定名称
+    Name(String),
+    /// 本级全称包含给定文本
+    NameContains(String),
+    /// 本级全称匹配正则表达式，语法和锚定规则同 `AddressIndex::search_regex`
+    NameRegex(String),
+    /// 区划编码等于给定编码
+    Code(String),
+    /// 区划编码以给定前缀开头，如 `"4414"` 限定在梅州市内
+    CodePrefix(String),
+    /// 全文搜索，分词和打分与 `AddressIndex::search` 相同
+    Text(String),
+    /// 额外字段的值等于给定值，字段须以索引方式注册
+    Extra(String, String),
+    /// 所有条件都满足
+    And(Vec<Q>),
+    /// 至少满足一个条件
+    Or(Vec<Q>),
+    /// 不满足该条件
+    Not(Box<Q>),
+}
+
+impl Q {
+    /// 本级层级为指定层级
+    pub fn level(level: AdminLevel) -> Self {
+        Self::Level(level)
+    }
+
+    /// 本级全称等于给定名称
+    pub fn name(name: impl Into<String>) -> Self {
+        Self::Name(name.into())
+    }
+
+    /// 本级全称包含给定文本
+    pub fn name_contains(text: impl Into<String>) -> Self {
+        Self::NameContains(text.into())
+    }
+
+    /// 本级全称匹配正则表达式
+    pub fn name_regex(pattern: impl Into<String>) -> Self {
+        Self::NameRegex(pattern.into())
+    }
+
+    /// 区划编码等于给定编码
+    pub fn code(code: impl Into<String>) -> Self {
+        Self::Code(code.into())
+    }
+
+    /// 区划编码以给定前缀开头
+    pub fn code_prefix(prefix: impl Into<String>) -> Self {
+        Self::CodePrefix(prefix.into())
+    }
+
+    /// 全文搜索
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text(text.into())
+    }
+
+    /// 额外字段的值等于给定值
+    pub fn extra(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::Extra(name.into(), value.into())
+    }
+
+    /// 与另一个条件同时满足，连续的 `and` 合并为一层
+    pub fn and(self, other: Q) -> Self {
+        match self {
+            Self::And(mut clauses) => {
+                clauses.push(other);
+                Self::And(clauses)
+            }
+            q => Self::And(vec![q, other]),
+        }
+    }
+
+    /// 满足该条件或另一个条件，连续的 `or` 合并为一层
+    pub fn or(self, other: Q) -> Self {
+        match self {
+            Self::Or(mut clauses) => {
+                clauses.push(other);
+                Self::Or(clauses)
+            }
+            q => Self::Or(vec![q, other]),
+        }
+    }
+}
+
+impl Not for Q {
+    type Output = Q;
+
+    fn not(self) -> Q {
+        match self {
+            Self::Not(q) => *q,
+            q => Self::Not(Box::new(q)),
+        }
+    }
+}
+
+/// 可读的条件表达式，用于查询日志，如 `(level:District AND name~"兴宁")`
+impl fmt::Display for Q {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, clauses: &[Q], op: &str| {
+            f.write_str("(")?;
+            for (i, clause) in clauses.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {} ", op)?;
+                }
+                write!(f, "{}", clause)?;
+            }
+            f.write_str(")")
+        };
+        match self {
+            Self::Level(level) => write!(f, "level:{:?}", level),
+            Self::Name(name) => write!(f, "name:{:?}", name),
+            Self::NameContains(text) => write!(f, "name~{:?}", text),
+            Self::NameRegex(pattern) => write!(f, "name:/{}/", pattern),
+            Self::Code(code) => write!(f, "code:{}", code),
+            Self::CodePrefix(prefix) => write!(f, "code:{}*", prefix),
+            Self::Text(text) => write!(f, "{:?}", text),
+            Self::Extra(name, value) => write!(f, "{}:{:?}", name, value),
+            Self::And(clauses) => join(f, clauses, "AND"),
+            Self::Or(clauses) => join(f, clauses, "OR"),
+            Self::Not(q) => write!(f, "NOT {}", q),
+        }
+    }
+}
+
+/// 转义正则表达式中的特殊字符，使文本按字面匹配
+pub(crate) fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,121 @@
+use crate::address::AddressResult;
+use crate::error::{Result, Text2LocationError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// 一次查询的日志记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryLogEntry {
+    /// 原始查询字符串
+    pub query: String,
+    /// 预处理（分词、去重）后的查询
+    pub normalized_query: String,
+    /// 得分最高结果的地址编码
+    pub top_code: Option<String>,
+    /// 得分最高结果的完整层级，用空格分隔
+    pub top_address: Option<String>,
+    /// 得分最高结果的得分
+    pub score: Option<f32>,
+    /// 返回的结果数量
+    pub hits: usize,
+    /// 查询耗时（微秒）
+    pub latency_us: u64,
+}
+
+impl QueryLogEntry {
+    pub(crate) fn new(
+        query: &str,
+        normalized_query: &str,
+        top: Option<&AddressResult>,
+        hits: usize,
+        latency_us: u64,
+    ) -> Self {
+        Self {
+            query: query.to_string(),
+            normalized_query: normalized_query.to_string(),
+            top_code: top.map(|r| r.address_code.clone()),
+            top_address: top.map(|r| {
+                [&r.province, &r.city, &r.district, &r.county]
+                    .iter()
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }),
+            score: top.map(|r| r.score),
+            hits,
+            latency_us,
+        }
+    }
+}
+
+/// 查询日志：把每次查询的记录写入 JSONL 文件或交给回调处理
+#[derive(Clone)]
+pub enum QueryLog {
+    /// 每条记录一行 JSON，追加写入文件
+    Jsonl(Arc<Mutex<LineWriter<File>>>),
+    /// 由调用方处理记录
+    Callback(Arc<dyn Fn(&QueryLogEntry) + Send + Sync>),
+}
+
+impl QueryLog {
+    /// 追加写入 JSONL 文件，文件不存在时创建
+    pub fn jsonl<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(Text2LocationError::data_load)?;
+        Ok(Self::Jsonl(Arc::new(Mutex::new(LineWriter::new(file)))))
+    }
+
+    /// 使用回调处理每条记录
+    pub fn callback(f: impl Fn(&QueryLogEntry) + Send + Sync + 'static) -> Self {
+        Self::Callback(Arc::new(f))
+    }
+
+    /// 记录一次查询，写入失败只输出警告，不影响查询本身
+    pub(crate) fn record(&self, entry: &QueryLogEntry) {
+        match self {
+            Self::Jsonl(writer) => {
+                let line = match serde_json::to_string(entry) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        warn!("查询日志序列化失败: {}", e);
+                        return;
+                    }
+                };
+                let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+                if let Err(e) = writeln!(writer, "{}", line) {
+                    warn!("查询日志写入失败: {}", e);
+                }
+            }
+            Self::Callback(f) => f(entry),
+        }
+    }
+}
+
+impl fmt::Debug for QueryLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Jsonl(_) => f.write_str("QueryLog::Jsonl"),
+            Self::Callback(_) => f.write_str("QueryLog::Callback"),
+        }
+    }
+}
+
+/// 读取 JSONL 查询日志，跳过空行
+pub fn read_query_log<P: AsRef<Path>>(path: P) -> Result<Vec<QueryLogEntry>> {
+    let content = std::fs::read_to_string(path).map_err(Text2LocationError::data_load)?;
+    let mut entries = Vec::new();
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        let entry = serde_json::from_str(line).map_err(Text2LocationError::data_load)?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
@@ -0,0 +1,105 @@
+use crate::address_index::{AddressIndex, AddressResult, SearchOptions};
+use crate::analytics::{classify_results, UnresolvedCause};
+use crate::error::Result;
+#[cfg(feature = "fst")]
+use crate::fst::RegionFst;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// 得到解析结果的策略，按开销从低到高排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// FST 名称表的最长匹配
+    Fst,
+    /// 索引内区划名称词典（Aho-Corasick）的精确匹配
+    Exact,
+    /// tantivy 分词打分搜索
+    Search,
+}
+
+/// 一次解析的结果及其来源
+#[derive(Debug, Clone, Serialize)]
+pub struct Resolution {
+    pub result: AddressResult,
+    pub strategy: Strategy,
+    /// 精确匹配总是可信；搜索结果按 `classify_results` 判断，不可信时记录可能原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doubt: Option<UnresolvedCause>,
+}
+
+impl Resolution {
+    /// 结果是否可信
+    pub fn is_confident(&self) -> bool {
+        self.doubt.is_none()
+    }
+}
+
+/// 组合多种解析后端的统一入口，按开销从低到高依次尝试，直到得到可信的结果
+///
+/// 依次尝试：FST 名称表（启用 `fst` 功能并设置时）、索引内的名称词典精确匹配、
+/// tantivy 分词打分搜索。前两种只在名称能唯一确定区划时给出结果；
+/// 搜索总会给出最佳结果，是否可信由 `Resolution::doubt` 标明。
+#[derive(Default)]
+pub struct Resolver {
+    index: Option<Arc<AddressIndex>>,
+    #[cfg(feature = "fst")]
+    fst: Option<RegionFst>,
+}
+
+impl Resolver {
+    /// 创建不含任何后端的解析器，需要再设置索引或 FST 名称表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用 tantivy 地址索引（精确匹配和搜索两个阶段）
+    pub fn index(mut self, index: Arc<AddressIndex>) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// 使用 FST 名称表作为第一阶段
+    #[cfg(feature = "fst")]
+    pub fn fst(mut self, fst: RegionFst) -> Self {
+        self.fst = Some(fst);
+        self
+    }
+
+    /// 解析地址，所有后端都没有结果时返回 None
+    pub fn resolve(&self, query: &str) -> Result<Option<Resolution>> {
+        #[cfg(feature = "fst")]
+        if let Some(record) = self.fst.as_ref().and_then(|fst| fst.resolve(query)) {
+            // 有索引时从索引读取，以带上额外字段和区划变更信息
+            let result = match &self.index {
+                Some(index) => index.get(&record.address_code)?,
+                None => None,
+            };
+            let result = result.unwrap_or_else(|| AddressResult::from(record));
+            return Ok(Some(Resolution {
+                result,
+                strategy: Strategy::Fst,
+                doubt: None,
+            }));
+        }
+
+        let Some(index) = &self.index else {
+            return Ok(None);
+        };
+        if let Some(result) = index.resolve_exact(query)? {
+            return Ok(Some(Resolution {
+                result,
+                strategy: Strategy::Exact,
+                doubt: None,
+            }));
+        }
+
+        let results = index.search(query, &SearchOptions::new().limit(5).dedup(true))?;
+        let doubt = classify_results(index, query, &results);
+        Ok(results.into_iter().next().map(|result| Resolution {
+            result,
+            strategy: Strategy::Search,
+            doubt,
+        }))
+    }
+}
@@ -0,0 +1,828 @@
+use crate::address_index::{AddressQuery, AddressResult, SearchOptions};
+use crate::dataset::Datasets;
+use crate::error::{Result, Text2LocationError};
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{self, TcpListener, TcpStream, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// 一次请求的响应，序列化为 `{"results": [...]}`、`{"error": "..."}`
+/// 或批量请求的 `{"batch": [...]}`（每个元素为对应查询的响应）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Response {
+    Results(Vec<AddressResult>),
+    Error(String),
+    Batch(Vec<Response>),
+}
+
+/// 一个批量请求最多包含的查询数
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+/// 批量请求：`{"batch": ["兴宁市", {"query": "朝阳区", "limit": 3}]}`
+///
+/// 每个元素为地址文本或完整的 `AddressQuery`，逐个解析。每个元素的响应为有结果的 `results`、
+/// 未找到时为空的 `results`，或 `error`；无效或查询出错的元素只使对应的响应为错误，不影响整批。
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchRequest {
+    batch: Vec<serde_json::Value>,
+}
+
+/// 处理一条 JSON 请求：请求体为 `AddressQuery` 或批量请求，解析失败或查询出错时返回错误响应
+///
+/// 请求中的 `dataset` 选择查询的数据集，未指定时使用默认数据集。
+pub fn handle_json_request(datasets: &Datasets, line: &str) -> Response {
+    let request: serde_json::Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return Response::Error(format!("无效的请求: {}", e)),
+    };
+    if request.get("batch").is_some() {
+        return match serde_json::from_value(request) {
+            Ok(BatchRequest { batch }) => handle_batch(datasets, batch),
+            Err(e) => Response::Error(format!("无效的批量请求: {}", e)),
+        };
+    }
+    match serde_json::from_value(request) {
+        Ok(query) => search_response(datasets, &query),
+        Err(e) => Response::Error(format!("无效的请求: {}", e)),
+    }
+}
+
+/// 并发执行批量请求中的查询，相同的查询只执行一次，响应与请求中的查询一一对应
+///
+/// 批量请求减少了逐条请求的往返开销，适合通过服务清洗文件的客户端。
+fn handle_batch(datasets: &Datasets, batch: Vec<serde_json::Value>) -> Response {
+    if batch.len() > MAX_BATCH_SIZE {
+        return Response::Error(format!(
+            "批量请求最多包含 {} 个查询，实际 {} 个",
+            MAX_BATCH_SIZE,
+            batch.len()
+        ));
+    }
+    let queries: Vec<std::result::Result<AddressQuery, String>> = batch
+        .into_iter()
+        .map(|item| match item {
+            serde_json::Value::String(text) => Ok(AddressQuery::new(text)),
+            item => serde_json::from_value(item).map_err(|e| format!("无效的请求: {}", e)),
+        })
+        .collect();
+
+    // 相同的查询映射到同一个槽位
+    let mut distinct: Vec<&AddressQuery> = Vec::new();
+    let mut positions: HashMap<&AddressQuery, usize> = HashMap::new();
+    let slots: Vec<std::result::Result<usize, &String>> = queries
+        .iter()
+        .map(|query| {
+            let query = query.as_ref()?;
+            Ok(*positions.entry(query).or_insert_with(|| {
+                distinct.push(query);
+                distinct.len() - 1
+            }))
+        })
+        .collect();
+    let responses: Vec<Response> = distinct
+        .par_iter()
+        .map(|query| search_response(datasets, query))
+        .collect();
+
+    Response::Batch(
+        slots
+            .into_iter()
+            .map(|slot| match slot {
+                Ok(slot) => responses[slot].clone(),
+                Err(e) => Response::Error(e.clone()),
+            })
+            .collect(),
+    )
+}
+
+/// 执行一个结构化查询并包装为响应
+fn search_response(datasets: &Datasets, query: &AddressQuery) -> Response {
+    match datasets.search_query(query) {
+        Ok(results) => Response::Results(results),
+        Err(e) => Response::Error(e.to_string()),
+    }
+}
+
+/// 处理一行纯文本查询，在默认数据集上使用默认搜索选项
+pub fn handle_text_request(datasets: &Datasets, line: &str) -> Response {
+    match datasets
+        .default_index()
+        .search(line.trim(), &SearchOptions::default())
+    {
+        Ok(results) => Response::Results(results),
+        Err(e) => Response::Error(e.to_string()),
+    }
+}
+
+/// 在一个连接上处理 JSON Lines 协议：每行一个请求，每行一个响应，空行忽略
+///
+/// 请求在搜索线程池中并发执行，响应按请求的顺序写回。
+pub fn serve_json_lines<R: Read, W: Write + Send>(
+    datasets: &Arc<Datasets>,
+    pool: &ThreadPool,
+    input: R,
+    output: W,
+) -> io::Result<()> {
+    serve_with(datasets, pool, input, output, handle_json_request)
+}
+
+/// 在一个连接上处理纯文本行协议：每行一个地址，每行一个 JSON 响应，空行忽略
+///
+/// 请求在搜索线程池中并发执行，响应按请求的顺序写回。
+pub fn serve_lines<R: Read, W: Write + Send>(
+    datasets: &Arc<Datasets>,
+    pool: &ThreadPool,
+    input: R,
+    output: W,
+) -> io::Result<()> {
+    serve_with(datasets, pool, input, output, handle_text_request)
+}
+
+/// 创建执行查询的搜索线程池，`workers` 为 None 时线程数按 CPU 数量
+///
+/// 连接线程只负责读写，查询都分派到这个线程池，
+/// 因此同时执行的查询数不随连接数增长，单个连接上连续发送的请求也能并发执行。
+pub fn search_pool(workers: Option<usize>) -> io::Result<Arc<ThreadPool>> {
+    ThreadPoolBuilder::new()
+        .num_threads(workers.unwrap_or(0))
+        .thread_name(|i| format!("t2l-search-{}", i))
+        .build()
+        .map(Arc::new)
+        .map_err(io::Error::other)
+}
+
+/// 单个连接上已读取但尚未写回响应的请求数上限，达到上限时暂停读取
+const MAX_IN_FLIGHT: usize = 256;
+
+fn serve_with<R: Read, W: Write + Send>(
+    datasets: &Arc<Datasets>,
+    pool: &ThreadPool,
+    input: R,
+    mut output: W,
+    handle: impl Fn(&Datasets, &str) -> Response + Send + Sync + 'static,
+) -> io::Result<()> {
+    let handle = Arc::new(handle);
+    let (results, responses) = mpsc::channel::<(usize, Response)>();
+    // 读取一个请求占用一个名额，写回响应后归还
+    let (acquire, release) = mpsc::sync_channel::<()>(MAX_IN_FLIGHT);
+
+    thread::scope(|scope| {
+        // 写线程：响应可能乱序完成，按请求序号排好后依次写回
+        let writer = scope.spawn(move || -> io::Result<()> {
+            let mut pending = BTreeMap::new();
+            let mut next = 0;
+            for (seq, response) in responses {
+                pending.insert(seq, response);
+                while let Some(response) = pending.remove(&next) {
+                    serde_json::to_writer(&mut output, &response)?;
+                    output.write_all(b"\n")?;
+                    output.flush()?;
+                    next += 1;
+                    let _ = release.recv();
+                }
+            }
+            Ok(())
+        });
+
+        let mut seq = 0;
+        let mut read = Ok(());
+        for line in BufReader::new(input).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    read = Err(e);
+                    break;
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            // 写线程已退出（连接断开）时停止读取
+            if acquire.send(()).is_err() {
+                break;
+            }
+            let datasets = Arc::clone(datasets);
+            let handle = Arc::clone(&handle);
+            let results = results.clone();
+            pool.spawn(move || {
+                // 查询中的 panic 只让这个请求返回错误，不拖垮线程池和写线程的排序
+                let response = panic::catch_unwind(AssertUnwindSafe(|| handle(&datasets, &line)))
+                    .unwrap_or_else(|_| Response::Error("查询执行异常".to_string()));
+                let _ = results.send((seq, response));
+            });
+            seq += 1;
+        }
+        drop(results);
+
+        let written = writer
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("写线程异常退出")));
+        read.and(written)
+    })
+}
+
+/// 停止服务的句柄，可以在其他线程（如信号处理线程）中调用 `trigger`
+///
+/// 触发后 `serve_tcp` / `serve_unix` 不再接受新连接；已建立的连接停止读取新请求，
+/// 已读取的请求在搜索线程池中执行完、响应写回后断开，所有连接结束后监听函数返回。
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    inner: Arc<ShutdownState>,
+}
+
+#[derive(Default)]
+struct ShutdownState {
+    triggered: AtomicBool,
+    /// 连接编号 -> 使该连接停止读取的回调
+    connections: Mutex<HashMap<usize, Box<dyn Fn() + Send>>>,
+    next_id: AtomicUsize,
+}
+
+impl Shutdown {
+    /// 开始停止服务，重复调用无效果
+    pub fn trigger(&self) {
+        let connections = self.inner.connections.lock().unwrap();
+        if self.inner.triggered.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        for stop in connections.values() {
+            stop();
+        }
+    }
+
+    /// 是否已经开始停止服务
+    pub fn is_triggered(&self) -> bool {
+        self.inner.triggered.load(Ordering::SeqCst)
+    }
+
+    /// 登记一个连接，停止服务时调用 `stop`（已经开始停止时立即调用），返回注销用的编号
+    fn register(&self, stop: Box<dyn Fn() + Send>) -> usize {
+        let mut connections = self.inner.connections.lock().unwrap();
+        if self.is_triggered() {
+            stop();
+        }
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        connections.insert(id, stop);
+        id
+    }
+
+    fn unregister(&self, id: usize) {
+        self.inner.connections.lock().unwrap().remove(&id);
+    }
+}
+
+impl fmt::Debug for Shutdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Shutdown")
+            .field("triggered", &self.is_triggered())
+            .field("connections", &self.inner.connections.lock().unwrap().len())
+            .finish()
+    }
+}
+
+/// 服务使用的连接：TCP 或 Unix 域套接字
+trait Connection: Read + Write + Send + Sized + 'static {
+    fn try_clone(&self) -> io::Result<Self>;
+
+    /// 恢复为阻塞读写（非阻塞的监听套接字接受的连接在部分平台上也是非阻塞的）
+    fn set_blocking(&self) -> io::Result<()>;
+
+    /// 关闭读方向，阻塞中的读取随即返回 EOF，写方向不受影响
+    fn shutdown_read(&self) -> io::Result<()>;
+}
+
+impl Connection for TcpStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        TcpStream::try_clone(self)
+    }
+
+    fn set_blocking(&self) -> io::Result<()> {
+        self.set_nonblocking(false)
+    }
+
+    fn shutdown_read(&self) -> io::Result<()> {
+        self.shutdown(net::Shutdown::Read)
+    }
+}
+
+#[cfg(unix)]
+impl Connection for std::os::unix::net::UnixStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        std::os::unix::net::UnixStream::try_clone(self)
+    }
+
+    fn set_blocking(&self) -> io::Result<()> {
+        self.set_nonblocking(false)
+    }
+
+    fn shutdown_read(&self) -> io::Result<()> {
+        self.shutdown(net::Shutdown::Read)
+    }
+}
+
+/// 处理一个连接的函数，参数为读、写两端（同一连接的两个句柄）
+trait Serve<S>:
+    Fn(&Arc<Datasets>, &ThreadPool, S, S) -> io::Result<()> + Clone + Send + 'static
+{
+}
+
+impl<S, F> Serve<S> for F where
+    F: Fn(&Arc<Datasets>, &ThreadPool, S, S) -> io::Result<()> + Clone + Send + 'static
+{
+}
+
+/// 停止服务前检查是否有新连接的间隔
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 接受连接出错后重试间隔的上限，间隔从 `ACCEPT_POLL_INTERVAL` 起逐次翻倍
+const ACCEPT_MAX_BACKOFF: Duration = Duration::from_secs(2);
+
+/// 接受连接直到 `shutdown` 触发，然后等待已建立的连接处理完已读取的请求
+///
+/// `accept` 来自非阻塞的监听套接字，没有新连接时返回 `WouldBlock`。
+/// 其他错误（如文件描述符耗尽的 EMFILE / ENFILE）记录日志后等待一段时间再重试，
+/// 连续出错时等待时间逐次翻倍，避免空转占满 CPU。
+fn accept_until<S: Connection>(
+    datasets: &Arc<Datasets>,
+    pool: &Arc<ThreadPool>,
+    shutdown: &Shutdown,
+    mut accept: impl FnMut() -> io::Result<S>,
+    serve: impl Serve<S>,
+) {
+    let mut connections: Vec<JoinHandle<()>> = Vec::new();
+    let mut backoff = ACCEPT_POLL_INTERVAL;
+    while !shutdown.is_triggered() {
+        match accept() {
+            Ok(stream) => {
+                backoff = ACCEPT_POLL_INTERVAL;
+                connections.retain(|connection| !connection.is_finished());
+                let serve = serve.clone();
+                connections.extend(spawn_connection(datasets, pool, shutdown, stream, serve));
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            Err(e) => {
+                warn!("接受连接失败: {}，{:?} 后重试", e, backoff);
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(ACCEPT_MAX_BACKOFF);
+            }
+        }
+    }
+    connections.retain(|connection| !connection.is_finished());
+    info!(
+        "停止接受新连接，等待 {} 个连接处理完已读取的请求",
+        connections.len()
+    );
+    for connection in connections {
+        let _ = connection.join();
+    }
+}
+
+/// 在新线程中处理一个连接，连接出错只记录日志
+fn spawn_connection<S: Connection>(
+    datasets: &Arc<Datasets>,
+    pool: &Arc<ThreadPool>,
+    shutdown: &Shutdown,
+    stream: S,
+    serve: impl Serve<S>,
+) -> Option<JoinHandle<()>> {
+    if let Err(e) = stream.set_blocking() {
+        warn!("设置连接为阻塞模式失败: {}", e);
+        return None;
+    }
+    let datasets = Arc::clone(datasets);
+    let pool = Arc::clone(pool);
+    let shutdown = shutdown.clone();
+    Some(thread::spawn(move || {
+        let result = serve_connection(&datasets, &pool, &shutdown, stream, serve);
+        if let Err(e) = result {
+            warn!("连接异常断开: {}", e);
+        }
+    }))
+}
+
+/// 处理一个连接，停止服务时关闭连接的读方向，使其处理完已读取的请求后结束
+fn serve_connection<S: Connection>(
+    datasets: &Arc<Datasets>,
+    pool: &ThreadPool,
+    shutdown: &Shutdown,
+    stream: S,
+    serve: impl Serve<S>,
+) -> io::Result<()> {
+    let reader = stream.try_clone()?;
+    let stopper = stream.try_clone()?;
+    let id = shutdown.register(Box::new(move || {
+        let _ = stopper.shutdown_read();
+    }));
+    let result = serve(datasets, pool, reader, stream);
+    shutdown.unregister(id);
+    result
+}
+
+/// 按客户端限流的令牌桶：每个客户端每秒补充 `per_second` 个令牌，最多积攒 `burst` 个，
+/// 每个请求消耗一个，没有令牌时请求被拒绝
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    /// 客户端 -> （剩余令牌，上次补充的时间）
+    buckets: Mutex<HashMap<String, (f64, Instant)>>,
+}
+
+/// 限流时记录的客户端数达到该值时，清理令牌已经补满的客户端
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+impl RateLimiter {
+    /// `per_second` 须为正数，`burst` 至少为 1
+    pub fn new(per_second: f64, burst: u32) -> Result<Self> {
+        if !(per_second.is_finite() && per_second > 0.0) || burst == 0 {
+            return Err(Text2LocationError::InvalidInput(format!(
+                "无效的限流设置: 每秒 {} 个请求，突发 {} 个",
+                per_second, burst
+            )));
+        }
+        Ok(Self {
+            per_second,
+            burst: f64::from(burst),
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// 客户端还有令牌时消耗一个并返回 true
+    pub fn allow(&self, client: &str) -> bool {
+        self.allow_at(client, Instant::now())
+    }
+
+    fn allow_at(&self, client: &str, now: Instant) -> bool {
+        let refill = |(tokens, updated): (f64, Instant)| {
+            let elapsed = now.saturating_duration_since(updated).as_secs_f64();
+            (tokens + elapsed * self.per_second).min(self.burst)
+        };
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| refill(*bucket) < self.burst);
+        }
+        let bucket = buckets
+            .entry(client.to_string())
+            .or_insert((self.burst, now));
+        let tokens = refill(*bucket);
+        let allowed = tokens >= 1.0;
+        *bucket = (if allowed { tokens - 1.0 } else { tokens }, now);
+        allowed
+    }
+}
+
+/// 一个 API key 的请求统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyStats {
+    /// 执行了查询的请求数
+    pub requests: u64,
+    /// 因限流被拒绝的请求数
+    pub limited: u64,
+}
+
+/// TCP 服务的访问控制，默认不做任何限制
+#[derive(Default)]
+pub struct Access {
+    rate_limiter: Option<RateLimiter>,
+    /// key -> 名称
+    api_keys: HashMap<String, String>,
+    /// 名称 -> 请求统计
+    stats: Mutex<BTreeMap<String, KeyStats>>,
+}
+
+impl Access {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按客户端限流，超出限制的请求返回错误响应，不执行查询；
+    /// 配置了 API key 时按 key 的名称限流，否则按 IP
+    pub fn rate_limit(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// 添加一个 API key；添加后每个连接须先发送 `AUTH <key>`，请求按 `name` 统计
+    pub fn api_key(mut self, name: impl Into<String>, key: impl Into<String>) -> Self {
+        let name = name.into();
+        self.stats
+            .get_mut()
+            .unwrap()
+            .insert(name.clone(), KeyStats::default());
+        self.api_keys.insert(key.into(), name);
+        self
+    }
+
+    /// 各 API key 名称的请求统计，没有配置 API key 时为空
+    pub fn stats(&self) -> BTreeMap<String, KeyStats> {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// 按连接的第一行 `AUTH <key>` 认证，返回 key 的名称；没有配置 API key 时不需要认证
+    fn authenticate(&self, line: &str) -> Option<&str> {
+        let key = line.trim().strip_prefix("AUTH ")?.trim();
+        self.api_keys.get(key).map(String::as_str)
+    }
+
+    /// 客户端的请求是否未超出限流，并记录 API key 的请求统计
+    fn admit(&self, client: &str) -> bool {
+        let admitted = self
+            .rate_limiter
+            .as_ref()
+            .is_none_or(|limiter| limiter.allow(client));
+        if let Some(stats) = self.stats.lock().unwrap().get_mut(client) {
+            if admitted {
+                stats.requests += 1;
+            } else {
+                stats.limited += 1;
+            }
+        }
+        admitted
+    }
+}
+
+impl fmt::Debug for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 只输出 key 的名称，不输出 key 本身
+        f.debug_struct("Access")
+            .field("rate_limiter", &self.rate_limiter)
+            .field("api_keys", &self.api_keys.values().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// 在 TCP 端口上提供纯文本行协议服务，每个连接一个读写线程，查询在 `pool` 中执行
+///
+/// 供无法使用 HTTP 的系统接入：每行发送一个地址，每行收到一个 JSON 响应。
+/// 配置了 API key（见 `Access::api_key`）时，连接的第一行须为 `AUTH <key>`（认证成功不返回响应），
+/// 否则收到 `{"error": "401 未授权..."}` 后连接被关闭。
+/// 设置了限流（见 `Access::rate_limit`）时，超出限制的请求收到 `{"error": "请求过于频繁..."}`，
+/// 连接保持不断开。
+/// `shutdown` 触发后停止接受连接，已建立的连接处理完已读取的请求后返回，见 `Shutdown`；
+/// 返回前按 API key 输出请求统计。
+pub fn serve_tcp<A: ToSocketAddrs>(
+    datasets: Arc<Datasets>,
+    pool: Arc<ThreadPool>,
+    addr: A,
+    access: Arc<Access>,
+    shutdown: &Shutdown,
+) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    info!("监听 TCP 地址 {}", listener.local_addr()?);
+    accept_until(
+        &datasets,
+        &pool,
+        shutdown,
+        || listener.accept().map(|(stream, _)| stream),
+        {
+            let access = Arc::clone(&access);
+            move |datasets: &Arc<Datasets>, pool: &ThreadPool, reader: TcpStream, writer| {
+                serve_tcp_connection(datasets, pool, &access, reader, writer)
+            }
+        },
+    );
+    for (name, stats) in access.stats() {
+        info!(
+            key = name,
+            requests = stats.requests,
+            limited = stats.limited,
+            "API key 请求统计"
+        );
+    }
+    Ok(())
+}
+
+/// 在一个 TCP 连接上处理纯文本行协议，按 `access` 的设置认证和限流
+fn serve_tcp_connection(
+    datasets: &Arc<Datasets>,
+    pool: &ThreadPool,
+    access: &Arc<Access>,
+    reader: TcpStream,
+    mut writer: TcpStream,
+) -> io::Result<()> {
+    let peer = reader.peer_addr()?.ip().to_string();
+    let mut reader = BufReader::new(reader);
+    let client = if access.api_keys.is_empty() {
+        peer
+    } else {
+        let mut line = String::new();
+        while line.trim().is_empty() {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+        }
+        let Some(name) = access.authenticate(&line) else {
+            warn!("客户端 {} 认证失败", peer);
+            let response = Response::Error("401 未授权: 第一行须为 AUTH <API key>".to_string());
+            serde_json::to_writer(&mut writer, &response)?;
+            return writer.write_all(b"\n");
+        };
+        name.to_string()
+    };
+    let access = Arc::clone(access);
+    serve_with(datasets, pool, reader, writer, move |datasets, line| {
+        if !access.admit(&client) {
+            return Response::Error(format!("请求过于频繁，客户端 {} 已超出限流", client));
+        }
+        handle_text_request(datasets, line)
+    })
+}
+
+/// 在 Unix 域套接字上提供 JSON Lines 服务，每个连接一个读写线程，查询在 `pool` 中执行
+///
+/// 路径上已存在、且已无服务监听的套接字文件（通常是上次未正常退出留下的）会被先删除；
+/// 仍有服务在监听时返回 `AddrInUse`，其他类型的文件不会被删除。
+/// `shutdown` 触发后停止接受连接，已建立的连接处理完已读取的请求后删除套接字文件并返回。
+#[cfg(unix)]
+pub fn serve_unix<P: AsRef<std::path::Path>>(
+    datasets: Arc<Datasets>,
+    pool: Arc<ThreadPool>,
+    path: P,
+    shutdown: &Shutdown,
+) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let path = path.as_ref();
+    if std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("套接字 {} 正被其他服务使用", path.display()),
+            ));
+        }
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    listener.set_nonblocking(true)?;
+    info!("监听 Unix 套接字 {}", path.display());
+
+    accept_until(
+        &datasets,
+        &pool,
+        shutdown,
+        || listener.accept().map(|(stream, _)| stream),
+        serve_json_lines,
+    );
+    std::fs::remove_file(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address_index::{AddressIndex, AddressRecord, AdminLevel, LevelNames};
+
+    fn test_datasets() -> Arc<Datasets> {
+        let mut index = AddressIndex::builder().build().unwrap();
+        let record = AddressRecord {
+            province: "广东省".to_string(),
+            city: "梅州市".to_string(),
+            district: "兴宁市".to_string(),
+            county: String::new(),
+            address_code: "441481000000".to_string(),
+            level: AdminLevel::District,
+            id: None,
+            pid: None,
+            population: None,
+            location: None,
+            extra: BTreeMap::new(),
+            short_names: LevelNames::default(),
+            pinyin: LevelNames::default(),
+            aliases: Vec::new(),
+        };
+        index.add_documents(&[record]).unwrap();
+        index.commit().unwrap();
+        Arc::new(Datasets::new(Arc::new(index)))
+    }
+
+    #[test]
+    fn requests_read_before_eof_are_all_answered() {
+        let datasets = test_datasets();
+        let pool = search_pool(Some(2)).unwrap();
+        let input = "兴宁市\n".repeat(300);
+        let mut output = Vec::new();
+        serve_lines(&datasets, &pool, input.as_bytes(), &mut output).unwrap();
+        let responses: Vec<&str> = std::str::from_utf8(&output).unwrap().lines().collect();
+        assert_eq!(responses.len(), 300);
+        assert!(responses.iter().all(|r| r.contains("441481000000")));
+    }
+
+    #[test]
+    fn rate_limiter_refills_per_client() {
+        let limiter = RateLimiter::new(2.0, 3).unwrap();
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.allow_at("10.0.0.1", start));
+        }
+        assert!(!limiter.allow_at("10.0.0.1", start));
+        // 其他客户端有自己的令牌桶
+        assert!(limiter.allow_at("10.0.0.2", start));
+        // 半秒补充一个令牌
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.allow_at("10.0.0.1", later));
+        assert!(!limiter.allow_at("10.0.0.1", later));
+        assert!(RateLimiter::new(0.0, 1).is_err());
+        assert!(RateLimiter::new(1.0, 0).is_err());
+    }
+
+    #[test]
+    fn api_keys_authenticate_and_count_requests_per_name() {
+        let access = Access::new()
+            .rate_limit(RateLimiter::new(1.0, 1).unwrap())
+            .api_key("logistics", "secret");
+        assert_eq!(access.authenticate("AUTH secret\r\n"), Some("logistics"));
+        assert_eq!(access.authenticate("AUTH wrong"), None);
+        assert_eq!(access.authenticate("兴宁市"), None);
+
+        assert!(access.admit("logistics"));
+        assert!(!access.admit("logistics"));
+        assert_eq!(
+            access.stats()["logistics"],
+            KeyStats {
+                requests: 1,
+                limited: 1
+            }
+        );
+    }
+
+    #[test]
+    fn accept_errors_back_off_instead_of_spinning() {
+        let datasets = test_datasets();
+        let pool = search_pool(Some(1)).unwrap();
+        let shutdown = Shutdown::default();
+        let stopper = shutdown.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(500));
+            stopper.trigger();
+        });
+        let mut attempts = 0;
+        accept_until::<TcpStream>(
+            &datasets,
+            &pool,
+            &shutdown,
+            || {
+                attempts += 1;
+                Err(io::Error::other("Too many open files"))
+            },
+            serve_lines,
+        );
+        // 100、200、400 毫秒的等待后即已超过 500 毫秒
+        assert!(attempts <= 4, "accept 被调用了 {attempts} 次");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shutdown_closes_connections_and_removes_socket() {
+        use std::os::unix::net::UnixStream;
+
+        let datasets = test_datasets();
+        let pool = search_pool(Some(1)).unwrap();
+        let path = std::env::temp_dir().join(format!("t2l-shutdown-{}.sock", std::process::id()));
+        let shutdown = Shutdown::default();
+        let server = {
+            let (path, shutdown) = (path.clone(), shutdown.clone());
+            thread::spawn(move || serve_unix(datasets, pool, path, &shutdown))
+        };
+
+        let started = Instant::now();
+        let mut stream = loop {
+            match UnixStream::connect(&path) {
+                Ok(stream) => break stream,
+                Err(_) if started.elapsed() < Duration::from_secs(10) => {
+                    thread::sleep(Duration::from_millis(10))
+                }
+                Err(e) => panic!("无法连接到服务: {e}"),
+            }
+        };
+        stream
+            .write_all("{\"query\": \"兴宁市\"}\n".as_bytes())
+            .unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut response = String::new();
+        reader.read_line(&mut response).unwrap();
+        assert!(response.contains("441481000000"), "{response}");
+
+        shutdown.trigger();
+        // 读方向关闭后连接处理完已读取的请求即结束，客户端读到 EOF
+        response.clear();
+        assert_eq!(reader.read_line(&mut response).unwrap(), 0);
+        server.join().unwrap().unwrap();
+        assert!(!path.exists());
+        assert!(UnixStream::connect(&path).is_err());
+    }
+}
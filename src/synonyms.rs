@@ -0,0 +1,69 @@
+use crate::memory::HeapSize;
+use aho_corasick::{AhoCorasick, MatchKind};
+use std::collections::HashMap;
+
+/// 同义词表：把地标、俗称等（如“虹桥”“浦东机场”）扩展为行政区划名称
+///
+/// 扩展在分词前进行：命中的词条保留在原文中，对应的区划名称追加到文本末尾，
+/// 使只提到地标的查询也能匹配到区划，同时不影响原文中其他词的匹配。
+#[derive(Debug, Default)]
+pub(crate) struct Synonyms {
+    /// 所有词条构成的自动机，为 None 表示同义词表为空
+    automaton: Option<AhoCorasick>,
+    /// 模式编号 -> 扩展出的区划名称
+    expansions: Vec<String>,
+}
+
+impl HeapSize for Synonyms {
+    fn heap_size(&self) -> usize {
+        self.automaton.as_ref().map_or(0, AhoCorasick::memory_usage) + self.expansions.heap_size()
+    }
+}
+
+impl Synonyms {
+    /// 由词条 -> 区划名称的映射构建，空的词条被忽略
+    pub(crate) fn new(synonyms: &HashMap<String, String>) -> Self {
+        let (terms, expansions): (Vec<&str>, Vec<String>) = synonyms
+            .iter()
+            .filter(|(term, _)| !term.is_empty())
+            .map(|(term, expansion)| (term.as_str(), expansion.clone()))
+            .unzip();
+        let automaton = if terms.is_empty() {
+            None
+        } else {
+            AhoCorasick::builder()
+                .match_kind(MatchKind::LeftmostLongest)
+                .build(&terms)
+                .ok()
+        };
+        Self {
+            automaton,
+            expansions,
+        }
+    }
+
+    /// 在文本末尾追加命中的词条对应的区划名称，按最长匹配、互不重叠，同一名称只追加一次
+    pub(crate) fn expand(&self, text: &str) -> String {
+        let mut expanded = text.to_string();
+        for expansion in self.matches(text) {
+            expanded.push(' ');
+            expanded.push_str(expansion);
+        }
+        expanded
+    }
+
+    /// 文本中命中的词条对应的区划名称，按最长匹配、互不重叠，按出现顺序去重
+    pub(crate) fn matches(&self, text: &str) -> Vec<&str> {
+        let Some(automaton) = &self.automaton else {
+            return Vec::new();
+        };
+        let mut matched: Vec<&str> = Vec::new();
+        for m in automaton.find_iter(text) {
+            let expansion = self.expansions[m.pattern().as_usize()].as_str();
+            if !matched.contains(&expansion) {
+                matched.push(expansion);
+            }
+        }
+        matched
+    }
+}
@@ -0,0 +1,151 @@
+use crate::address_index::{
+    haversine_km, AddressIndex, AddressRecord, AddressResult, AdminLevel, LevelNames,
+};
+use crate::error::Result;
+use crate::geocoder::Geocoder;
+use std::collections::{BTreeMap, HashMap};
+
+/// 迷你数据集中的一行：本级层级、全称链、简称链、编码、id、上级 id、人口、中心点坐标
+type Row = (
+    AdminLevel,
+    [&'static str; 3],
+    [&'static str; 3],
+    &'static str,
+    u64,
+    u64,
+    u64,
+    (f64, f64),
+);
+
+/// 北京朝阳区与辽宁朝阳市同名，用于测试有歧义的输入
+#[rustfmt::skip]
+const MINI_ROWS: [Row; 11] = [
+    (AdminLevel::Province, ["北京市", "", ""], ["北京", "", ""], "110000000000", 11, 0, 21_890_000, (39.90, 116.40)),
+    (AdminLevel::City, ["北京市", "北京市", ""], ["北京", "北京", ""], "110100000000", 1101, 11, 21_890_000, (39.90, 116.40)),
+    (AdminLevel::District, ["北京市", "北京市", "朝阳区"], ["北京", "北京", "朝阳"], "110105000000", 110105, 1101, 3_450_000, (39.92, 116.44)),
+    (AdminLevel::District, ["北京市", "北京市", "海淀区"], ["北京", "北京", "海淀"], "110108000000", 110108, 1101, 3_130_000, (39.96, 116.30)),
+    (AdminLevel::Province, ["辽宁省", "", ""], ["辽宁", "", ""], "210000000000", 21, 0, 42_590_000, (41.80, 123.43)),
+    (AdminLevel::City, ["辽宁省", "朝阳市", ""], ["辽宁", "朝阳", ""], "211300000000", 2113, 21, 2_870_000, (41.57, 120.45)),
+    (AdminLevel::Province, ["广东省", "", ""], ["广东", "", ""], "440000000000", 44, 0, 126_010_000, (23.13, 113.26)),
+    (AdminLevel::City, ["广东省", "深圳市", ""], ["广东", "深圳", ""], "440300000000", 4403, 44, 17_560_000, (22.54, 114.06)),
+    (AdminLevel::District, ["广东省", "深圳市", "南山区"], ["广东", "深圳", "南山"], "440305000000", 440305, 4403, 1_800_000, (22.53, 113.93)),
+    (AdminLevel::City, ["广东省", "梅州市", ""], ["广东", "梅州", ""], "441400000000", 4414, 44, 3_870_000, (24.29, 116.12)),
+    (AdminLevel::District, ["广东省", "梅州市", "兴宁市"], ["广东", "梅州", "兴宁"], "441481000000", 441481, 4414, 1_170_000, (24.14, 115.73)),
+];
+
+/// 供测试使用的迷你数据集：北京、辽宁、广东三省下的 11 个区划，带简称、人口和坐标
+pub fn mini_dataset() -> Vec<AddressRecord> {
+    MINI_ROWS
+        .iter()
+        .map(
+            |&(level, names, short_names, code, id, pid, population, location)| AddressRecord {
+                province: names[0].to_string(),
+                city: names[1].to_string(),
+                district: names[2].to_string(),
+                county: String::new(),
+                address_code: code.to_string(),
+                level,
+                id: Some(id),
+                pid: Some(pid),
+                population: Some(population),
+                location: Some(location),
+                extra: BTreeMap::new(),
+                short_names: LevelNames {
+                    province: short_names[0].to_string(),
+                    city: short_names[1].to_string(),
+                    district: short_names[2].to_string(),
+                    county: String::new(),
+                },
+                pinyin: LevelNames::default(),
+                aliases: Vec::new(),
+            },
+        )
+        .collect()
+}
+
+/// 由迷你数据集建立的内存索引，可用于测试依赖 `AddressIndex` 本身的代码
+pub fn mini_index() -> Result<AddressIndex> {
+    let mut index = AddressIndex::builder().build()?;
+    index.add_documents(&mini_dataset())?;
+    index.commit()?;
+    Ok(index)
+}
+
+/// 确定性的 `Geocoder` 实现，不建立任何索引，供下游 crate 测试其集成代码
+///
+/// 优先返回 `respond` 设置的固定应答；否则在 `records` 中找本级全称或简称出现在查询中的区划，
+/// 取层级链上被提到的层级最多的一个，其次取层级最深、编码最小的。`reverse` 返回中心点最近的区划。
+#[derive(Debug, Clone, Default)]
+pub struct MockGeocoder {
+    responses: HashMap<String, Option<AddressResult>>,
+    records: Vec<AddressRecord>,
+}
+
+impl MockGeocoder {
+    /// 创建没有任何区划和应答的 MockGeocoder，所有查询都返回 None
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用迷你数据集作为区划
+    pub fn mini() -> Self {
+        Self::new().records(mini_dataset())
+    }
+
+    /// 设置参与匹配和逆地理编码的区划
+    pub fn records(mut self, records: Vec<AddressRecord>) -> Self {
+        self.records = records;
+        self
+    }
+
+    /// 对指定查询固定返回给定结果（None 表示未找到），优先于按区划匹配
+    pub fn respond(mut self, query: impl Into<String>, result: Option<AddressResult>) -> Self {
+        self.responses.insert(query.into(), result);
+        self
+    }
+}
+
+impl Geocoder for MockGeocoder {
+    fn resolve(&self, query: &str) -> Result<Option<AddressResult>> {
+        if let Some(result) = self.responses.get(query) {
+            return Ok(result.clone());
+        }
+        let mentioned = |name: &str, short_name: &str| {
+            (!name.is_empty() && query.contains(name))
+                || (!short_name.is_empty() && query.contains(short_name))
+        };
+        let matched = self
+            .records
+            .iter()
+            .filter(|record| mentioned(record.name(), record.short_name()))
+            .map(|record| {
+                let names = [
+                    (&record.province, &record.short_names.province),
+                    (&record.city, &record.short_names.city),
+                    (&record.district, &record.short_names.district),
+                    (&record.county, &record.short_names.county),
+                ];
+                let mentions = names
+                    .iter()
+                    .filter(|(name, short_name)| mentioned(name, short_name))
+                    .count();
+                (mentions, record)
+            })
+            .max_by(|(a, x), (b, y)| {
+                a.cmp(b)
+                    .then_with(|| x.level.cmp(&y.level))
+                    .then_with(|| y.address_code.cmp(&x.address_code))
+            })
+            .map(|(_, record)| record);
+        Ok(matched.map(AddressResult::from))
+    }
+
+    fn reverse(&self, lat: f64, lng: f64) -> Result<Option<AddressResult>> {
+        let nearest = self
+            .records
+            .iter()
+            .filter_map(|record| Some((haversine_km((lat, lng), record.location?), record)))
+            .min_by(|(a, _), (b, _)| a.total_cmp(b));
+        Ok(nearest.map(|(_, record)| AddressResult::from(record)))
+    }
+}
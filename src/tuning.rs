@@ -0,0 +1,167 @@
+use crate::address_index::{AddressIndex, RankingWeights, SearchOptions};
+use crate::error::{Result, Text2LocationError};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tantivy::Score;
+use tracing::info;
+
+/// 评估时每条查询取的候选数量，期望的区划不在其中时按未命中计
+const EVALUATION_DEPTH: usize = 10;
+
+/// 取出 `RankingWeights` 中某个权重的可变引用
+type Accessor = fn(&mut RankingWeights) -> &mut Score;
+
+/// 参与拟合的权重及其候选取值
+///
+/// 标注的查询不带位置和层级偏好，因此 `bias`、`level_preference` 不参与拟合，保持原值。
+/// 层级系数的候选值都大于 0，避免某一层级的区划被完全排除。
+const PARAMETERS: [(&str, Accessor, &[Score]); 6] = [
+    (
+        "population",
+        |w| &mut w.population,
+        &[0.0, 0.02, 0.05, 0.1, 0.2, 0.5],
+    ),
+    ("hint", |w| &mut w.hint, &[0.0, 0.5, 1.0, 2.0, 4.0, 8.0]),
+    ("levels.province", |w| &mut w.levels[0], LEVEL_CANDIDATES),
+    ("levels.city", |w| &mut w.levels[1], LEVEL_CANDIDATES),
+    ("levels.district", |w| &mut w.levels[2], LEVEL_CANDIDATES),
+    ("levels.county", |w| &mut w.levels[3], LEVEL_CANDIDATES),
+];
+
+/// 层级系数的候选取值
+const LEVEL_CANDIDATES: &[Score] = &[0.5, 0.75, 0.9, 1.0, 1.1, 1.25, 1.5];
+
+/// 一组权重在标注查询上的效果
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Evaluation {
+    /// 标注查询的数量
+    pub total: usize,
+    /// 第一个结果即为期望区划的查询数
+    pub top1: usize,
+    /// 期望区划排名倒数的平均值（Mean Reciprocal Rank），不在前 10 个结果中按 0 计
+    pub mrr: f64,
+}
+
+impl Evaluation {
+    /// 第一个结果的准确率，没有查询时为 0
+    pub fn accuracy(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.top1 as f64 / self.total as f64
+    }
+
+    /// 是否优于另一组结果：先比较准确率，相同时比较 MRR
+    fn is_better_than(&self, other: &Evaluation) -> bool {
+        (self.top1, self.mrr) > (other.top1, other.mrr)
+    }
+}
+
+/// `tune` 的结果：拟合出的权重及拟合前后的效果
+#[derive(Debug, Clone, Serialize)]
+pub struct TuningReport {
+    pub weights: RankingWeights,
+    /// 使用初始权重的效果
+    pub baseline: Evaluation,
+    /// 使用拟合出的权重的效果
+    pub tuned: Evaluation,
+}
+
+/// 用索引当前的排序权重执行标注的查询（查询，期望的区划编码），统计排序效果
+pub fn evaluate(index: &AddressIndex, pairs: &[(String, String)]) -> Result<Evaluation> {
+    let options = SearchOptions::new().limit(EVALUATION_DEPTH);
+    let ranks = pairs
+        .par_iter()
+        .map(|(query, expected)| {
+            let results = index.search(query, &options)?;
+            Ok(results.iter().position(|r| &r.address_code == expected))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Evaluation {
+        total: pairs.len(),
+        top1: ranks.iter().filter(|&&rank| rank == Some(0)).count(),
+        mrr: ranks
+            .iter()
+            .map(|rank| rank.map_or(0.0, |rank| 1.0 / (rank + 1) as f64))
+            .sum::<f64>()
+            / pairs.len().max(1) as f64,
+    })
+}
+
+/// 从标注的查询中拟合排序权重（坐标搜索）
+///
+/// 从索引当前的权重开始，每轮依次对每个权重尝试所有候选取值、固定其他权重，
+/// 保留使准确率（相同时为 MRR）最高的取值；某一轮没有任何改进或达到 `rounds` 轮后停止。
+/// 结束时索引使用拟合出的权重，可用 `save_weights` 保存后由配置 `search.weights` 加载。
+pub fn tune(
+    index: &mut AddressIndex,
+    pairs: &[(String, String)],
+    rounds: usize,
+) -> Result<TuningReport> {
+    let baseline = evaluate(index, pairs)?;
+    let mut weights = index.weights();
+    let mut best = baseline;
+    info!(
+        "初始权重：准确率 {:.3}，MRR {:.3}",
+        best.accuracy(),
+        best.mrr
+    );
+
+    for round in 1..=rounds {
+        let mut improved = false;
+        for (name, parameter, candidates) in PARAMETERS {
+            let mut best_value = *parameter(&mut weights);
+            for &value in candidates {
+                if value == best_value {
+                    continue;
+                }
+                let mut candidate = weights;
+                *parameter(&mut candidate) = value;
+                index.set_weights(candidate);
+                let evaluation = evaluate(index, pairs)?;
+                if evaluation.is_better_than(&best) {
+                    info!(
+                        "第 {} 轮：{} = {}，准确率 {:.3}，MRR {:.3}",
+                        round,
+                        name,
+                        value,
+                        evaluation.accuracy(),
+                        evaluation.mrr
+                    );
+                    best = evaluation;
+                    best_value = value;
+                    improved = true;
+                }
+            }
+            *parameter(&mut weights) = best_value;
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    index.set_weights(weights);
+    Ok(TuningReport {
+        weights,
+        baseline,
+        tuned: best,
+    })
+}
+
+/// 从 TOML 文件加载排序权重，文件中没有的权重使用默认值
+pub fn load_weights<P: AsRef<Path>>(path: P) -> Result<RankingWeights> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(Text2LocationError::data_load)?;
+    toml::from_str(&content).map_err(|e| {
+        Text2LocationError::InvalidInput(format!("权重文件 {} 无效: {}", path.display(), e))
+    })
+}
+
+/// 把排序权重保存为 TOML 文件
+pub fn save_weights<P: AsRef<Path>>(path: P, weights: &RankingWeights) -> Result<()> {
+    let content = toml::to_string(weights)
+        .map_err(|e| Text2LocationError::InvalidInput(format!("无法序列化权重: {}", e)))?;
+    fs::write(path, content).map_err(Text2LocationError::data_load)
+}
@@ -0,0 +1,97 @@
+use crate::address_index::{AddressIndex, AddressResult, AdminLevel, SearchOptions};
+use crate::analytics::{classify_results, UnresolvedCause, CONFLICT_RATIO};
+use crate::error::Result;
+use serde::Serialize;
+use std::fmt;
+
+/// 地址校验的结果，由 `validate` 返回
+///
+/// 表单后端可以据此给出具体的提示（Display 为面向用户的中文说明），而不只是“未找到”。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationOutcome {
+    /// 地址能确定一个区划
+    Valid(Box<AddressResult>),
+    /// 地址对应多个得分接近、互不相容的区划（如只写了“朝阳区”），需要补充上级区划
+    AmbiguousCandidates(usize),
+    /// 地址中的区划名称互相矛盾（如“广东省朝阳区”），列出矛盾的层级
+    Conflict(Vec<AdminLevel>),
+    /// 地址中没有任何已知的区划名称（如“人民医院”），不会给出偶然命中的结果
+    NoLocationSignal,
+    /// 地址中有区划名称，但不能据此确定区划
+    NotFound,
+}
+
+impl ValidationOutcome {
+    /// 是否为有效地址
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid(_))
+    }
+}
+
+impl fmt::Display for ValidationOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Valid(result) => write!(f, "地址有效：{}", result.full_address),
+            Self::AmbiguousCandidates(n) => {
+                write!(f, "地址可能指 {} 个不同的区划，请补充省、市等上级区划", n)
+            }
+            Self::Conflict(levels) => {
+                let levels: Vec<&str> = levels.iter().map(|&level| level_label(level)).collect();
+                write!(f, "地址中的{}名称互相矛盾，请检查", levels.join("、"))
+            }
+            Self::NoLocationSignal => f.write_str("地址中没有省、市、区县等地名，请补充"),
+            Self::NotFound => f.write_str("未能识别地址中的行政区划"),
+        }
+    }
+}
+
+/// 层级在提示中的名称
+fn level_label(level: AdminLevel) -> &'static str {
+    match level {
+        AdminLevel::Province => "省级",
+        AdminLevel::City => "地级市",
+        AdminLevel::District => "区县",
+        AdminLevel::County => "乡镇街道",
+    }
+}
+
+/// 校验地址文本，说明能否确定区划以及不能确定的原因
+///
+/// 依次判断：区划名称能唯一确定区划时有效；区划全称互相矛盾时为矛盾；
+/// 没有任何已知的区划名称时为缺少地名；
+/// 否则按模糊搜索的结果判断，没有可识别的区划时为未找到，
+/// 多个不相容的解释得分接近（达到第一名的 90%）时为有歧义，其余情况取第一名为有效。
+pub fn validate(index: &AddressIndex, text: &str) -> Result<ValidationOutcome> {
+    if let Some(result) = index.resolve_exact(text)? {
+        return Ok(ValidationOutcome::Valid(Box::new(result)));
+    }
+    let levels = index.conflicting_levels(text);
+    if !levels.is_empty() {
+        return Ok(ValidationOutcome::Conflict(levels));
+    }
+    if !index.has_location_signal(text) {
+        return Ok(ValidationOutcome::NoLocationSignal);
+    }
+
+    let options = SearchOptions::new()
+        .limit(5)
+        .dedup(true)
+        .interpretations(true);
+    let results = index.search(text, &options)?;
+    let Some(top) = results.first() else {
+        return Ok(ValidationOutcome::NotFound);
+    };
+    if classify_results(index, text, &results) == Some(UnresolvedCause::UnknownName) {
+        return Ok(ValidationOutcome::NotFound);
+    }
+    // 相容的结果已合并为同一个解释，剩下的得分接近的结果互不相容
+    let candidates = results
+        .iter()
+        .filter(|r| r.score >= top.score * CONFLICT_RATIO)
+        .count();
+    if candidates > 1 {
+        return Ok(ValidationOutcome::AmbiguousCandidates(candidates));
+    }
+    Ok(ValidationOutcome::Valid(Box::new(top.clone())))
+}
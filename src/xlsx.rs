@@ -0,0 +1,64 @@
+use crate::error::{Result, Text2LocationError};
+use calamine::{open_workbook_auto, Data, Range, Reader};
+use std::path::Path;
+
+/// 读取表格（.xlsx / .xls / .ods）中某一列的所有单元格，跳过表头行
+///
+/// `sheet` 为 None 时读取第一个工作表；`column` 可以是表头名称，
+/// 也可以是从 1 开始的列号。
+pub fn read_column<P: AsRef<Path>>(
+    path: P,
+    sheet: Option<&str>,
+    column: &str,
+) -> Result<Vec<String>> {
+    let (sheet, range) = open_sheet(path, sheet)?;
+    let mut rows = range.rows();
+    let header = rows.next().unwrap_or_default();
+    let index = header
+        .iter()
+        .position(|cell| cell.to_string().trim() == column)
+        .or_else(|| column.parse::<usize>().ok().and_then(|n| n.checked_sub(1)))
+        .ok_or_else(|| {
+            Text2LocationError::InvalidInput(format!("工作表 {} 中没有列: {}", sheet, column))
+        })?;
+
+    Ok(rows
+        .map(|row| {
+            row.get(index)
+                .map(|cell| cell.to_string())
+                .unwrap_or_default()
+        })
+        .collect())
+}
+
+/// 读取表格中某一工作表的所有行，返回表头行和其余各行的单元格文本
+///
+/// `sheet` 为 None 时读取第一个工作表。
+pub fn read_table<P: AsRef<Path>>(
+    path: P,
+    sheet: Option<&str>,
+) -> Result<(Vec<String>, Vec<Vec<String>>)> {
+    let (_, range) = open_sheet(path, sheet)?;
+    let mut rows = range
+        .rows()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect::<Vec<_>>());
+    let header = rows.next().unwrap_or_default();
+    Ok((header, rows.collect()))
+}
+
+/// 打开表格中的工作表，`sheet` 为 None 时取第一个，返回工作表名称和单元格区域
+fn open_sheet<P: AsRef<Path>>(path: P, sheet: Option<&str>) -> Result<(String, Range<Data>)> {
+    let mut workbook = open_workbook_auto(path).map_err(Text2LocationError::data_load)?;
+    let sheet = match sheet {
+        Some(name) => name.to_string(),
+        None => workbook
+            .sheet_names()
+            .first()
+            .cloned()
+            .ok_or_else(|| Text2LocationError::data_load("表格中没有工作表"))?,
+    };
+    let range = workbook
+        .worksheet_range(&sheet)
+        .map_err(Text2LocationError::data_load)?;
+    Ok((sheet, range))
+}